@@ -0,0 +1,141 @@
+//! Derive macro for the `Z3Sort` trait of the [`z3`](https://docs.rs/z3/)
+//! crate, which maps Rust structs and enums to Z3 datatypes.
+//!
+//! Use it through the `derive` feature of `z3`, which re-exports it,
+//! rather than depending on this crate directly.
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{Data, DeriveInput, Error, Fields, Ident};
+
+#[proc_macro_derive(Z3Sort)]
+pub fn derive_z3_sort(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// A constructor of the datatype, together with the Rust path that
+/// builds values with it.
+struct Constructor<'a> {
+    name: String,
+    path: TokenStream2,
+    fields: &'a Fields,
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2, Error> {
+    let ty = &input.ident;
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "Z3Sort cannot be derived for generic types",
+        ));
+    }
+    let constructors: Vec<Constructor> = match input.data {
+        Data::Struct(ref data) => vec![Constructor {
+            name: ty.to_string(),
+            path: quote!(#ty),
+            fields: &data.fields,
+        }],
+        Data::Enum(ref data) => {
+            if data.variants.is_empty() {
+                return Err(Error::new_spanned(
+                    ty,
+                    "Z3Sort cannot be derived for enums without variants",
+                ));
+            }
+            data.variants
+                .iter()
+                .map(|v| {
+                    let variant = &v.ident;
+                    Constructor {
+                        name: variant.to_string(),
+                        path: quote!(#ty::#variant),
+                        fields: &v.fields,
+                    }
+                })
+                .collect()
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                ty,
+                "Z3Sort cannot be derived for unions",
+            ));
+        }
+    };
+
+    let name = ty.to_string();
+    let variants = constructors.iter().map(|c| {
+        let ctor_name = &c.name;
+        let fields = c.fields.iter().enumerate().map(|(i, f)| {
+            let accessor = match f.ident {
+                Some(ref ident) => ident.to_string(),
+                None => format!("{}_{}", c.name, i),
+            };
+            let field_ty = &f.ty;
+            quote!((#accessor, <#field_ty as ::z3::Z3Sort>::z3_field(ctx, group)))
+        });
+        quote!(.variant(#ctor_name, vec![#(#fields),*]))
+    });
+
+    let arms = constructors.iter().enumerate().map(|(i, c)| {
+        let path = &c.path;
+        let bindings: Vec<Ident> = (0..c.fields.len())
+            .map(|i| Ident::new(&format!("field{}", i), proc_macro2::Span::call_site()))
+            .collect();
+        let pattern = match *c.fields {
+            Fields::Named(ref fields) => {
+                let names = fields.named.iter().map(|f| &f.ident);
+                quote!(#path { #(#names: ref #bindings),* })
+            }
+            Fields::Unnamed(_) => quote!(#path(#(ref #bindings),*)),
+            Fields::Unit => quote!(#path),
+        };
+        quote! {
+            #pattern => {
+                let args: Vec<::z3::Ast> = vec![#(::z3::Z3Sort::to_z3(#bindings, ctx)),*];
+                let args: Vec<&::z3::Ast> = args.iter().collect();
+                datatype.variants[#i].constructor.apply(&args)
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl ::z3::Z3Datatype for #ty {
+            fn z3_datatype(ctx: &::z3::Context) -> ::z3::DatatypeSort<'_> {
+                ::z3::DatatypeGroup::datatype::<Self>(ctx)
+            }
+        }
+
+        impl ::z3::Z3Sort for #ty {
+            fn z3_sort(ctx: &::z3::Context) -> ::z3::Sort<'_> {
+                ::z3::DatatypeGroup::sort::<Self>(ctx)
+            }
+
+            fn z3_field<'ctx>(
+                ctx: &'ctx ::z3::Context,
+                group: &mut ::z3::DatatypeGroup<'ctx>,
+            ) -> ::z3::DatatypeAccessor<'ctx> {
+                group.field::<Self, _>(#name, |group| {
+                    ::z3::DatatypeBuilder::new(ctx, #name)
+                        #(#variants)*
+                })
+            }
+
+            fn to_z3<'ctx>(&self, ctx: &'ctx ::z3::Context) -> ::z3::Ast<'ctx> {
+                let datatype = <Self as ::z3::Z3Datatype>::z3_datatype(ctx);
+                match *self {
+                    #(#arms)*
+                }
+            }
+        }
+    })
+}
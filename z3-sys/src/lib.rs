@@ -1,7 +1,7 @@
 /* automatically generated by rust-bindgen */
 
 #![allow(non_camel_case_types)]
-#![cfg_attr(feature = "cargo-clippy", allow(unreadable_literal))]
+#![allow(clippy::unreadable_literal)]
 
 mod generated;
 
@@ -197,6 +197,16 @@ pub struct _Z3_solver {
 /// tactic or logic.
 pub type Z3_solver = *mut _Z3_solver;
 
+#[doc(hidden)]
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct _Z3_solver_callback {
+    _unused: [u8; 0],
+}
+/// Handle passed to the callbacks of a user propagator, used to report
+/// propagations back to the solver.
+pub type Z3_solver_callback = *mut _Z3_solver_callback;
+
 #[doc(hidden)]
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
@@ -1534,6 +1544,65 @@ pub enum ErrorCode {
 pub type Z3_error_handler =
     ::std::option::Option<unsafe extern "C" fn(c: Z3_context, e: ErrorCode)>;
 
+/// Callback of a user propagator for a new backtracking point.
+pub type Z3_push_eh = ::std::option::Option<unsafe extern "C" fn(ctx: *mut ::std::os::raw::c_void)>;
+
+/// Callback of a user propagator for backtracking `num_scopes` points.
+pub type Z3_pop_eh = ::std::option::Option<
+    unsafe extern "C" fn(ctx: *mut ::std::os::raw::c_void, num_scopes: ::std::os::raw::c_uint),
+>;
+
+/// Callback of a user propagator for copying it into `new_context`. It
+/// returns the user context of the copy.
+pub type Z3_fresh_eh = ::std::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut ::std::os::raw::c_void,
+        new_context: Z3_context,
+    ) -> *mut ::std::os::raw::c_void,
+>;
+
+/// Callback of a user propagator for a registered expression being
+/// fixed to `value`.
+pub type Z3_fixed_eh = ::std::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut ::std::os::raw::c_void,
+        cb: Z3_solver_callback,
+        id: ::std::os::raw::c_uint,
+        value: Z3_ast,
+    ),
+>;
+
+/// Callback of a user propagator for two registered expressions becoming
+/// equal, or disequal.
+pub type Z3_eq_eh = ::std::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut ::std::os::raw::c_void,
+        cb: Z3_solver_callback,
+        x: ::std::os::raw::c_uint,
+        y: ::std::os::raw::c_uint,
+    ),
+>;
+
+/// Callback of a user propagator for the final check.
+pub type Z3_final_eh = ::std::option::Option<
+    unsafe extern "C" fn(ctx: *mut ::std::os::raw::c_void, cb: Z3_solver_callback),
+>;
+
+/// Callback for a clause inferred by the solver, with the proof hint that
+/// justifies it and the ids of the assumptions it depends on.
+///
+/// Requires the `z3_4_13` feature.
+#[cfg(feature = "z3_4_13")]
+pub type Z3_on_clause_eh = ::std::option::Option<
+    unsafe extern "C" fn(
+        ctx: *mut ::std::os::raw::c_void,
+        proof_hint: Z3_ast,
+        n: ::std::os::raw::c_uint,
+        deps: *const ::std::os::raw::c_uint,
+        literals: Z3_ast_vector,
+    ),
+>;
+
 /// A Goal is essentially a set of formulas.
 /// Z3 provide APIs for building strategies/tactics for solving and transforming Goals.
 /// Some of these transformations apply under/over approximations.
@@ -1973,8 +2042,8 @@ extern "C" {
     /// - `field_names`: names of the constructor fields.
     /// - `sorts`: field sorts, 0 if the field sort refers to a recursive sort.
     /// - `sort_refs`: reference to datatype sort that is an argument to the constructor; if the corresponding
-    /// sort reference is 0, then the value in sort_refs should be an index referring to
-    /// one of the recursive datatypes that is declared.
+    ///   sort reference is 0, then the value in sort_refs should be an index referring to
+    ///   one of the recursive datatypes that is declared.
     pub fn Z3_mk_constructor(
         c: Z3_context,
         name: Z3_symbol,
@@ -2867,7 +2936,7 @@ extern "C" {
     ///
     /// - `c`: logical context.
     /// - `numeral`: A string representing the numeral value in decimal notation. The string may be of the form `[num]*[.[num]*][E[+|-][num]+]`.
-    /// If the given sort is a real, then the numeral can be a rational, that is, a string of the form `[num]* / [num]*` .
+    ///   If the given sort is a real, then the numeral can be a rational, that is, a string of the form `[num]* / [num]*` .
     /// - `ty`: The sort of the numeral. In the current implementation, the given sort can be an int, real, finite-domain, or bit-vectors of arbitrary size.
     ///
     /// # See also:
@@ -3119,6 +3188,42 @@ extern "C" {
     /// - `re` is a regular expression sort.
     pub fn Z3_mk_re_full(c: Z3_context, re: Z3_sort) -> Z3_ast;
 
+    /// Create a linear ordering relation over signature `a`.
+    /// The relation is identified by the index `id`.
+    pub fn Z3_mk_linear_order(
+        c: Z3_context,
+        a: Z3_sort,
+        id: ::std::os::raw::c_uint,
+    ) -> Z3_func_decl;
+
+    /// Create a partial ordering relation over signature `a` and index `id`.
+    pub fn Z3_mk_partial_order(
+        c: Z3_context,
+        a: Z3_sort,
+        id: ::std::os::raw::c_uint,
+    ) -> Z3_func_decl;
+
+    /// Create a piecewise linear ordering relation over signature `a` and
+    /// index `id`.
+    pub fn Z3_mk_piecewise_linear_order(
+        c: Z3_context,
+        a: Z3_sort,
+        id: ::std::os::raw::c_uint,
+    ) -> Z3_func_decl;
+
+    /// Create a tree ordering relation over signature `a` identified using
+    /// index `id`.
+    pub fn Z3_mk_tree_order(c: Z3_context, a: Z3_sort, id: ::std::os::raw::c_uint) -> Z3_func_decl;
+
+    /// Create transitive closure of binary relation.
+    ///
+    /// # Preconditions:
+    ///
+    /// - `f` is a binary relation, such that the two arguments have the same sorts.
+    ///
+    /// The resulting relation `f+` represents the transitive closure of `f`.
+    pub fn Z3_mk_transitive_closure(c: Z3_context, f: Z3_func_decl) -> Z3_func_decl;
+
     /// Create a pattern for quantifier instantiation.
     ///
     /// Z3 uses pattern matching to instantiate quantifiers. If a
@@ -3297,7 +3402,7 @@ extern "C" {
     ///
     /// - `c`: logical context.
     /// - `weight`: quantifiers are associated with weights indicating the importance of using
-    /// the quantifier during instantiation. By default, pass the weight 0.
+    ///   the quantifier during instantiation. By default, pass the weight 0.
     /// - `num_bound`: number of constants to be abstracted into bound variables.
     /// - `bound`: array of constants to be abstracted into bound variables.
     /// - `num_patterns`: number of patterns.
@@ -3325,7 +3430,7 @@ extern "C" {
     ///
     /// - `c`: logical context.
     /// - `weight`: quantifiers are associated with weights indicating the importance of using
-    /// the quantifier during instantiation. By default, pass the weight 0.
+    ///   the quantifier during instantiation. By default, pass the weight 0.
     /// - `num_bound`: number of constants to be abstracted into bound variables.
     /// - `bound`: array of constants to be abstracted into bound variables.
     /// - `num_patterns`: number of patterns.
@@ -3976,6 +4081,19 @@ extern "C" {
     /// - [`AstKind::Numeral`](enum.AstKind.html#variant.Numeral)
     pub fn Z3_get_numeral_string(c: Z3_context, a: Z3_ast) -> Z3_string;
 
+    /// Return numeral value, as a binary string of a numeric constant term
+    ///
+    /// # Preconditions:
+    ///
+    /// - `Z3_get_ast_kind(c, a) == AstKind::Numeral`
+    /// - `a` represents a non-negative integer
+    ///
+    /// # See also:
+    ///
+    /// - [`Z3_get_ast_kind`](fn.Z3_get_ast_kind.html)
+    /// - [`AstKind::Numeral`](enum.AstKind.html#variant.Numeral)
+    pub fn Z3_get_numeral_binary_string(c: Z3_context, a: Z3_ast) -> Z3_string;
+
     /// Return numeral as a string in decimal notation.
     /// The result has at most `precision` decimal places.
     ///
@@ -4742,8 +4860,8 @@ extern "C" {
 
     /// Parse the given string using the SMT-LIB2 parser.
     ///
-    /// It returns a formula comprising of the conjunction of assertions
-    /// in the scope (up to push/pop) at the end of the string.
+    /// It returns the vector of assertions in the scope (up to push/pop)
+    /// at the end of the string.
     pub fn Z3_parse_smtlib2_string(
         c: Z3_context,
         str: Z3_string,
@@ -4753,7 +4871,7 @@ extern "C" {
         num_decls: ::std::os::raw::c_uint,
         decl_names: *const Z3_symbol,
         decls: *const Z3_func_decl,
-    ) -> Z3_ast;
+    ) -> Z3_ast_vector;
 
     /// Similar to [`Z3_parse_smtlib2_string`](fn.Z3_parse_smtlib2_string.html),
     /// but reads the benchmark from a file.
@@ -4766,7 +4884,7 @@ extern "C" {
         num_decls: ::std::os::raw::c_uint,
         decl_names: *const Z3_symbol,
         decls: *const Z3_func_decl,
-    ) -> Z3_ast;
+    ) -> Z3_ast_vector;
 
     /// Parse and evaluate and SMT-LIB2 command sequence. The state from a previous
     /// call is saved so the next evaluation builds on top of the previous call.
@@ -4934,7 +5052,11 @@ extern "C" {
     /// converted to Booleans either, so the if caller intends to
     /// preserve satisfiability, it should apply bit-blasting tactics.
     /// Quantifiers and theory atoms will not be encoded.
-    pub fn Z3_goal_to_dimacs_string(c: Z3_context, g: Z3_goal) -> Z3_string;
+    ///
+    /// If `include_names` is true, the names of the Boolean constants are
+    /// emitted as comments.
+    pub fn Z3_goal_to_dimacs_string(c: Z3_context, g: Z3_goal, include_names: Z3_bool)
+        -> Z3_string;
 
     /// Return a tactic associated with the given name.
     ///
@@ -5181,7 +5303,7 @@ extern "C" {
     /// Unlike `Z3_mk_solver()` this solver
     /// - Does not attempt to apply any logic specific tactics.
     /// - Does not change its behaviour based on whether it used
-    /// incrementally/non-incrementally.
+    ///   incrementally/non-incrementally.
     ///
     /// Note that these differences can result in very different performance
     /// compared to `Z3_mk_solver()`.
@@ -5342,6 +5464,73 @@ extern "C" {
     /// Return the set of non units in the solver state.
     pub fn Z3_solver_get_non_units(c: Z3_context, s: Z3_solver) -> Z3_ast_vector;
 
+    /// Register a user-propagator with the solver.
+    pub fn Z3_solver_propagate_init(
+        c: Z3_context,
+        s: Z3_solver,
+        user_context: *mut ::std::os::raw::c_void,
+        push_eh: Z3_push_eh,
+        pop_eh: Z3_pop_eh,
+        fresh_eh: Z3_fresh_eh,
+    );
+
+    /// Register a callback for when an expression is bound to a fixed value.
+    /// The supported expression types are
+    /// - Booleans
+    /// - Bit-vectors
+    pub fn Z3_solver_propagate_fixed(c: Z3_context, s: Z3_solver, fixed_eh: Z3_fixed_eh);
+
+    /// Register a callback on final check.
+    /// This provides freedom to the propagator to delay actions or implement a branch-and bound solver.
+    ///
+    /// The final_eh callback takes as argument the original user_context that was used
+    /// when calling [`Z3_solver_propagate_init`](fn.Z3_solver_propagate_init.html), and it takes a callback context for propagations.
+    /// If may use the callback context to invoke the [`Z3_solver_propagate_consequence`](fn.Z3_solver_propagate_consequence.html) function.
+    /// If the callback context gets used, the solver continues.
+    pub fn Z3_solver_propagate_final(c: Z3_context, s: Z3_solver, final_eh: Z3_final_eh);
+
+    /// Register a callback on expression equalities.
+    pub fn Z3_solver_propagate_eq(c: Z3_context, s: Z3_solver, eq_eh: Z3_eq_eh);
+
+    /// Register a callback on expression dis-equalities.
+    pub fn Z3_solver_propagate_diseq(c: Z3_context, s: Z3_solver, eq_eh: Z3_eq_eh);
+
+    /// Register a callback that is invoked for every clause the solver
+    /// infers, e.g. to log or check proofs.
+    ///
+    /// Requires the `z3_4_13` feature, as the function is missing from
+    /// older versions of Z3.
+    #[cfg(feature = "z3_4_13")]
+    pub fn Z3_solver_register_on_clause(
+        c: Z3_context,
+        s: Z3_solver,
+        user_context: *mut ::std::os::raw::c_void,
+        on_clause_eh: Z3_on_clause_eh,
+    );
+
+    /// Register an expression to propagate on with the solver.
+    /// Only expressions of type Bool and type Bit-Vector can be registered for propagation.
+    pub fn Z3_solver_propagate_register(
+        c: Z3_context,
+        s: Z3_solver,
+        e: Z3_ast,
+    ) -> ::std::os::raw::c_uint;
+
+    /// Propagate a consequence based on fixed values.
+    /// This is a callback a client may invoke during the fixed_eh callback.
+    /// The callback adds a propagation consequence based on the fixed values of the
+    /// `ids`.
+    pub fn Z3_solver_propagate_consequence(
+        c: Z3_context,
+        cb: Z3_solver_callback,
+        num_fixed: ::std::os::raw::c_uint,
+        fixed_ids: *const ::std::os::raw::c_uint,
+        num_eqs: ::std::os::raw::c_uint,
+        eq_lhs: *const ::std::os::raw::c_uint,
+        eq_rhs: *const ::std::os::raw::c_uint,
+        conseq: Z3_ast,
+    );
+
     /// Check whether the assertions in a given solver are consistent or not.
     ///
     /// The function [`Z3_solver_get_model`](fn.Z3_solver_get_model.html)
@@ -6329,6 +6518,14 @@ extern "C" {
     /// - [`Z3_optimize_assert_soft`](fn.Z3_optimize_assert_soft.html)
     pub fn Z3_optimize_assert(c: Z3_context, o: Z3_optimize, a: Z3_ast);
 
+    /// Assert tracked hard constraint to the optimization context.
+    ///
+    /// # See also:
+    ///
+    /// - [`Z3_optimize_assert`](fn.Z3_optimize_assert.html)
+    /// - [`Z3_optimize_assert_soft`](fn.Z3_optimize_assert_soft.html)
+    pub fn Z3_optimize_assert_and_track(c: Z3_context, o: Z3_optimize, a: Z3_ast, t: Z3_ast);
+
     /// Assert soft constraint to the optimization context.
     /// - `c`: - context
     /// - `o`: - optimization context
@@ -6393,6 +6590,8 @@ extern "C" {
     /// Check consistency and produce optimal values.
     /// - `c`: - context
     /// - `o`: - optimization context
+    /// - `num_assumptions`: - number of additional assumptions
+    /// - `assumptions`: - the additional assumptions
     ///
     /// # See also:
     ///
@@ -6400,7 +6599,12 @@ extern "C" {
     /// - [`Z3_optimize_get_model`](fn.Z3_optimize_get_model.html)
     /// - [`Z3_optimize_get_statistics`](fn.Z3_optimize_get_statistics.html)
     /// - [`Z3_optimize_get_unsat_core`](fn.Z3_optimize_get_unsat_core.html)
-    pub fn Z3_optimize_check(c: Z3_context, o: Z3_optimize) -> Z3_lbool;
+    pub fn Z3_optimize_check(
+        c: Z3_context,
+        o: Z3_optimize,
+        num_assumptions: ::std::os::raw::c_uint,
+        assumptions: *const Z3_ast,
+    ) -> Z3_lbool;
 
     /// Retrieve a string that describes the last status returned by [`Z3_optimize_check`](fn.Z3_optimize_check.html).
     ///
@@ -31,7 +31,7 @@ fn smoketest() {
         let model_s = Z3_model_to_string(ctx, model);
         assert_eq!(
             CStr::from_ptr(model_s).to_str().unwrap(),
-            "y -> (- 1)\nx -> 0\n"
+            "x -> 0\ny -> (- 1)\n"
         );
 
         // Grab the actual constant values out of the model
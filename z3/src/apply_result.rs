@@ -0,0 +1,85 @@
+use std::ffi::CStr;
+use std::fmt;
+use z3_sys::*;
+use ApplyResult;
+use Context;
+use Goal;
+use Model;
+
+impl<'ctx> ApplyResult<'ctx> {
+    /// Wrap a result returned by Z3.
+    pub(crate) unsafe fn from_raw(ctx: &'ctx Context, r: Z3_apply_result) -> ApplyResult<'ctx> {
+        ctx.check_null(r);
+        Z3_apply_result_inc_ref(ctx.z3_ctx, r);
+        ApplyResult {
+            ctx,
+            z3_apply_result: r,
+        }
+    }
+
+    /// Return the number of subgoals.
+    pub fn len(&self) -> usize {
+        unsafe { Z3_apply_result_get_num_subgoals(self.ctx.z3_ctx, self.z3_apply_result) as usize }
+    }
+
+    /// Return `true` if there are no subgoals, i.e. the original goal
+    /// was shown to be satisfiable.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the subgoal at index `i`.
+    ///
+    /// # Panics
+    ///
+    /// If `i` is out of bounds.
+    pub fn subgoal(&self, i: usize) -> Goal<'ctx> {
+        assert!(i < self.len());
+        unsafe {
+            let g = Z3_apply_result_get_subgoal(self.ctx.z3_ctx, self.z3_apply_result, i as u32);
+            Goal::from_raw(self.ctx, g)
+        }
+    }
+
+    /// Iterate over the subgoals.
+    pub fn subgoals<'a>(&'a self) -> impl Iterator<Item = Goal<'ctx>> + 'a {
+        (0..self.len()).map(move |i| self.subgoal(i))
+    }
+
+    /// Convert a model of the subgoal at index `i` into a model of the
+    /// original goal, restoring the variables that the tactic eliminated.
+    ///
+    /// # Panics
+    ///
+    /// If `i` is out of bounds.
+    pub fn convert_model(&self, i: usize, model: &Model<'ctx>) -> Model<'ctx> {
+        let goal = self.subgoal(i);
+        unsafe {
+            let m = Z3_goal_convert_model(self.ctx.z3_ctx, goal.z3_goal, model.z3_mdl);
+            Z3_model_inc_ref(self.ctx.z3_ctx, m);
+            Model {
+                ctx: self.ctx,
+                z3_mdl: m,
+            }
+        }
+    }
+}
+
+impl<'ctx> fmt::Display for ApplyResult<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let p = unsafe { Z3_apply_result_to_string(self.ctx.z3_ctx, self.z3_apply_result) };
+        if p.is_null() {
+            return Result::Err(fmt::Error);
+        }
+        match unsafe { CStr::from_ptr(p) }.to_str() {
+            Ok(s) => write!(f, "{}", s),
+            Err(_) => Result::Err(fmt::Error),
+        }
+    }
+}
+
+impl<'ctx> Drop for ApplyResult<'ctx> {
+    fn drop(&mut self) {
+        unsafe { Z3_apply_result_dec_ref(self.ctx.z3_ctx, self.z3_apply_result) };
+    }
+}
@@ -0,0 +1,278 @@
+use std::fmt;
+use std::ops::Deref;
+use z3_sys::*;
+use Array;
+use Ast;
+use Context;
+use FuncDecl;
+use Sort;
+use Symbol;
+
+impl<'ctx> Array<'ctx> {
+    fn wrap(ctx: &'ctx Context, ast: Z3_ast) -> Array<'ctx> {
+        Array {
+            ast: Ast::new(ctx, ast),
+        }
+    }
+
+    /// Wrap an `Ast` as an `Array`, returning `None` if it does not have
+    /// array sort.
+    pub fn from_ast(ast: Ast<'ctx>) -> Option<Array<'ctx>> {
+        if ast.sort_kind() == SortKind::Array {
+            Some(Array { ast })
+        } else {
+            None
+        }
+    }
+
+    /// Create an array constant named `name`, mapping values of sort
+    /// `domain` to values of sort `range`.
+    pub fn new_const(
+        ctx: &'ctx Context,
+        name: &str,
+        domain: &Sort<'ctx>,
+        range: &Sort<'ctx>,
+    ) -> Array<'ctx> {
+        let sort = Sort::array(ctx, domain, range);
+        Array {
+            ast: Ast::new_const(&ctx.str_sym(name), &sort),
+        }
+    }
+
+    /// Create the constant array, which maps every index of sort
+    /// `domain` to `val`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    ///
+    /// let zeroes = Array::const_array(&ctx, &ctx.int_sort(), &ctx.from_i64(0));
+    /// let mem = zeroes.store(&ctx.from_i64(3), &ctx.from_i64(42));
+    ///
+    /// let i = ctx.named_int_const("i");
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&mem.select(&i)._eq(&ctx.from_i64(42)));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    ///
+    /// let model = solver.get_model().unwrap();
+    /// assert_eq!(model.eval(&i, true).unwrap().as_i64(), Some(3));
+    /// ```
+    pub fn const_array(ctx: &'ctx Context, domain: &Sort<'ctx>, val: &Ast<'ctx>) -> Array<'ctx> {
+        Array::wrap(ctx, unsafe {
+            Z3_mk_const_array(ctx.z3_ctx, domain.z3_sort, val.z3_ast)
+        })
+    }
+
+    /// Create the array defined by the anonymous function
+    /// `lambda bounds. body`, whose indices range over the sorts of the
+    /// constants in `bounds` and which maps them to `body`. With more
+    /// than one bound constant the array is multi-dimensional.
+    ///
+    /// # Panics
+    ///
+    /// If `bounds` is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    ///
+    /// // squares[i] = i * i
+    /// let i = ctx.named_int_const("i");
+    /// let squares = Array::lambda(&ctx, &[&i], &i.mul(&[&i]));
+    ///
+    /// let j = ctx.named_int_const("j");
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&squares.select(&j)._eq(&ctx.from_i64(49)));
+    /// solver.assert(&j.gt(&ctx.from_i64(0)));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    ///
+    /// let model = solver.get_model().unwrap();
+    /// assert_eq!(model.eval(&j, true).unwrap().as_i64(), Some(7));
+    /// ```
+    ///
+    /// # See also:
+    ///
+    /// - [`Array::lambda_bound()`](#method.lambda_bound)
+    pub fn lambda(ctx: &'ctx Context, bounds: &[&Ast<'ctx>], body: &Ast<'ctx>) -> Array<'ctx> {
+        assert!(
+            !bounds.is_empty(),
+            "a lambda needs at least one bound constant"
+        );
+        Array::wrap(ctx, unsafe {
+            let bounds: Vec<Z3_app> = bounds
+                .iter()
+                .map(|a| Z3_to_app(ctx.z3_ctx, a.z3_ast))
+                .collect();
+            Z3_mk_lambda_const(
+                ctx.z3_ctx,
+                bounds.len() as u32,
+                bounds.as_ptr(),
+                body.z3_ast,
+            )
+        })
+    }
+
+    /// Create the array defined by the anonymous function
+    /// `lambda bounds. body`, where `body` refers to the bound variables
+    /// with [`Ast::bound()`](struct.Ast.html#method.bound) instead of
+    /// constants. Each bound variable is given by its name and sort; the
+    /// last one has de-Bruijn index 0, the one before it index 1, and so
+    /// on.
+    ///
+    /// # Panics
+    ///
+    /// If `bounds` is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    ///
+    /// // succ[x] = x + 1
+    /// let int = ctx.int_sort();
+    /// let x = Ast::bound(&ctx, 0, &int);
+    /// let body = x.add(&[&ctx.from_i64(1)]);
+    /// let succ = Array::lambda_bound(&ctx, &[(&ctx.str_sym("x"), &int)], &body);
+    ///
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&succ.select(&ctx.from_i64(41))._eq(&ctx.from_i64(42)));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    /// ```
+    ///
+    /// # See also:
+    ///
+    /// - [`Array::lambda()`](#method.lambda)
+    pub fn lambda_bound(
+        ctx: &'ctx Context,
+        bounds: &[(&Symbol<'ctx>, &Sort<'ctx>)],
+        body: &Ast<'ctx>,
+    ) -> Array<'ctx> {
+        assert!(
+            !bounds.is_empty(),
+            "a lambda needs at least one bound variable"
+        );
+        let sorts: Vec<Z3_sort> = bounds.iter().map(|(_, s)| s.z3_sort).collect();
+        let names: Vec<Z3_symbol> = bounds.iter().map(|(n, _)| n.z3_sym).collect();
+        Array::wrap(ctx, unsafe {
+            Z3_mk_lambda(
+                ctx.z3_ctx,
+                bounds.len() as u32,
+                sorts.as_ptr(),
+                names.as_ptr(),
+                body.z3_ast,
+            )
+        })
+    }
+
+    /// Read the value stored at `index`.
+    ///
+    /// # See also:
+    ///
+    /// - [`Array::store()`](#method.store)
+    pub fn select(&self, index: &Ast<'ctx>) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_select(self.ctx.z3_ctx, self.z3_ast, index.z3_ast)
+        })
+    }
+
+    /// Return the array that is identical to `self`, except that it
+    /// maps `index` to `val`.
+    ///
+    /// # See also:
+    ///
+    /// - [`Array::select()`](#method.select)
+    pub fn store(&self, index: &Ast<'ctx>, val: &Ast<'ctx>) -> Array<'ctx> {
+        Array::wrap(self.ctx, unsafe {
+            Z3_mk_store(self.ctx.z3_ctx, self.z3_ast, index.z3_ast, val.z3_ast)
+        })
+    }
+
+    /// Apply `f` pointwise to `arrays`: the result maps each index `i` to
+    /// `f(a1[i], ..., an[i])`. All arrays must have the same domain, and
+    /// their ranges must match the domain of `f`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    ///
+    /// let int = ctx.int_sort();
+    /// let a = Array::new_const(&ctx, "a", &int, &int);
+    /// let b = Array::new_const(&ctx, "b", &int, &int);
+    /// let x = ctx.named_int_const("x");
+    /// let plus = x.add(&[&x]).decl();
+    /// let sum = Array::map(&plus, &[&a, &b]);
+    ///
+    /// let i = ctx.named_int_const("i");
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(
+    ///     &sum.select(&i)
+    ///         ._eq(&a.select(&i).add(&[&b.select(&i)]))
+    ///         .not(),
+    /// );
+    /// assert_eq!(solver.check(), SatResult::Unsat);
+    /// ```
+    pub fn map(f: &FuncDecl<'ctx>, arrays: &[&Array<'ctx>]) -> Array<'ctx> {
+        let args: Vec<Z3_ast> = arrays.iter().map(|a| a.z3_ast).collect();
+        Array::wrap(f.ctx, unsafe {
+            Z3_mk_map(
+                f.ctx.z3_ctx,
+                f.z3_func_decl,
+                args.len() as u32,
+                args.as_ptr(),
+            )
+        })
+    }
+
+    /// Return the default value of the array, e.g. the value of a
+    /// [`const_array()`](#method.const_array). This is only meaningful
+    /// for arrays that map all but finitely many indices to one value.
+    pub fn default(&self) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_array_default(self.ctx.z3_ctx, self.z3_ast)
+        })
+    }
+
+    /// Return an index at which `self` and `other` differ, if they are
+    /// not equal. This is the extensionality witness
+    /// `(=> (= (select a (ext a b)) (select b (ext a b))) (= a b))`.
+    pub fn ext(&self, other: &Array<'ctx>) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_array_ext(self.ctx.z3_ctx, self.z3_ast, other.z3_ast)
+        })
+    }
+}
+
+impl<'ctx> Deref for Array<'ctx> {
+    type Target = Ast<'ctx>;
+
+    fn deref(&self) -> &Ast<'ctx> {
+        &self.ast
+    }
+}
+
+impl<'ctx> From<Array<'ctx>> for Ast<'ctx> {
+    fn from(array: Array<'ctx>) -> Ast<'ctx> {
+        array.ast
+    }
+}
+
+impl<'ctx> fmt::Display for Array<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt::Display::fmt(&self.ast, f)
+    }
+}
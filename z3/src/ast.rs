@@ -2,18 +2,22 @@ use std::cmp::{Eq, PartialEq};
 use std::ffi::{CStr, CString};
 use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::ops::{Add, BitAnd, BitOr, BitXor, Mul, Neg, Not, Shl, Shr, Sub};
 use z3_sys::*;
 use Ast;
+use AstKind;
+use AstVector;
 use Context;
+use FuncDecl;
+use Params;
+use Pattern;
 use Sort;
 use Symbol;
-use Z3_MUTEX;
 
 macro_rules! unop {
     ( $f:ident, $z3fn:ident ) => {
         pub fn $f(&self) -> Ast<'ctx> {
             Ast::new(self.ctx, unsafe {
-                let guard = Z3_MUTEX.lock().unwrap();
                 $z3fn(self.ctx.z3_ctx, self.z3_ast)
             })
     }
@@ -24,7 +28,6 @@ macro_rules! binop {
     ( $f:ident, $z3fn:ident ) => {
         pub fn $f(&self, other: &Ast<'ctx>) -> Ast<'ctx> {
             Ast::new(self.ctx, unsafe {
-                let guard = Z3_MUTEX.lock().unwrap();
                 $z3fn(self.ctx.z3_ctx, self.z3_ast, other.z3_ast)
             })
     }
@@ -35,7 +38,6 @@ macro_rules! trinop {
     ( $f:ident, $z3fn:ident ) => {
         pub fn $f(&self, a: &Ast<'ctx>, b: &Ast<'ctx>) -> Ast<'ctx> {
             Ast::new(self.ctx, unsafe {
-                let guard = Z3_MUTEX.lock().unwrap();
                 $z3fn(self.ctx.z3_ctx, self.z3_ast, a.z3_ast, b.z3_ast)
             })
     }
@@ -46,7 +48,6 @@ macro_rules! varop {
     ( $f:ident, $z3fn:ident ) => {
         pub fn $f(&self, other: &[&Ast<'ctx>]) -> Ast<'ctx> {
             Ast::new(self.ctx, unsafe {
-                let guard = Z3_MUTEX.lock().unwrap();
                 let mut tmp = vec![self.z3_ast];
                 for a in other {
                     tmp.push(a.z3_ast)
@@ -59,13 +60,13 @@ macro_rules! varop {
 }
 
 impl<'ctx> Ast<'ctx> {
-    pub fn new(ctx: &Context, ast: Z3_ast) -> Ast {
-        assert!(!ast.is_null());
+    #[allow(clippy::not_unsafe_ptr_arg_deref)]
+    pub fn new(ctx: &Context, ast: Z3_ast) -> Ast<'_> {
+        ctx.check_null(ast);
         Ast {
             ctx,
             z3_ast: unsafe {
                 debug!("new ast {:p}", ast);
-                let guard = Z3_MUTEX.lock().unwrap();
                 Z3_inc_ref(ctx.z3_ctx, ast);
                 ast
             },
@@ -74,23 +75,190 @@ impl<'ctx> Ast<'ctx> {
 
     pub fn new_const(sym: &Symbol<'ctx>, sort: &Sort<'ctx>) -> Ast<'ctx> {
         Ast::new(sym.ctx, unsafe {
-            let guard = Z3_MUTEX.lock().unwrap();
             Z3_mk_const(sym.ctx.z3_ctx, sym.z3_sym, sort.z3_sort)
         })
     }
 
+    /// Create a constant of sort `sort` with a fresh name that starts
+    /// with `prefix`, distinct from every other constant.
+    ///
+    /// # See also:
+    ///
+    /// - [`FuncDecl::fresh()`](struct.FuncDecl.html#method.fresh)
     pub fn fresh_const(ctx: &'ctx Context, prefix: &str, sort: &Sort<'ctx>) -> Ast<'ctx> {
         Ast::new(ctx, unsafe {
             let pp = CString::new(prefix).unwrap();
             let p = pp.as_ptr();
-            let guard = Z3_MUTEX.lock().unwrap();
             Z3_mk_fresh_const(ctx.z3_ctx, p, sort.z3_sort)
         })
     }
 
+    /// Create a bound variable.
+    ///
+    /// Bound variables are indexed by de-Bruijn indices. It is perhaps
+    /// easiest to explain the meaning of de-Bruijn indices by indicating
+    /// the compilation process from non-de-Bruijn formulas to de-Bruijn
+    /// format: a variable bound by the innermost quantifier has index 0,
+    /// the next one out has index 1, and so on.
+    ///
+    /// Most users should prefer [`Ast::forall_const()`](#method.forall_const)
+    /// and [`Ast::exists_const()`](#method.exists_const), which abstract
+    /// ordinary constants into bound variables.
+    ///
+    /// # See also:
+    ///
+    /// - [`Array::lambda_bound()`](struct.Array.html#method.lambda_bound)
+    pub fn bound(ctx: &'ctx Context, index: u32, sort: &Sort<'ctx>) -> Ast<'ctx> {
+        Ast::new(ctx, unsafe { Z3_mk_bound(ctx.z3_ctx, index, sort.z3_sort) })
+    }
+
+    /// Create a universal quantifier using a list of constants that
+    /// will form the set of bound variables.
+    ///
+    /// - `bounds`: constants to be abstracted into bound variables.
+    /// - `patterns`: patterns used to guide instantiation. If empty,
+    ///   Z3 will infer patterns on its own.
+    /// - `body`: the body of the quantifier.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    ///
+    /// let x = ctx.named_int_const("x");
+    /// let y = ctx.named_int_const("y");
+    ///
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&Ast::forall_const(&ctx, &[&x], &[], &x.add(&[&y]).gt(&x)));
+    /// solver.assert(&y.le(&ctx.from_i64(0)));
+    /// assert_eq!(solver.check(), SatResult::Unsat);
+    /// ```
+    ///
+    /// # See also:
+    ///
+    /// - [`Ast::exists_const()`](#method.exists_const)
+    /// - [`Ast::quantifier_const()`](#method.quantifier_const)
+    pub fn forall_const(
+        ctx: &'ctx Context,
+        bounds: &[&Ast<'ctx>],
+        patterns: &[&Pattern<'ctx>],
+        body: &Ast<'ctx>,
+    ) -> Ast<'ctx> {
+        Ast::quantifier_const(ctx, true, 0, None, None, bounds, patterns, &[], body)
+    }
+
+    /// Create an existential quantifier using a list of constants that
+    /// will form the set of bound variables.
+    ///
+    /// - `bounds`: constants to be abstracted into bound variables.
+    /// - `patterns`: patterns used to guide instantiation. If empty,
+    ///   Z3 will infer patterns on its own.
+    /// - `body`: the body of the quantifier.
+    ///
+    /// # See also:
+    ///
+    /// - [`Ast::forall_const()`](#method.forall_const)
+    /// - [`Ast::quantifier_const()`](#method.quantifier_const)
+    pub fn exists_const(
+        ctx: &'ctx Context,
+        bounds: &[&Ast<'ctx>],
+        patterns: &[&Pattern<'ctx>],
+        body: &Ast<'ctx>,
+    ) -> Ast<'ctx> {
+        Ast::quantifier_const(ctx, false, 0, None, None, bounds, patterns, &[], body)
+    }
+
+    /// Create a universal or existential quantifier, with all of the
+    /// optional attributes Z3 supports.
+    ///
+    /// - `is_forall`: whether to create a universal (`true`) or
+    ///   existential (`false`) quantifier.
+    /// - `weight`: quantifiers are associated with weights indicating the
+    ///   importance of using the quantifier during instantiation. By
+    ///   default, pass the weight 0.
+    /// - `quantifier_id`: identifier for the quantifier, reported in
+    ///   statistics and traces.
+    /// - `skolem_id`: identifier used as a prefix for skolem constants.
+    /// - `bounds`: constants to be abstracted into bound variables.
+    /// - `patterns`: patterns used to guide instantiation.
+    /// - `no_patterns`: terms that must not be used as patterns.
+    /// - `body`: the body of the quantifier.
+    ///
+    /// # See also:
+    ///
+    /// - [`Ast::forall_const()`](#method.forall_const)
+    /// - [`Ast::exists_const()`](#method.exists_const)
+    #[allow(clippy::too_many_arguments)]
+    pub fn quantifier_const(
+        ctx: &'ctx Context,
+        is_forall: bool,
+        weight: u32,
+        quantifier_id: Option<&Symbol<'ctx>>,
+        skolem_id: Option<&Symbol<'ctx>>,
+        bounds: &[&Ast<'ctx>],
+        patterns: &[&Pattern<'ctx>],
+        no_patterns: &[&Ast<'ctx>],
+        body: &Ast<'ctx>,
+    ) -> Ast<'ctx> {
+        let patterns: Vec<Z3_pattern> = patterns.iter().map(|p| p.z3_patt).collect();
+        let no_patterns: Vec<Z3_ast> = no_patterns.iter().map(|a| a.z3_ast).collect();
+        Ast::new(ctx, unsafe {
+            let bounds: Vec<Z3_app> = bounds
+                .iter()
+                .map(|a| Z3_to_app(ctx.z3_ctx, a.z3_ast))
+                .collect();
+            Z3_mk_quantifier_const_ex(
+                ctx.z3_ctx,
+                is_forall,
+                weight,
+                quantifier_id.map_or(::std::ptr::null_mut(), |s| s.z3_sym),
+                skolem_id.map_or(::std::ptr::null_mut(), |s| s.z3_sym),
+                bounds.len() as u32,
+                bounds.as_ptr(),
+                patterns.len() as u32,
+                patterns.as_ptr(),
+                no_patterns.len() as u32,
+                no_patterns.as_ptr(),
+                body.z3_ast,
+            )
+        })
+    }
+
+    /// Constraint that all of `values` are pairwise distinct. Unlike a
+    /// conjunction of disequalities this stays linear in the number of
+    /// values. Fewer than two values are trivially distinct.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use z3::{Ast, Config, Context, SatResult, Solver};
+    /// # let ctx = Context::new(&Config::new());
+    /// let xs: Vec<Ast> = (0..3).map(|i| ctx.named_int_const(&i.to_string())).collect();
+    /// let refs: Vec<&Ast> = xs.iter().collect();
+    ///
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&Ast::distinct(&ctx, &refs));
+    /// for x in &xs {
+    ///     solver.assert(&x.ge(&ctx.from_i64(0)));
+    ///     solver.assert(&x.lt(&ctx.from_i64(2)));
+    /// }
+    /// assert_eq!(solver.check(), SatResult::Unsat);
+    /// ```
+    pub fn distinct(ctx: &'ctx Context, values: &[&Ast<'ctx>]) -> Ast<'ctx> {
+        if values.len() < 2 {
+            return Ast::from_bool(ctx, true);
+        }
+        let args: Vec<Z3_ast> = values.iter().map(|a| a.z3_ast).collect();
+        Ast::new(ctx, unsafe {
+            Z3_mk_distinct(ctx.z3_ctx, args.len() as u32, args.as_ptr())
+        })
+    }
+
     pub fn from_bool(ctx: &'ctx Context, b: bool) -> Ast<'ctx> {
         Ast::new(ctx, unsafe {
-            let guard = Z3_MUTEX.lock().unwrap();
             if b {
                 Z3_mk_true(ctx.z3_ctx)
             } else {
@@ -102,7 +270,6 @@ impl<'ctx> Ast<'ctx> {
     pub fn from_i64(ctx: &'ctx Context, i: i64) -> Ast<'ctx> {
         Ast::new(ctx, unsafe {
             let sort = ctx.int_sort();
-            let guard = Z3_MUTEX.lock().unwrap();
             Z3_mk_int64(ctx.z3_ctx, i, sort.z3_sort)
         })
     }
@@ -110,14 +277,12 @@ impl<'ctx> Ast<'ctx> {
     pub fn from_u64(ctx: &'ctx Context, u: u64) -> Ast<'ctx> {
         Ast::new(ctx, unsafe {
             let sort = ctx.int_sort();
-            let guard = Z3_MUTEX.lock().unwrap();
             Z3_mk_unsigned_int64(ctx.z3_ctx, u, sort.z3_sort)
         })
     }
 
     pub fn from_real(ctx: &'ctx Context, num: i32, den: i32) -> Ast<'ctx> {
         Ast::new(ctx, unsafe {
-            let guard = Z3_MUTEX.lock().unwrap();
             Z3_mk_real(
                 ctx.z3_ctx,
                 num as ::std::os::raw::c_int,
@@ -126,9 +291,10 @@ impl<'ctx> Ast<'ctx> {
         })
     }
 
+    /// Return the value of a Boolean literal, or `None` if `self` is
+    /// neither `true` nor `false`.
     pub fn as_bool(&self) -> Option<bool> {
         unsafe {
-            let guard = Z3_MUTEX.lock().unwrap();
             match Z3_get_bool_value(self.ctx.z3_ctx, self.z3_ast) {
                 Z3_L_TRUE => Some(true),
                 Z3_L_FALSE => Some(false),
@@ -137,9 +303,26 @@ impl<'ctx> Ast<'ctx> {
         }
     }
 
+    /// Return the value of an integer, real or bit-vector numeral, or
+    /// `None` if `self` is not a numeral or its value is not an integer
+    /// that fits in an `i64`.
+    ///
+    /// Bit-vector numerals are read as unsigned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// assert_eq!(ctx.from_i64(-7).as_i64(), Some(-7));
+    /// assert_eq!(ctx.from_real(6, 3).as_i64(), Some(2));
+    /// assert_eq!(ctx.from_real(1, 3).as_i64(), None);
+    /// assert_eq!(ctx.named_int_const("x").as_i64(), None);
+    /// ```
     pub fn as_i64(&self) -> Option<i64> {
         unsafe {
-            let guard = Z3_MUTEX.lock().unwrap();
             let mut tmp: ::std::os::raw::c_longlong = 0;
             if Z3_get_numeral_int64(self.ctx.z3_ctx, self.z3_ast, &mut tmp) {
                 Some(tmp)
@@ -149,9 +332,11 @@ impl<'ctx> Ast<'ctx> {
         }
     }
 
+    /// Return the value of an integer, real or bit-vector numeral, or
+    /// `None` if `self` is not a numeral or its value is not an integer
+    /// that fits in a `u64`.
     pub fn as_u64(&self) -> Option<u64> {
         unsafe {
-            let guard = Z3_MUTEX.lock().unwrap();
             let mut tmp: ::std::os::raw::c_ulonglong = 0;
             if Z3_get_numeral_uint64(self.ctx.z3_ctx, self.z3_ast, &mut tmp) {
                 Some(tmp)
@@ -161,9 +346,14 @@ impl<'ctx> Ast<'ctx> {
         }
     }
 
+    /// Return the value of a numeral as a fraction `(numerator,
+    /// denominator)` in lowest terms, or `None` if `self` is not a
+    /// numeral or either part does not fit in an `i64`.
     pub fn as_real(&self) -> Option<(i64, i64)> {
         unsafe {
-            let guard = Z3_MUTEX.lock().unwrap();
+            if !Z3_is_numeral_ast(self.ctx.z3_ctx, self.z3_ast) {
+                return None;
+            }
             let mut num: i64 = 0;
             let mut den: i64 = 0;
             if Z3_get_numeral_small(self.ctx.z3_ctx, self.z3_ast, &mut num, &mut den) {
@@ -174,10 +364,476 @@ impl<'ctx> Ast<'ctx> {
         }
     }
 
-    varop!(distinct, Z3_mk_distinct);
+    /// Simplify the term using Z3's rewriter, for instance folding
+    /// constants and normalizing arithmetic.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_int_const("x");
+    /// let e = x.add(&[&ctx.from_i64(2), &ctx.from_i64(3)]);
+    /// assert_eq!(e.simplify().to_string(), "(+ 5 x)");
+    /// ```
+    ///
+    /// # See also:
+    ///
+    /// - [`Ast::simplify_with()`](#method.simplify_with)
+    pub fn simplify(&self) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_simplify(self.ctx.z3_ctx, self.z3_ast)
+        })
+    }
+
+    /// Simplify the term, configuring the rewriter with `params`, for
+    /// instance `som` to put polynomials in sum-of-monomials form.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_int_const("x");
+    /// let y = ctx.named_int_const("y");
+    /// let e = x.add(&[&y]).mul(&[&x]);
+    ///
+    /// let mut params = Params::new(&ctx);
+    /// params.set_bool("som", true);
+    /// assert_eq!(e.simplify_with(&params).to_string(), "(+ (* x x) (* x y))");
+    /// ```
+    ///
+    /// # See also:
+    ///
+    /// - [`Ast::simplify()`](#method.simplify)
+    pub fn simplify_with(&self, params: &Params<'ctx>) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_simplify_ex(self.ctx.z3_ctx, self.z3_ast, params.z3_params)
+        })
+    }
+
+    /// Simultaneously replace every occurrence of each `from` term in
+    /// `self` with the corresponding `to` term, which must have the same
+    /// sort.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_int_const("x");
+    /// let y = ctx.named_int_const("y");
+    /// let e = x.add(&[&y]);
+    ///
+    /// let swapped = e.substitute(&[(&x, &y), (&y, &x)]);
+    /// assert_eq!(swapped.to_string(), "(+ y x)");
+    /// ```
+    pub fn substitute(&self, substitutions: &[(&Ast<'ctx>, &Ast<'ctx>)]) -> Ast<'ctx> {
+        let from: Vec<Z3_ast> = substitutions.iter().map(|(f, _)| f.z3_ast).collect();
+        let to: Vec<Z3_ast> = substitutions.iter().map(|(_, t)| t.z3_ast).collect();
+        Ast::new(self.ctx, unsafe {
+            Z3_substitute(
+                self.ctx.z3_ctx,
+                self.z3_ast,
+                from.len() as u32,
+                from.as_ptr(),
+                to.as_ptr(),
+            )
+        })
+    }
+
+    /// Replace the free [bound variables](#method.bound) of `self`: the
+    /// variable with de-Bruijn index `i` is replaced with `to[i]`.
+    ///
+    /// This instantiates, for instance, the body of a quantifier.
+    pub fn substitute_vars(&self, to: &[&Ast<'ctx>]) -> Ast<'ctx> {
+        let to: Vec<Z3_ast> = to.iter().map(|t| t.z3_ast).collect();
+        Ast::new(self.ctx, unsafe {
+            Z3_substitute_vars(self.ctx.z3_ctx, self.z3_ast, to.len() as u32, to.as_ptr())
+        })
+    }
+
+    /// Create an integer numeral from a string of digits in the given
+    /// `radix`, optionally preceded by `-` or `+`, for values that do not
+    /// fit in 64 bits. Returns `None` if the string is not a valid
+    /// number in that radix.
+    ///
+    /// # Panics
+    ///
+    /// If `radix` is not in the range `2..=36`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use z3::{Ast, Config, Context};
+    /// # let ctx = Context::new(&Config::new());
+    /// let n = Ast::int_from_str_radix(&ctx, "ffffffffffffffffffff", 16).unwrap();
+    /// assert_eq!(n.to_string(), "1208925819614629174706175");
+    /// assert!(Ast::int_from_str_radix(&ctx, "12g", 16).is_none());
+    /// ```
+    pub fn int_from_str_radix(ctx: &'ctx Context, s: &str, radix: u32) -> Option<Ast<'ctx>> {
+        assert!((2..=36).contains(&radix), "radix must be in 2..=36");
+        let (negative, digits) = match s.as_bytes().first() {
+            Some(b'-') => (true, &s[1..]),
+            Some(b'+') => (false, &s[1..]),
+            _ => (false, s),
+        };
+        if digits.is_empty() {
+            return None;
+        }
+        // Convert to decimal, which is what Z3_mk_numeral accepts,
+        // keeping the least significant decimal digit first.
+        let mut decimal: Vec<u32> = vec![0];
+        for c in digits.chars() {
+            let mut carry = c.to_digit(radix)?;
+            for d in decimal.iter_mut() {
+                let v = *d * radix + carry;
+                *d = v % 10;
+                carry = v / 10;
+            }
+            while carry > 0 {
+                decimal.push(carry % 10);
+                carry /= 10;
+            }
+        }
+        while decimal.len() > 1 && decimal[decimal.len() - 1] == 0 {
+            decimal.pop();
+        }
+        let mut repr = String::with_capacity(decimal.len() + 1);
+        if negative {
+            repr.push('-');
+        }
+        repr.extend(decimal.iter().rev().map(|&d| (b'0' + d as u8) as char));
+        Some(Ast::numeral_from_str(ctx, &repr, &ctx.int_sort()))
+    }
+
+    /// Create a real numeral from a decimal string such as `-12`,
+    /// `3.14159` or `1/3`, without losing precision. Returns `None` if
+    /// the string is not of one of these forms, or the denominator is
+    /// zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use z3::{Ast, Config, Context};
+    /// # let ctx = Context::new(&Config::new());
+    /// let r = Ast::real_from_decimal_str(&ctx, "0.125").unwrap();
+    /// assert_eq!(r.as_real(), Some((1, 8)));
+    /// assert!(Ast::real_from_decimal_str(&ctx, "one half").is_none());
+    /// ```
+    pub fn real_from_decimal_str(ctx: &'ctx Context, s: &str) -> Option<Ast<'ctx>> {
+        // Z3_mk_numeral only checks the characters used, so "1..5" would
+        // be accepted; validate the structure here instead.
+        let is_digits = |t: &str| !t.is_empty() && t.bytes().all(|b| b.is_ascii_digit());
+        let unsigned = s.trim_start_matches(['-', '+']);
+        if s.len() - unsigned.len() > 1 {
+            return None;
+        }
+        let valid = if let Some(slash) = unsigned.find('/') {
+            let (num, den) = (&unsigned[..slash], &unsigned[slash + 1..]);
+            is_digits(num) && is_digits(den) && den.bytes().any(|b| b != b'0')
+        } else if let Some(dot) = unsigned.find('.') {
+            is_digits(&unsigned[..dot]) && is_digits(&unsigned[dot + 1..])
+        } else {
+            is_digits(unsigned)
+        };
+        if valid {
+            Some(Ast::numeral_from_str(ctx, s, &ctx.real_sort()))
+        } else {
+            None
+        }
+    }
+
+    /// Create a numeral of sort `sort` from its decimal representation,
+    /// e.g. `-12` or `1/3`.
+    pub(crate) fn numeral_from_str(ctx: &'ctx Context, s: &str, sort: &Sort<'ctx>) -> Ast<'ctx> {
+        let s = CString::new(s).unwrap();
+        Ast::new(ctx, unsafe {
+            Z3_mk_numeral(ctx.z3_ctx, s.as_ptr(), sort.z3_sort)
+        })
+    }
+
+    pub(crate) fn is_numeral(&self) -> bool {
+        unsafe { Z3_is_numeral_ast(self.ctx.z3_ctx, self.z3_ast) }
+    }
+
+    /// Return the decimal representation of a numeral, e.g. `-12` or
+    /// `1/3`.
+    pub(crate) fn numeral_string(&self) -> Option<String> {
+        if !self.is_numeral() {
+            return None;
+        }
+        unsafe {
+            let p = Z3_get_numeral_string(self.ctx.z3_ctx, self.z3_ast);
+            CStr::from_ptr(p).to_str().ok().map(|s| s.to_owned())
+        }
+    }
+
+    /// Return the numerator and denominator of an integer or real
+    /// numeral.
+    pub(crate) fn numerator_denominator(&self) -> Option<(Ast<'ctx>, Ast<'ctx>)> {
+        match self.sort_kind() {
+            SortKind::Int | SortKind::Real => {}
+            _ => return None,
+        }
+        if !self.is_numeral() {
+            return None;
+        }
+        let num = Ast::new(self.ctx, unsafe {
+            Z3_get_numerator(self.ctx.z3_ctx, self.z3_ast)
+        });
+        let den = Ast::new(self.ctx, unsafe {
+            Z3_get_denominator(self.ctx.z3_ctx, self.z3_ast)
+        });
+        Some((num, den))
+    }
+
+    /// Return the unique identifier of the term within its context.
+    ///
+    /// Z3 shares structurally equal terms, so two terms are equal exactly
+    /// when they have the same identifier; this is what `Hash` and `Eq`
+    /// are based on.
+    pub fn id(&self) -> u32 {
+        unsafe { Z3_get_ast_id(self.ctx.z3_ctx, self.z3_ast) }
+    }
+
+    /// Return the kind of the term: a numeral, an application, a bound
+    /// variable or a quantifier.
+    pub fn kind(&self) -> AstKind {
+        unsafe { Z3_get_ast_kind(self.ctx.z3_ctx, self.z3_ast) }
+    }
+
+    /// Return `true` if the term is the application of a function
+    /// declaration to arguments. Constants and numerals are applications
+    /// without arguments.
+    pub fn is_app(&self) -> bool {
+        unsafe { Z3_is_app(self.ctx.z3_ctx, self.z3_ast) }
+    }
+
+    /// Return the number of arguments of an application, or 0 if the
+    /// term is not an application.
+    pub fn num_args(&self) -> u32 {
+        if !self.is_app() {
+            return 0;
+        }
+        unsafe {
+            let app = Z3_to_app(self.ctx.z3_ctx, self.z3_ast);
+            Z3_get_app_num_args(self.ctx.z3_ctx, app)
+        }
+    }
+
+    /// Return the `i`-th argument of an application.
+    ///
+    /// # Panics
+    ///
+    /// If `i` is not less than [`Ast::num_args()`](#method.num_args).
+    pub fn arg(&self, i: u32) -> Ast<'ctx> {
+        assert!(i < self.num_args());
+        Ast::new(self.ctx, unsafe {
+            let app = Z3_to_app(self.ctx.z3_ctx, self.z3_ast);
+            Z3_get_app_arg(self.ctx.z3_ctx, app, i)
+        })
+    }
+
+    /// Return all arguments of an application, which is empty if the
+    /// term is not an application.
+    pub fn args(&self) -> Vec<Ast<'ctx>> {
+        (0..self.num_args()).map(|i| self.arg(i)).collect()
+    }
+
+    /// Return the function declaration of an application.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_int_const("x");
+    /// let e = x.add(&[&ctx.from_i64(1)]);
+    ///
+    /// assert_eq!(e.kind(), AstKind::App);
+    /// assert_eq!(e.decl().name(), "+");
+    /// assert_eq!(e.num_args(), 2);
+    /// assert_eq!(e.arg(0).decl().name(), "x");
+    /// assert_eq!(e.arg(1).kind(), AstKind::Numeral);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the term is not an application, see
+    /// [`Ast::is_app()`](#method.is_app).
+    pub fn decl(&self) -> FuncDecl<'ctx> {
+        assert!(self.is_app());
+        unsafe {
+            let app = Z3_to_app(self.ctx.z3_ctx, self.z3_ast);
+            FuncDecl::from_raw(self.ctx, Z3_get_app_decl(self.ctx.z3_ctx, app))
+        }
+    }
+
+    /// Copy the term into the context `dest`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_int_const("x");
+    /// let f = x.add(&[&ctx.from_i64(1)]).gt(&x);
+    ///
+    /// let dest = Context::new(&cfg);
+    /// let solver = Solver::new(&dest);
+    /// solver.assert(&f.translate(&dest).not());
+    /// assert_eq!(solver.check(), SatResult::Unsat);
+    /// ```
+    pub fn translate<'dest>(&self, dest: &'dest Context) -> Ast<'dest> {
+        Ast::new(dest, unsafe {
+            Z3_translate(self.ctx.z3_ctx, self.z3_ast, dest.z3_ctx)
+        })
+    }
+
+    /// Return the term in SMT-LIB2 syntax, preceded by declarations of
+    /// the sorts and functions it uses, one per line.
+    ///
+    /// Unlike the `Display` output, which depends on the context's
+    /// [print mode](struct.Context.html#method.set_ast_print_mode), this
+    /// always abbreviates shared subterms with `let` and can be parsed by
+    /// other SMT-LIB2 tools.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_int_const("x");
+    /// let sum = x.add(&[&ctx.from_i64(1)]);
+    ///
+    /// let smt2 = sum.mul(&[&sum]).to_smt2();
+    /// let mut lines = smt2.lines();
+    /// assert_eq!(lines.next(), Some("(declare-fun x () Int)"));
+    /// assert!(lines.next().unwrap().starts_with("(let "));
+    /// ```
+    pub fn to_smt2(&self) -> String {
+        let empty = CString::new("").unwrap();
+        let benchmark = unsafe {
+            let p = Z3_benchmark_to_smtlib_string(
+                self.ctx.z3_ctx,
+                empty.as_ptr(),
+                empty.as_ptr(),
+                empty.as_ptr(),
+                empty.as_ptr(),
+                0,
+                ::std::ptr::null(),
+                self.z3_ast,
+            );
+            CStr::from_ptr(p).to_string_lossy().into_owned()
+        };
+
+        // Z3 prints a comment and the declarations, followed by the term
+        // itself, or for a formula other than `true` by an assertion of it
+        // and a `(check-sat)`.
+        let mut smt2 = String::new();
+        let mut lines = benchmark.lines().map(str::trim).peekable();
+        while let Some(line) = lines.peek() {
+            if line.starts_with(';') || line.starts_with("(set-info") {
+            } else if line.starts_with("(declare-") || line.starts_with("(define-") {
+                smt2.push_str(line);
+                smt2.push('\n');
+            } else {
+                break;
+            }
+            lines.next();
+        }
+        let rest: Vec<&str> = lines.collect();
+        match rest.split_first() {
+            Some((&"(assert", body)) => {
+                let body = &body[..body.len() - 1];
+                let term = body.join("\n");
+                smt2.push_str(&term[..term.len() - 1]);
+            }
+            Some((&"(check-sat)", _)) => smt2.push_str("true"),
+            _ => smt2.push_str(&rest.join("\n")),
+        }
+        smt2
+    }
+
+    /// Copy the elements of a `Z3_ast_vector` returned by Z3 into a
+    /// `Vec`.
+    pub(crate) fn vec_from_ast_vector(ctx: &'ctx Context, v: Z3_ast_vector) -> Vec<Ast<'ctx>> {
+        let v = unsafe { AstVector::from_raw(ctx, v) };
+        v.to_vec()
+    }
+
+    /// Return the sort of this term.
+    pub fn get_sort(&self) -> Sort<'ctx> {
+        Sort {
+            ctx: self.ctx,
+            z3_sort: unsafe {
+                let s = Z3_get_sort(self.ctx.z3_ctx, self.z3_ast);
+                Z3_inc_ref(self.ctx.z3_ctx, Z3_sort_to_ast(self.ctx.z3_ctx, s));
+                s
+            },
+        }
+    }
+
+    pub(crate) fn sort_kind(&self) -> SortKind {
+        unsafe {
+            let s = Z3_get_sort(self.ctx.z3_ctx, self.z3_ast);
+            Z3_get_sort_kind(self.ctx.z3_ctx, s)
+        }
+    }
 
     // Boolean ops
-    trinop!(ite, Z3_mk_ite);
+
+    /// If-then-else: the term that equals `then` when the Boolean `self`
+    /// holds and `otherwise` when it does not.
+    ///
+    /// # Panics
+    ///
+    /// If `self` is not Boolean or the branches have different sorts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use z3::{Config, Context, SatResult, Solver};
+    /// # let ctx = Context::new(&Config::new());
+    /// let x = ctx.named_int_const("x");
+    /// let zero = ctx.from_i64(0);
+    /// let abs = x.lt(&zero).ite(&x.minus(), &x);
+    ///
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&abs.lt(&zero));
+    /// assert_eq!(solver.check(), SatResult::Unsat);
+    /// ```
+    pub fn ite(&self, then: &Ast<'ctx>, otherwise: &Ast<'ctx>) -> Ast<'ctx> {
+        assert!(
+            self.sort_kind() == SortKind::Bool,
+            "ite condition is not Boolean: {}",
+            self.get_sort()
+        );
+        let (then_sort, otherwise_sort) = (then.get_sort(), otherwise.get_sort());
+        assert!(
+            then_sort == otherwise_sort,
+            "ite branches have different sorts: {} and {}",
+            then_sort,
+            otherwise_sort
+        );
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_ite(self.ctx.z3_ctx, self.z3_ast, then.z3_ast, otherwise.z3_ast)
+        })
+    }
+
     binop!(iff, Z3_mk_iff);
     binop!(implies, Z3_mk_implies);
     binop!(xor, Z3_mk_xor);
@@ -244,13 +900,18 @@ impl<'ctx> Ast<'ctx> {
     binop!(set_del, Z3_mk_set_del);
     varop!(set_union, Z3_mk_set_union);
     varop!(set_intersect, Z3_mk_set_intersect);
+    binop!(set_difference, Z3_mk_set_difference);
     binop!(set_member, Z3_mk_set_member);
     binop!(set_subset, Z3_mk_set_subset);
     unop!(set_complement, Z3_mk_set_complement);
 
+    // Cardinality and pseudo-Boolean ops. These take `self` as the first
+    // of the Boolean terms; Z3 handles them natively rather than through
+    // an arithmetic encoding.
+
+    /// Constraint that at most `k` of `self` and `other` are true.
     pub fn at_most(&self, other: &[&Ast<'ctx>], k: u32) -> Ast<'ctx> {
         Ast::new(self.ctx, unsafe {
-            let guard = Z3_MUTEX.lock().unwrap();
             let mut tmp = vec![self.z3_ast];
             for a in other {
                 tmp.push(a.z3_ast)
@@ -260,10 +921,27 @@ impl<'ctx> Ast<'ctx> {
         })
     }
 
-    // pseudoboolean ops
+    /// Constraint that at least `k` of `self` and `other` are true.
+    pub fn at_least(&self, other: &[&Ast<'ctx>], k: u32) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            let mut tmp = vec![self.z3_ast];
+            for a in other {
+                tmp.push(a.z3_ast)
+            }
+            assert!(tmp.len() <= 0xffffffff);
+            Z3_mk_atleast(self.ctx.z3_ctx, tmp.len() as u32, tmp.as_ptr(), k)
+        })
+    }
+
+    /// Weighted constraint `c0*self + c1*other[0] + ... <= k`, where a
+    /// true term counts as 1 and a false one as 0.
+    ///
+    /// # Panics
+    ///
+    /// If there is not exactly one coefficient per term.
     pub fn pb_le(&self, other: &[&Ast<'ctx>], coeffs: Vec<i32>, k: i32) -> Ast<'ctx> {
+        assert_eq!(other.len() + 1, coeffs.len(), "one coefficient per term");
         Ast::new(self.ctx, unsafe {
-            let guard = Z3_MUTEX.lock().unwrap();
             let mut tmp = vec![self.z3_ast];
             for a in other {
                 tmp.push(a.z3_ast)
@@ -279,9 +957,12 @@ impl<'ctx> Ast<'ctx> {
             )
         })
     }
+
+    /// Weighted constraint `c0*self + c1*other[0] + ... >= k`. See
+    /// [`pb_le()`](#method.pb_le).
     pub fn pb_ge(&self, other: &[&Ast<'ctx>], coeffs: Vec<i32>, k: i32) -> Ast<'ctx> {
+        assert_eq!(other.len() + 1, coeffs.len(), "one coefficient per term");
         Ast::new(self.ctx, unsafe {
-            let guard = Z3_MUTEX.lock().unwrap();
             let mut tmp = vec![self.z3_ast];
             for a in other {
                 tmp.push(a.z3_ast)
@@ -297,9 +978,12 @@ impl<'ctx> Ast<'ctx> {
             )
         })
     }
+
+    /// Weighted constraint `c0*self + c1*other[0] + ... = k`. See
+    /// [`pb_le()`](#method.pb_le).
     pub fn pb_eq(&self, other: &[&Ast<'ctx>], coeffs: Vec<i32>, k: i32) -> Ast<'ctx> {
+        assert_eq!(other.len() + 1, coeffs.len(), "one coefficient per term");
         Ast::new(self.ctx, unsafe {
-            let guard = Z3_MUTEX.lock().unwrap();
             let mut tmp = vec![self.z3_ast];
             for a in other {
                 tmp.push(a.z3_ast)
@@ -317,14 +1001,71 @@ impl<'ctx> Ast<'ctx> {
     }
 }
 
+// Operators dispatch on the sort of the left operand, so that the same
+// operator works for both bitvector and arithmetic/Boolean terms.
+impl_binary_op!(Ast, Add, add, |a, b| if a.sort_kind() == SortKind::BV {
+    a.bvadd(b)
+} else {
+    Ast::add(a, &[b])
+});
+impl_binary_op!(Ast, Sub, sub, |a, b| if a.sort_kind() == SortKind::BV {
+    a.bvsub(b)
+} else {
+    Ast::sub(a, &[b])
+});
+impl_binary_op!(Ast, Mul, mul, |a, b| if a.sort_kind() == SortKind::BV {
+    a.bvmul(b)
+} else {
+    Ast::mul(a, &[b])
+});
+impl_binary_op!(
+    Ast,
+    BitAnd,
+    bitand,
+    |a, b| if a.sort_kind() == SortKind::BV {
+        a.bvand(b)
+    } else {
+        a.and(&[b])
+    }
+);
+impl_binary_op!(Ast, BitOr, bitor, |a, b| if a.sort_kind() == SortKind::BV {
+    a.bvor(b)
+} else {
+    a.or(&[b])
+});
+impl_binary_op!(
+    Ast,
+    BitXor,
+    bitxor,
+    |a, b| if a.sort_kind() == SortKind::BV {
+        a.bvxor(b)
+    } else {
+        a.xor(b)
+    }
+);
+// Bitvectors carry no signedness, so `>>` is a logical shift; use
+// `Ast::bvashr()` for an arithmetic one.
+impl_binary_op!(Ast, Shl, shl, |a, b| a.bvshl(b));
+impl_binary_op!(Ast, Shr, shr, |a, b| a.bvlshr(b));
+
+impl_unary_op!(Ast, Neg, neg, |a| if a.sort_kind() == SortKind::BV {
+    a.bvneg()
+} else {
+    a.minus()
+});
+impl_unary_op!(Ast, Not, not, |a| if a.sort_kind() == SortKind::BV {
+    a.bvnot()
+} else {
+    Ast::not(a)
+});
+
 impl<'ctx> fmt::Display for Ast<'ctx> {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        let p =
-            unsafe { CStr::from_ptr(Z3_ast_to_string(self.ctx.z3_ctx, self.z3_ast) as *mut i8) };
-        if p.as_ptr().is_null() {
+        let p = unsafe { Z3_ast_to_string(self.ctx.z3_ctx, self.z3_ast) };
+        if p.is_null() {
             return Result::Err(fmt::Error);
         }
-        match p.to_str() {
+        match unsafe { CStr::from_ptr(p) }.to_str() {
             Ok(s) => write!(f, "{}", s),
             Err(_) => Result::Err(fmt::Error),
         }
@@ -342,7 +1083,6 @@ impl<'ctx> Drop for Ast<'ctx> {
     fn drop(&mut self) {
         unsafe {
             debug!("drop ast {:p}", self.z3_ast);
-            let guard = Z3_MUTEX.lock().unwrap();
             Z3_dec_ref(self.ctx.z3_ctx, self.z3_ast);
         }
     }
@@ -350,10 +1090,7 @@ impl<'ctx> Drop for Ast<'ctx> {
 
 impl<'ctx> Hash for Ast<'ctx> {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        unsafe {
-            let u = Z3_get_ast_hash(self.ctx.z3_ctx, self.z3_ast);
-            u.hash(state);
-        }
+        self.id().hash(state);
     }
 }
 
@@ -0,0 +1,114 @@
+use std::ffi::CStr;
+use std::fmt;
+use z3_sys::*;
+use Ast;
+use AstVector;
+use Context;
+
+impl<'ctx> AstVector<'ctx> {
+    /// Create an empty vector.
+    pub fn new(ctx: &'ctx Context) -> AstVector<'ctx> {
+        unsafe { AstVector::from_raw(ctx, Z3_mk_ast_vector(ctx.z3_ctx)) }
+    }
+
+    /// Create a vector holding the elements of `asts`.
+    pub(crate) fn from_slice(ctx: &'ctx Context, asts: &[&Ast<'ctx>]) -> AstVector<'ctx> {
+        let mut v = AstVector::new(ctx);
+        for a in asts {
+            v.push(a);
+        }
+        v
+    }
+
+    /// Wrap a vector returned by Z3.
+    pub(crate) unsafe fn from_raw(ctx: &'ctx Context, v: Z3_ast_vector) -> AstVector<'ctx> {
+        ctx.check_null(v);
+        Z3_ast_vector_inc_ref(ctx.z3_ctx, v);
+        AstVector {
+            ctx,
+            z3_ast_vector: v,
+        }
+    }
+
+    /// Return the number of elements.
+    pub fn len(&self) -> usize {
+        unsafe { Z3_ast_vector_size(self.ctx.z3_ctx, self.z3_ast_vector) as usize }
+    }
+
+    /// Return `true` if the vector has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the element at index `i`, or `None` if `i` is out of
+    /// bounds.
+    pub fn get(&self, i: usize) -> Option<Ast<'ctx>> {
+        if i >= self.len() {
+            return None;
+        }
+        Some(Ast::new(self.ctx, unsafe {
+            Z3_ast_vector_get(self.ctx.z3_ctx, self.z3_ast_vector, i as u32)
+        }))
+    }
+
+    /// Replace the element at index `i` with `ast`.
+    ///
+    /// # Panics
+    ///
+    /// If `i` is out of bounds.
+    pub fn set(&mut self, i: usize, ast: &Ast<'ctx>) {
+        assert!(i < self.len());
+        unsafe { Z3_ast_vector_set(self.ctx.z3_ctx, self.z3_ast_vector, i as u32, ast.z3_ast) };
+    }
+
+    /// Append `ast` to the vector.
+    pub fn push(&mut self, ast: &Ast<'ctx>) {
+        unsafe { Z3_ast_vector_push(self.ctx.z3_ctx, self.z3_ast_vector, ast.z3_ast) };
+    }
+
+    /// Shrink the vector to its first `n` elements. Only shrinking is
+    /// supported, as the vector cannot hold placeholder elements.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is greater than the length of the vector.
+    pub fn truncate(&mut self, n: usize) {
+        assert!(n <= self.len());
+        unsafe { Z3_ast_vector_resize(self.ctx.z3_ctx, self.z3_ast_vector, n as u32) };
+    }
+
+    /// Iterate over the elements of the vector.
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = Ast<'ctx>> + 'a {
+        (0..self.len()).map(move |i| self.get(i).unwrap())
+    }
+
+    /// Copy the elements into a `Vec`.
+    pub fn to_vec(&self) -> Vec<Ast<'ctx>> {
+        self.iter().collect()
+    }
+}
+
+impl<'ctx> From<AstVector<'ctx>> for Vec<Ast<'ctx>> {
+    fn from(v: AstVector<'ctx>) -> Vec<Ast<'ctx>> {
+        v.to_vec()
+    }
+}
+
+impl<'ctx> fmt::Display for AstVector<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let p = unsafe { Z3_ast_vector_to_string(self.ctx.z3_ctx, self.z3_ast_vector) };
+        if p.is_null() {
+            return Result::Err(fmt::Error);
+        }
+        match unsafe { CStr::from_ptr(p) }.to_str() {
+            Ok(s) => write!(f, "{}", s),
+            Err(_) => Result::Err(fmt::Error),
+        }
+    }
+}
+
+impl<'ctx> Drop for AstVector<'ctx> {
+    fn drop(&mut self) {
+        unsafe { Z3_ast_vector_dec_ref(self.ctx.z3_ctx, self.z3_ast_vector) };
+    }
+}
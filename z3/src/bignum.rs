@@ -0,0 +1,61 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use Ast;
+use Context;
+use Sort;
+
+impl<'ctx> Ast<'ctx> {
+    /// Create an integer numeral with the value `i`.
+    ///
+    /// Requires the `num` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # extern crate num_bigint;
+    /// # extern crate z3;
+    /// use num_bigint::BigInt;
+    /// use z3::*;
+    ///
+    /// # fn main() {
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let big: BigInt = "123456789012345678901234567890".parse().unwrap();
+    /// let y = ctx.named_int_const("y");
+    ///
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&y._eq(&Ast::from_bigint(&ctx, &big).mul(&[&ctx.from_i64(2)])));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    ///
+    /// let model = solver.get_model().unwrap();
+    /// assert_eq!(model.eval(&y, true).unwrap().as_bigint(), Some(big * 2));
+    /// # }
+    /// ```
+    pub fn from_bigint(ctx: &'ctx Context, i: &BigInt) -> Ast<'ctx> {
+        Ast::numeral_from_str(ctx, &i.to_string(), &Sort::int(ctx))
+    }
+
+    /// Create a real numeral with the value `r`.
+    ///
+    /// Requires the `num` feature.
+    pub fn from_big_rational(ctx: &'ctx Context, r: &BigRational) -> Ast<'ctx> {
+        Ast::numeral_from_str(ctx, &r.to_string(), &Sort::real(ctx))
+    }
+
+    /// Return the value of an integer-valued numeral, or `None` if
+    /// `self` is not a numeral or its value is not an integer.
+    ///
+    /// Requires the `num` feature.
+    pub fn as_bigint(&self) -> Option<BigInt> {
+        self.numeral_string()?.parse().ok()
+    }
+
+    /// Return the value of an integer or real numeral as a fraction, or
+    /// `None` if `self` is not such a numeral.
+    ///
+    /// Requires the `num` feature.
+    pub fn as_big_rational(&self) -> Option<BigRational> {
+        let (num, den) = self.numerator_denominator()?;
+        Some(BigRational::new(num.as_bigint()?, den.as_bigint()?))
+    }
+}
@@ -0,0 +1,267 @@
+use std::ffi::CString;
+use std::fmt;
+use std::ops::{Add, BitAnd, BitOr, BitXor, Deref, Mul, Neg, Not, Shl, Shr, Sub};
+use z3_sys::*;
+use Ast;
+use Context;
+use Sort;
+use BV;
+
+macro_rules! unop {
+    ( $f:ident, $z3fn:ident ) => {
+        pub fn $f(&self) -> BV<'ctx> {
+            BV::wrap(self.ctx, unsafe { $z3fn(self.ctx.z3_ctx, self.z3_ast) })
+        }
+    };
+}
+
+macro_rules! binop {
+    ( $f:ident, $z3fn:ident ) => {
+        pub fn $f(&self, other: &BV<'ctx>) -> BV<'ctx> {
+            BV::wrap(self.ctx, unsafe {
+                $z3fn(self.ctx.z3_ctx, self.z3_ast, other.z3_ast)
+            })
+        }
+    };
+}
+
+macro_rules! pred {
+    ( $f:ident, $z3fn:ident ) => {
+        pub fn $f(&self, other: &BV<'ctx>) -> Ast<'ctx> {
+            Ast::new(self.ctx, unsafe {
+                $z3fn(self.ctx.z3_ctx, self.z3_ast, other.z3_ast)
+            })
+        }
+    };
+}
+
+macro_rules! signed_pred {
+    ( $f:ident, $z3fn:ident ) => {
+        pub fn $f(&self, other: &BV<'ctx>, signed: bool) -> Ast<'ctx> {
+            Ast::new(self.ctx, unsafe {
+                $z3fn(self.ctx.z3_ctx, self.z3_ast, other.z3_ast, signed)
+            })
+        }
+    };
+}
+
+macro_rules! indexop {
+    ( $f:ident, $z3fn:ident ) => {
+        pub fn $f(&self, i: u32) -> BV<'ctx> {
+            BV::wrap(self.ctx, unsafe { $z3fn(self.ctx.z3_ctx, i, self.z3_ast) })
+        }
+    };
+}
+
+impl<'ctx> BV<'ctx> {
+    fn wrap(ctx: &'ctx Context, ast: Z3_ast) -> BV<'ctx> {
+        BV {
+            ast: Ast::new(ctx, ast),
+        }
+    }
+
+    /// Wrap an `Ast` as a `BV`, returning `None` if it does not have
+    /// bitvector sort.
+    pub fn from_ast(ast: Ast<'ctx>) -> Option<BV<'ctx>> {
+        if ast.sort_kind() == SortKind::BV {
+            Some(BV { ast })
+        } else {
+            None
+        }
+    }
+
+    /// Create a bitvector constant of `sz` bits named `name`.
+    pub fn new_const(ctx: &'ctx Context, name: &str, sz: u32) -> BV<'ctx> {
+        BV {
+            ast: ctx.named_bitvector_const(name, sz),
+        }
+    }
+
+    /// Create a fresh bitvector constant of `sz` bits, whose name starts
+    /// with `prefix`.
+    pub fn fresh_const(ctx: &'ctx Context, prefix: &str, sz: u32) -> BV<'ctx> {
+        let sort = Sort::bitvector(ctx, sz);
+        BV::wrap(ctx, unsafe {
+            let pp = CString::new(prefix).unwrap();
+            let p = pp.as_ptr();
+            Z3_mk_fresh_const(ctx.z3_ctx, p, sort.z3_sort)
+        })
+    }
+
+    /// Create a bitvector numeral of `sz` bits from an unsigned integer.
+    ///
+    /// `u` is truncated to its low `sz` bits.
+    pub fn from_u64(ctx: &'ctx Context, u: u64, sz: u32) -> BV<'ctx> {
+        BV {
+            ast: Sort::bitvector(ctx, sz).from_u64(u),
+        }
+    }
+
+    /// Create a bitvector numeral of `sz` bits from a signed integer,
+    /// using two's complement.
+    pub fn from_i64(ctx: &'ctx Context, i: i64, sz: u32) -> BV<'ctx> {
+        BV {
+            ast: Sort::bitvector(ctx, sz).from_i64(i),
+        }
+    }
+
+    /// Convert the integer term `ast` to a bitvector of `sz` bits, which
+    /// is its value modulo `2^sz`.
+    ///
+    /// # See also:
+    ///
+    /// - [`BV::to_int()`](#method.to_int)
+    pub fn from_int(ast: &Ast<'ctx>, sz: u32) -> BV<'ctx> {
+        BV::wrap(ast.ctx, unsafe {
+            Z3_mk_int2bv(ast.ctx.z3_ctx, sz, ast.z3_ast)
+        })
+    }
+
+    /// Convert this bitvector to an integer term, reading it in two's
+    /// complement if `signed` is `true` and as unsigned otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use z3::{BV, Config, Context};
+    /// # let ctx = Context::new(&Config::new());
+    /// let minus_one = BV::from_i64(&ctx, -1, 8);
+    /// assert_eq!(minus_one.to_int(false).simplify().as_i64(), Some(255));
+    /// assert_eq!(minus_one.to_int(true).simplify().as_i64(), Some(-1));
+    /// ```
+    ///
+    /// # See also:
+    ///
+    /// - [`BV::from_int()`](#method.from_int)
+    pub fn to_int(&self, signed: bool) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_bv2int(self.ctx.z3_ctx, self.z3_ast, signed)
+        })
+    }
+
+    /// Return the size of this bitvector in bits.
+    pub fn get_size(&self) -> u32 {
+        unsafe {
+            let s = Z3_get_sort(self.ctx.z3_ctx, self.z3_ast);
+            Z3_get_bv_sort_size(self.ctx.z3_ctx, s)
+        }
+    }
+
+    // Bitwise ops
+    unop!(bvnot, Z3_mk_bvnot);
+    unop!(bvredand, Z3_mk_bvredand);
+    unop!(bvredor, Z3_mk_bvredor);
+    binop!(bvand, Z3_mk_bvand);
+    binop!(bvor, Z3_mk_bvor);
+    binop!(bvxor, Z3_mk_bvxor);
+    binop!(bvnand, Z3_mk_bvnand);
+    binop!(bvnor, Z3_mk_bvnor);
+    binop!(bvxnor, Z3_mk_bvxnor);
+
+    // Arithmetic ops
+    unop!(bvneg, Z3_mk_bvneg);
+    binop!(bvadd, Z3_mk_bvadd);
+    binop!(bvsub, Z3_mk_bvsub);
+    binop!(bvmul, Z3_mk_bvmul);
+    binop!(bvudiv, Z3_mk_bvudiv);
+    binop!(bvsdiv, Z3_mk_bvsdiv);
+    binop!(bvurem, Z3_mk_bvurem);
+    binop!(bvsrem, Z3_mk_bvsrem);
+    binop!(bvsmod, Z3_mk_bvsmod);
+
+    // Shifts and rotations. The `ext_` rotations take the amount as a
+    // bitvector term rather than a constant.
+    binop!(bvshl, Z3_mk_bvshl);
+    binop!(bvlshr, Z3_mk_bvlshr);
+    binop!(bvashr, Z3_mk_bvashr);
+    indexop!(rotate_left, Z3_mk_rotate_left);
+    indexop!(rotate_right, Z3_mk_rotate_right);
+    binop!(ext_rotate_left, Z3_mk_ext_rotate_left);
+    binop!(ext_rotate_right, Z3_mk_ext_rotate_right);
+
+    // Comparisons
+    pred!(bvult, Z3_mk_bvult);
+    pred!(bvslt, Z3_mk_bvslt);
+    pred!(bvule, Z3_mk_bvule);
+    pred!(bvsle, Z3_mk_bvsle);
+    pred!(bvuge, Z3_mk_bvuge);
+    pred!(bvsge, Z3_mk_bvsge);
+    pred!(bvugt, Z3_mk_bvugt);
+    pred!(bvsgt, Z3_mk_bvsgt);
+
+    // Overflow checks. Each is true if the operation does not wrap
+    // around; those taking `signed` check for signed or unsigned
+    // wraparound, the others only make sense for signed operands.
+    signed_pred!(bvadd_no_overflow, Z3_mk_bvadd_no_overflow);
+    pred!(bvadd_no_underflow, Z3_mk_bvadd_no_underflow);
+    pred!(bvsub_no_overflow, Z3_mk_bvsub_no_overflow);
+    signed_pred!(bvsub_no_underflow, Z3_mk_bvsub_no_underflow);
+    signed_pred!(bvmul_no_overflow, Z3_mk_bvmul_no_overflow);
+    pred!(bvmul_no_underflow, Z3_mk_bvmul_no_underflow);
+    pred!(bvsdiv_no_overflow, Z3_mk_bvsdiv_no_overflow);
+
+    /// True if negating this bitvector, read as signed, does not
+    /// overflow, i.e. it is not the minimum signed value.
+    pub fn bvneg_no_overflow(&self) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_bvneg_no_overflow(self.ctx.z3_ctx, self.z3_ast)
+        })
+    }
+
+    // Slicing and extension
+
+    /// Concatenate two bitvectors. The result has the bits of `self`
+    /// as its most significant part.
+    pub fn concat(&self, other: &BV<'ctx>) -> BV<'ctx> {
+        BV::wrap(self.ctx, unsafe {
+            Z3_mk_concat(self.ctx.z3_ctx, self.z3_ast, other.z3_ast)
+        })
+    }
+
+    /// Extract the bits `high` down to `low` (both inclusive), yielding
+    /// a bitvector of size `high - low + 1`.
+    ///
+    /// # Preconditions:
+    ///
+    /// - `self.get_size() > high >= low`
+    pub fn extract(&self, high: u32, low: u32) -> BV<'ctx> {
+        BV::wrap(self.ctx, unsafe {
+            Z3_mk_extract(self.ctx.z3_ctx, high, low, self.z3_ast)
+        })
+    }
+
+    indexop!(sign_ext, Z3_mk_sign_ext);
+    indexop!(zero_ext, Z3_mk_zero_ext);
+    indexop!(repeat, Z3_mk_repeat);
+}
+
+impl<'ctx> Deref for BV<'ctx> {
+    type Target = Ast<'ctx>;
+
+    fn deref(&self) -> &Ast<'ctx> {
+        &self.ast
+    }
+}
+
+impl<'ctx> From<BV<'ctx>> for Ast<'ctx> {
+    fn from(bv: BV<'ctx>) -> Ast<'ctx> {
+        bv.ast
+    }
+}
+
+impl<'ctx> fmt::Display for BV<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt::Display::fmt(&self.ast, f)
+    }
+}
+
+impl_binary_op!(BV, Add, add, |a, b| a.bvadd(b));
+impl_binary_op!(BV, Sub, sub, |a, b| a.bvsub(b));
+impl_binary_op!(BV, Mul, mul, |a, b| a.bvmul(b));
+impl_binary_op!(BV, BitAnd, bitand, |a, b| a.bvand(b));
+impl_binary_op!(BV, BitOr, bitor, |a, b| a.bvor(b));
+impl_binary_op!(BV, BitXor, bitxor, |a, b| a.bvxor(b));
+impl_binary_op!(BV, Shl, shl, |a, b| a.bvshl(b));
+impl_binary_op!(BV, Shr, shr, |a, b| a.bvlshr(b));
+impl_unary_op!(BV, Neg, neg, |a| a.bvneg());
+impl_unary_op!(BV, Not, not, |a| a.bvnot());
@@ -0,0 +1,64 @@
+use z3_sys::*;
+use CheckResult;
+use Model;
+use SatResult;
+
+impl<'a> CheckResult<'a> {
+    /// Return the outcome of the check, dropping any model.
+    pub fn sat_result(&self) -> SatResult {
+        match self {
+            CheckResult::Satisfiable(_) => SatResult::Sat,
+            CheckResult::Unknown(_, _) => SatResult::Unknown,
+            CheckResult::Unsatisfiable => SatResult::Unsat,
+        }
+    }
+
+    /// Return `true` if the result is
+    /// [`Satisfiable`](#variant.Satisfiable).
+    pub fn is_sat(&self) -> bool {
+        matches!(self, CheckResult::Satisfiable(_))
+    }
+
+    /// Return `true` if the result is
+    /// [`Unsatisfiable`](#variant.Unsatisfiable).
+    pub fn is_unsat(&self) -> bool {
+        matches!(self, CheckResult::Unsatisfiable)
+    }
+
+    /// Return the model of a satisfiable result, or the partial model of
+    /// an unknown result if there is one.
+    pub fn model(&self) -> Option<&Model<'a>> {
+        match self {
+            CheckResult::Satisfiable(m) => Some(m),
+            CheckResult::Unknown(m, _) => m.as_ref(),
+            CheckResult::Unsatisfiable => None,
+        }
+    }
+
+    /// Return the model of a satisfiable result.
+    ///
+    /// # Panics
+    ///
+    /// If the result is not satisfiable.
+    pub fn unwrap_sat(self) -> Model<'a> {
+        match self {
+            CheckResult::Satisfiable(m) => m,
+            CheckResult::Unknown(_, reason) => {
+                panic!("called `unwrap_sat()` on an unknown result: {}", reason)
+            }
+            CheckResult::Unsatisfiable => {
+                panic!("called `unwrap_sat()` on an unsatisfiable result")
+            }
+        }
+    }
+}
+
+impl SatResult {
+    pub(crate) fn from_lbool(l: Z3_lbool) -> SatResult {
+        match l {
+            Z3_L_TRUE => SatResult::Sat,
+            Z3_L_FALSE => SatResult::Unsat,
+            _ => SatResult::Unknown,
+        }
+    }
+}
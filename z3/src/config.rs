@@ -1,3 +1,4 @@
+use memory;
 use std::ffi::CString;
 use z3_sys::*;
 use Config;
@@ -10,6 +11,7 @@ impl Config {
             z3_cfg: unsafe {
                 let guard = Z3_MUTEX.lock().unwrap();
                 let p = Z3_mk_config();
+                memory::object_created();
                 debug!("new config {:p}", p);
                 p
             },
@@ -61,5 +63,6 @@ impl Drop for Config {
     fn drop(&mut self) {
         let guard = Z3_MUTEX.lock().unwrap();
         unsafe { Z3_del_config(self.z3_cfg) };
+        memory::object_deleted();
     }
 }
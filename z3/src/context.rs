@@ -1,38 +1,221 @@
+use memory;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
 use z3_sys::*;
 use Ast;
+use AstVector;
 use Config;
 use Context;
+use ContextHandle;
+use FuncDecl;
 use Sort;
 use Symbol;
+use Z3Error;
 use Z3_MUTEX;
 
+// Datatypes declared for types implementing `Z3Datatype`, by context and
+// Rust type, each holding a reference to its sort. This lives outside
+// `Context` so that the context stays free of interior mutability.
+lazy_static! {
+    static ref DATATYPES: Mutex<HashMap<(usize, TypeId), usize>> = Mutex::new(HashMap::new());
+}
+
+/// Convert `path` for a Z3 function that reads a file, failing like Z3
+/// does for a file it cannot access if the path is not valid UTF-8.
+pub(crate) fn path_to_cstring(path: &Path) -> Result<CString, Z3Error> {
+    match path.to_str() {
+        Some(s) => Ok(CString::new(s).unwrap()),
+        None => Err(Z3Error {
+            code: ErrorCode::FileAccessError,
+            message: format!("invalid path: {}", path.display()),
+        }),
+    }
+}
+
 impl Context {
     pub fn new(cfg: &Config) -> Context {
         Context {
             z3_ctx: unsafe {
                 let guard = Z3_MUTEX.lock().unwrap();
                 let p = Z3_mk_context_rc(cfg.z3_cfg);
+                memory::object_created();
+                // Errors are checked for after the calls that can raise
+                // them; Z3's default handler would exit the process.
+                Z3_set_error_handler(p, None);
                 debug!("new context {:p}", p);
                 p
             },
         }
     }
 
+    /// Create a context managed by an `Arc`, for use with the owned
+    /// handle types such as [`OwnedAst`](struct.OwnedAst.html), which have
+    /// no lifetime parameter.
+    ///
+    /// The `Arc` only shares the context within a thread: `Context` is
+    /// not `Send` or `Sync`, so neither is the result. A
+    /// [`DetachedSolver`](struct.DetachedSolver.html) carries a problem
+    /// over to another thread.
+    #[allow(clippy::arc_with_non_send_sync)]
+    pub fn new_shared(cfg: &Config) -> Arc<Context> {
+        Arc::new(Context::new(cfg))
+    }
+
+    /// Run `f`, which calls into Z3, and return the error it raised, if
+    /// any.
+    ///
+    /// Calls whose errors are not checked can leave one pending, which
+    /// is discarded first so that it is not blamed on `f`.
+    pub(crate) unsafe fn try_z3<T, F: FnOnce() -> T>(&self, f: F) -> Result<T, Z3Error> {
+        self.take_error();
+        let result = f();
+        match self.take_error() {
+            None => Ok(result),
+            Some(e) => Err(e),
+        }
+    }
+
+    /// Return the datatype declared for the Rust type `id`, if any.
+    pub(crate) fn cached_datatype(&self, id: TypeId) -> Option<Sort<'_>> {
+        let datatypes = DATATYPES.lock().unwrap();
+        let s = *datatypes.get(&(self.z3_ctx as usize, id))? as Z3_sort;
+        unsafe { Z3_inc_ref(self.z3_ctx, Z3_sort_to_ast(self.z3_ctx, s)) };
+        Some(Sort {
+            ctx: self,
+            z3_sort: s,
+        })
+    }
+
+    /// Remember `sort` as the datatype of the Rust type `id`.
+    pub(crate) fn cache_datatype(&self, id: TypeId, sort: &Sort) {
+        unsafe { Z3_inc_ref(self.z3_ctx, Z3_sort_to_ast(self.z3_ctx, sort.z3_sort)) };
+        let mut datatypes = DATATYPES.lock().unwrap();
+        let old = datatypes.insert((self.z3_ctx as usize, id), sort.z3_sort as usize);
+        assert!(old.is_none());
+    }
+
+    /// Return the error raised by the last call into Z3, if any, and
+    /// clear it. Not every function clears the error of an earlier call
+    /// itself.
+    fn take_error(&self) -> Option<Z3Error> {
+        let code = unsafe { Z3_get_error_code(self.z3_ctx) };
+        if code == ErrorCode::OK {
+            return None;
+        }
+        let msg = unsafe { CStr::from_ptr(Z3_get_error_msg(self.z3_ctx, code)) };
+        let error = Z3Error {
+            code,
+            message: msg.to_string_lossy().into_owned(),
+        };
+        unsafe { Z3_set_error(self.z3_ctx, ErrorCode::OK) };
+        Some(error)
+    }
+
+    /// Run `f`, which calls into Z3, and panic with the error it raised,
+    /// if any.
+    ///
+    /// This is for errors that only a bug in the caller can cause, such as
+    /// applying an operation to terms of the wrong sort. Operations that
+    /// can fail on valid input, e.g. parsing, use `try_z3` and return the
+    /// error instead.
+    pub(crate) unsafe fn check_z3<T, F: FnOnce() -> T>(&self, f: F) -> T {
+        match self.try_z3(f) {
+            Ok(result) => result,
+            Err(e) => panic!("Z3 error: {}", e.message),
+        }
+    }
+
+    /// Panic with the error raised by the last call into Z3 if it
+    /// returned `p` and `p` is null.
+    pub(crate) fn check_null<T>(&self, p: *mut T) {
+        if p.is_null() {
+            if let Some(e) = self.take_error() {
+                panic!("Z3 error: {}", e.message);
+            }
+            panic!("Z3 returned a null pointer");
+        }
+    }
+
+    /// Interrupt a solver performing a satisfiability test, a tactic
+    /// processing a goal, or a simplify operation running in this
+    /// context. The interrupted operation returns as if it had reached a
+    /// resource limit, e.g. [`Solver::check()`] reports unknown with the
+    /// reason `interrupted`.
+    ///
+    /// Unlike other operations this may be called while another thread is
+    /// inside a check in this context. Calling it when no operation is
+    /// running has no effect.
+    ///
+    /// To interrupt from another thread, pass it a
+    /// [`handle()`](#method.handle).
+    ///
+    /// [`Solver::check()`]: struct.Solver.html#method.check
+    pub fn interrupt(&self) {
+        unsafe { Z3_interrupt(self.z3_ctx) };
+    }
+
+    /// Get a handle that other threads can use to interrupt this
+    /// context.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use z3::{Config, Context};
+    /// use std::thread;
+    ///
+    /// let ctx = Context::new(&Config::new());
+    /// let handle = ctx.handle();
+    /// thread::scope(|s| {
+    ///     s.spawn(|| handle.interrupt());
+    /// });
+    /// ```
+    pub fn handle(&self) -> ContextHandle<'_> {
+        ContextHandle {
+            z3_ctx: self.z3_ctx,
+            ctx: PhantomData,
+        }
+    }
+
+    /// Set the format in which ASTs, sorts, function declarations and
+    /// models of this context are displayed.
+    ///
+    /// The default, [`AstPrintMode::SmtLibFull`], writes out shared
+    /// subterms every time they occur, which can make the output of large
+    /// terms grow exponentially. [`AstPrintMode::SmtLib2Compliant`]
+    /// abbreviates them with `let` instead, and is the mode to use when
+    /// the output is read back by other SMT-LIB2 tools.
+    /// [`AstPrintMode::LowLevel`] shows Z3's internal representation.
+    ///
+    /// [`AstPrintMode::SmtLibFull`]: enum.AstPrintMode.html#variant.SmtLibFull
+    /// [`AstPrintMode::SmtLib2Compliant`]: enum.AstPrintMode.html#variant.SmtLib2Compliant
+    /// [`AstPrintMode::LowLevel`]: enum.AstPrintMode.html#variant.LowLevel
+    pub fn set_ast_print_mode(&self, mode: AstPrintMode) {
+        unsafe { Z3_set_ast_print_mode(self.z3_ctx, mode) };
+    }
+
     // Helpers for common constructions
 
-    pub fn bool_sort(&self) -> Sort {
+    pub fn uninterpreted_sort<'ctx>(&'ctx self, sym: &Symbol<'ctx>) -> Sort<'ctx> {
+        Sort::uninterpreted(self, sym)
+    }
+
+    pub fn bool_sort(&self) -> Sort<'_> {
         Sort::bool(self)
     }
 
-    pub fn int_sort(&self) -> Sort {
+    pub fn int_sort(&self) -> Sort<'_> {
         Sort::int(self)
     }
 
-    pub fn real_sort(&self) -> Sort {
+    pub fn real_sort(&self) -> Sort<'_> {
         Sort::real(self)
     }
 
-    pub fn bitvector_sort(&self, sz: u32) -> Sort {
+    pub fn bitvector_sort(&self, sz: u32) -> Sort<'_> {
         Sort::bitvector(self, sz)
     }
 
@@ -44,11 +227,52 @@ impl Context {
         Sort::set(self, elt)
     }
 
-    pub fn int_sym(&self, i: u32) -> Symbol {
+    pub fn string_sort(&self) -> Sort<'_> {
+        Sort::string(self)
+    }
+
+    pub fn seq_sort<'ctx>(&'ctx self, elt: &Sort<'ctx>) -> Sort<'ctx> {
+        Sort::seq(self, elt)
+    }
+
+    pub fn regexp_sort<'ctx>(&'ctx self, seq: &Sort<'ctx>) -> Sort<'ctx> {
+        Sort::regexp(self, seq)
+    }
+
+    pub fn finite_domain_sort<'ctx>(&'ctx self, name: &Symbol<'ctx>, size: u64) -> Sort<'ctx> {
+        Sort::finite_domain(self, name, size)
+    }
+
+    pub fn float_sort(&self, ebits: u32, sbits: u32) -> Sort<'_> {
+        Sort::float(self, ebits, sbits)
+    }
+
+    pub fn float32_sort(&self) -> Sort<'_> {
+        Sort::float32(self)
+    }
+
+    pub fn float64_sort(&self) -> Sort<'_> {
+        Sort::float64(self)
+    }
+
+    pub fn rounding_mode_sort(&self) -> Sort<'_> {
+        Sort::rounding_mode(self)
+    }
+
+    pub fn func_decl<'ctx>(
+        &'ctx self,
+        name: &Symbol<'ctx>,
+        domain: &[&Sort<'ctx>],
+        range: &Sort<'ctx>,
+    ) -> FuncDecl<'ctx> {
+        FuncDecl::new(self, name, domain, range)
+    }
+
+    pub fn int_sym(&self, i: u32) -> Symbol<'_> {
         Symbol::from_int(self, i)
     }
 
-    pub fn str_sym(&self, s: &str) -> Symbol {
+    pub fn str_sym(&self, s: &str) -> Symbol<'_> {
         Symbol::from_string(self, s)
     }
 
@@ -64,11 +288,11 @@ impl Context {
         Ast::fresh_const(self, prefix, sort)
     }
 
-    pub fn named_bool_const(&self, s: &str) -> Ast {
+    pub fn named_bool_const(&self, s: &str) -> Ast<'_> {
         Ast::new_const(&self.str_sym(s), &self.bool_sort())
     }
 
-    pub fn numbered_bool_const(&self, i: u32) -> Ast {
+    pub fn numbered_bool_const(&self, i: u32) -> Ast<'_> {
         Ast::new_const(&self.int_sym(i), &self.bool_sort())
     }
 
@@ -76,11 +300,11 @@ impl Context {
         Ast::fresh_const(self, prefix, &self.bool_sort())
     }
 
-    pub fn named_int_const(&self, s: &str) -> Ast {
+    pub fn named_int_const(&self, s: &str) -> Ast<'_> {
         Ast::new_const(&self.str_sym(s), &self.int_sort())
     }
 
-    pub fn numbered_int_const(&self, i: u32) -> Ast {
+    pub fn numbered_int_const(&self, i: u32) -> Ast<'_> {
         Ast::new_const(&self.int_sym(i), &self.int_sort())
     }
 
@@ -88,11 +312,11 @@ impl Context {
         Ast::fresh_const(self, prefix, &self.int_sort())
     }
 
-    pub fn named_real_const(&self, s: &str) -> Ast {
+    pub fn named_real_const(&self, s: &str) -> Ast<'_> {
         Ast::new_const(&self.str_sym(s), &self.real_sort())
     }
 
-    pub fn numbered_real_const(&self, i: u32) -> Ast {
+    pub fn numbered_real_const(&self, i: u32) -> Ast<'_> {
         Ast::new_const(&self.int_sym(i), &self.real_sort())
     }
 
@@ -100,11 +324,11 @@ impl Context {
         Ast::fresh_const(self, prefix, &self.real_sort())
     }
 
-    pub fn named_bitvector_const(&self, s: &str, sz: u32) -> Ast {
+    pub fn named_bitvector_const(&self, s: &str, sz: u32) -> Ast<'_> {
         Ast::new_const(&self.str_sym(s), &self.bitvector_sort(sz))
     }
 
-    pub fn numbered_bitvector_const(&self, i: u32, sz: u32) -> Ast {
+    pub fn numbered_bitvector_const(&self, i: u32, sz: u32) -> Ast<'_> {
         Ast::new_const(&self.int_sym(i), &self.bitvector_sort(sz))
     }
 
@@ -112,25 +336,145 @@ impl Context {
         Ast::fresh_const(self, prefix, &self.bitvector_sort(sz))
     }
 
-    pub fn from_bool(&self, b: bool) -> Ast {
+    pub fn from_bool(&self, b: bool) -> Ast<'_> {
         Ast::from_bool(self, b)
     }
 
-    pub fn from_u64(&self, u: u64) -> Ast {
+    pub fn from_u64(&self, u: u64) -> Ast<'_> {
         Ast::from_u64(self, u)
     }
 
-    pub fn from_i64(&self, i: i64) -> Ast {
+    pub fn from_i64(&self, i: i64) -> Ast<'_> {
         Ast::from_i64(self, i)
     }
 
-    pub fn from_real(&self, num: i32, den: i32) -> Ast {
+    /// Parse `src` as an SMT-LIB2 benchmark and return the assertions it
+    /// contains.
+    ///
+    /// The sorts and function declarations in `sorts` and `decls` can be
+    /// referred to by name in `src`, in addition to the ones it declares
+    /// itself. Returns an error whose message includes line and column
+    /// information if `src` is malformed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_int_const("x");
+    /// let f = ctx.func_decl(&ctx.str_sym("f"), &[&ctx.int_sort()], &ctx.int_sort());
+    ///
+    /// let asts = ctx
+    ///     .parse_smtlib2("(declare-const y Int) (assert (> (f x) y))", &[], &[&f, &x.decl()])
+    ///     .unwrap();
+    /// assert_eq!(asts.len(), 1);
+    ///
+    /// let err = ctx.parse_smtlib2("(assert (> z 0))", &[], &[]).err().unwrap();
+    /// assert_eq!(err.code, ErrorCode::ParserError);
+    /// assert!(err.message.contains("line 1"));
+    /// ```
+    pub fn parse_smtlib2<'ctx>(
+        &'ctx self,
+        src: &str,
+        sorts: &[&Sort<'ctx>],
+        decls: &[&FuncDecl<'ctx>],
+    ) -> Result<Vec<Ast<'ctx>>, Z3Error> {
+        let src = CString::new(src).unwrap();
+        self.parse_smtlib2_with(sorts, decls, |c, ns, sn, ss, nd, dn, ds| unsafe {
+            Z3_parse_smtlib2_string(c, src.as_ptr(), ns, sn, ss, nd, dn, ds)
+        })
+    }
+
+    /// Like [`Context::parse_smtlib2()`](#method.parse_smtlib2), but read
+    /// the benchmark from the file at `path`.
+    ///
+    /// Returns an error if the file cannot be read or is malformed.
+    pub fn parse_smtlib2_file<'ctx, P: AsRef<Path>>(
+        &'ctx self,
+        path: P,
+        sorts: &[&Sort<'ctx>],
+        decls: &[&FuncDecl<'ctx>],
+    ) -> Result<Vec<Ast<'ctx>>, Z3Error> {
+        let name = path_to_cstring(path.as_ref())?;
+        self.parse_smtlib2_with(sorts, decls, |c, ns, sn, ss, nd, dn, ds| unsafe {
+            Z3_parse_smtlib2_file(c, name.as_ptr(), ns, sn, ss, nd, dn, ds)
+        })
+    }
+
+    /// Run one of the SMT-LIB2 parsing functions with the given sorts and
+    /// declarations in scope, reporting the errors it raises.
+    fn parse_smtlib2_with<'ctx, F>(
+        &'ctx self,
+        sorts: &[&Sort<'ctx>],
+        decls: &[&FuncDecl<'ctx>],
+        parse: F,
+    ) -> Result<Vec<Ast<'ctx>>, Z3Error>
+    where
+        F: FnOnce(
+            Z3_context,
+            u32,
+            *const Z3_symbol,
+            *const Z3_sort,
+            u32,
+            *const Z3_symbol,
+            *const Z3_func_decl,
+        ) -> Z3_ast_vector,
+    {
+        let z3_sorts: Vec<Z3_sort> = sorts.iter().map(|s| s.z3_sort).collect();
+        let z3_decls: Vec<Z3_func_decl> = decls.iter().map(|d| d.z3_func_decl).collect();
+        let v = unsafe {
+            let c = self.z3_ctx;
+            let sort_names: Vec<Z3_symbol> =
+                z3_sorts.iter().map(|&s| Z3_get_sort_name(c, s)).collect();
+            let decl_names: Vec<Z3_symbol> =
+                z3_decls.iter().map(|&d| Z3_get_decl_name(c, d)).collect();
+            self.try_z3(|| {
+                parse(
+                    c,
+                    z3_sorts.len() as u32,
+                    sort_names.as_ptr(),
+                    z3_sorts.as_ptr(),
+                    z3_decls.len() as u32,
+                    decl_names.as_ptr(),
+                    z3_decls.as_ptr(),
+                )
+            })
+            .map(|v| AstVector::from_raw(self, v))
+        };
+        v.map(|v| v.to_vec())
+    }
+
+    pub fn from_real(&self, num: i32, den: i32) -> Ast<'_> {
         Ast::from_real(self, num, den)
     }
 }
 
+impl<'ctx> ContextHandle<'ctx> {
+    /// Interrupt the context, like
+    /// [`Context::interrupt()`](struct.Context.html#method.interrupt).
+    pub fn interrupt(&self) {
+        unsafe { Z3_interrupt(self.z3_ctx) };
+    }
+}
+
+// Z3_interrupt may be called from any thread, and the lifetime keeps the
+// context alive; the handle gives access to nothing else.
+unsafe impl<'ctx> Send for ContextHandle<'ctx> {}
+unsafe impl<'ctx> Sync for ContextHandle<'ctx> {}
+
 impl Drop for Context {
     fn drop(&mut self) {
+        DATATYPES.lock().unwrap().retain(|&(ctx, _), &mut s| {
+            if ctx != self.z3_ctx as usize {
+                return true;
+            }
+            let s = s as Z3_sort;
+            unsafe { Z3_dec_ref(self.z3_ctx, Z3_sort_to_ast(self.z3_ctx, s)) };
+            false
+        });
         unsafe { Z3_del_context(self.z3_ctx) };
+        memory::object_deleted();
     }
 }
@@ -0,0 +1,253 @@
+use std::ffi::CString;
+use std::ptr;
+use z3_sys::*;
+use Context;
+use DatatypeAccessor;
+use DatatypeBuilder;
+use DatatypeSort;
+use DatatypeVariant;
+use FuncDecl;
+use Sort;
+
+impl<'ctx> DatatypeBuilder<'ctx> {
+    /// Start declaring a datatype named `name`.
+    pub fn new(ctx: &'ctx Context, name: &str) -> DatatypeBuilder<'ctx> {
+        DatatypeBuilder {
+            ctx,
+            name: name.to_owned(),
+            constructors: Vec::new(),
+        }
+    }
+
+    /// Add a constructor named `name` with the given fields. Its
+    /// recognizer is named `is-<name>`.
+    ///
+    /// An enumeration is a datatype all of whose constructors have no
+    /// fields, and a record is a datatype with a single constructor.
+    pub fn variant(
+        mut self,
+        name: &str,
+        fields: Vec<(&str, DatatypeAccessor<'ctx>)>,
+    ) -> DatatypeBuilder<'ctx> {
+        let fields = fields.into_iter().map(|(n, a)| (n.to_owned(), a)).collect();
+        self.constructors.push((name.to_owned(), fields));
+        self
+    }
+
+    /// Declare the datatype.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    ///
+    /// // (declare-datatype List ((nil) (cons (head Int) (tail List))))
+    /// let list = DatatypeBuilder::new(&ctx, "List")
+    ///     .variant("nil", vec![])
+    ///     .variant(
+    ///         "cons",
+    ///         vec![
+    ///             ("head", DatatypeAccessor::Sort(ctx.int_sort())),
+    ///             ("tail", DatatypeAccessor::Datatype("List".to_owned())),
+    ///         ],
+    ///     )
+    ///     .finish();
+    ///
+    /// let nil = list.variants[0].constructor.apply(&[]);
+    /// let cons = &list.variants[1];
+    /// let l = cons.constructor.apply(&[&ctx.from_i64(1), &nil]);
+    ///
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&cons.tester.apply(&[&l]));
+    /// solver.assert(&cons.accessors[0].apply(&[&l])._eq(&ctx.from_i64(1)));
+    /// solver.assert(&cons.accessors[1].apply(&[&l])._eq(&nil));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// - if the datatype has no variants
+    /// - if a `DatatypeAccessor::Datatype` names another datatype
+    pub fn finish(self) -> DatatypeSort<'ctx> {
+        DatatypeBuilder::create_datatypes(vec![self]).remove(0)
+    }
+
+    /// Declare several, possibly mutually recursive, datatypes at once.
+    /// A [`DatatypeAccessor::Datatype`] field may refer to any of the
+    /// datatypes in `builders` by name.
+    ///
+    /// The result has one `DatatypeSort` per builder, in the same order.
+    ///
+    /// # Panics
+    ///
+    /// - if `builders` is empty or the builders belong to different
+    ///   contexts
+    /// - if a builder has no variants
+    /// - if a `DatatypeAccessor::Datatype` names none of the builders
+    ///
+    /// [`DatatypeAccessor::Datatype`]: enum.DatatypeAccessor.html#variant.Datatype
+    pub fn create_datatypes(builders: Vec<DatatypeBuilder<'ctx>>) -> Vec<DatatypeSort<'ctx>> {
+        assert!(!builders.is_empty());
+        let ctx = builders[0].ctx;
+        assert!(builders.iter().all(|b| ptr::eq(b.ctx, ctx)));
+
+        // Check everything before calling into Z3, so that a panic leaves
+        // nothing half-declared behind.
+        let index_of = |name: &str| builders.iter().position(|b| b.name == name);
+        for b in &builders {
+            assert!(
+                !b.constructors.is_empty(),
+                "datatype `{}` has no variants",
+                b.name
+            );
+            for (_, accessor) in b.constructors.iter().flat_map(|(_, fields)| fields) {
+                if let DatatypeAccessor::Datatype(n) = accessor {
+                    assert!(
+                        index_of(n).is_some(),
+                        "unknown datatype `{}` in field sort",
+                        n
+                    );
+                }
+            }
+        }
+
+        let mk_sym = |s: &str| unsafe {
+            let cs = CString::new(s).unwrap();
+            Z3_mk_string_symbol(ctx.z3_ctx, cs.as_ptr())
+        };
+
+        let sort_names: Vec<Z3_symbol> = builders.iter().map(|b| mk_sym(&b.name)).collect();
+        let mut sorts: Vec<Z3_sort> = vec![ptr::null_mut(); builders.len()];
+
+        let mut ctors: Vec<Vec<(Z3_constructor, usize)>> = Vec::new();
+        let mut ctor_lists: Vec<Z3_constructor_list> = Vec::new();
+        for b in &builders {
+            let mut cs = Vec::new();
+            for (name, fields) in &b.constructors {
+                let field_names: Vec<Z3_symbol> = fields.iter().map(|(n, _)| mk_sym(n)).collect();
+                let mut field_sorts = Vec::new();
+                let mut sort_refs = Vec::new();
+                for (_, accessor) in fields {
+                    match accessor {
+                        DatatypeAccessor::Sort(s) => {
+                            field_sorts.push(s.z3_sort);
+                            sort_refs.push(0);
+                        }
+                        DatatypeAccessor::Datatype(n) => {
+                            field_sorts.push(ptr::null_mut());
+                            sort_refs.push(index_of(n).unwrap() as u32);
+                        }
+                    }
+                }
+                let c = unsafe {
+                    Z3_mk_constructor(
+                        ctx.z3_ctx,
+                        mk_sym(name),
+                        mk_sym(&format!("is-{}", name)),
+                        fields.len() as u32,
+                        field_names.as_ptr(),
+                        field_sorts.as_ptr(),
+                        sort_refs.as_mut_ptr(),
+                    )
+                };
+                cs.push((c, fields.len()));
+            }
+            let raw: Vec<Z3_constructor> = cs.iter().map(|&(c, _)| c).collect();
+            ctor_lists.push(unsafe {
+                Z3_mk_constructor_list(ctx.z3_ctx, raw.len() as u32, raw.as_ptr())
+            });
+            ctors.push(cs);
+        }
+
+        unsafe {
+            Z3_mk_datatypes(
+                ctx.z3_ctx,
+                builders.len() as u32,
+                sort_names.as_ptr(),
+                sorts.as_mut_ptr(),
+                ctor_lists.as_mut_ptr(),
+            );
+        }
+
+        let mut result = Vec::new();
+        for (s, cs) in sorts.into_iter().zip(ctors) {
+            let mut variants = Vec::new();
+            for (c, num_fields) in cs {
+                let mut constructor = ptr::null_mut();
+                let mut tester = ptr::null_mut();
+                let mut accessors = vec![ptr::null_mut(); num_fields];
+                unsafe {
+                    Z3_query_constructor(
+                        ctx.z3_ctx,
+                        c,
+                        num_fields as u32,
+                        &mut constructor,
+                        &mut tester,
+                        accessors.as_mut_ptr(),
+                    );
+                    variants.push(DatatypeVariant {
+                        constructor: FuncDecl::from_raw(ctx, constructor),
+                        tester: FuncDecl::from_raw(ctx, tester),
+                        accessors: accessors
+                            .into_iter()
+                            .map(|a| FuncDecl::from_raw(ctx, a))
+                            .collect(),
+                    });
+                    Z3_del_constructor(ctx.z3_ctx, c);
+                }
+            }
+            unsafe { Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s)) };
+            result.push(DatatypeSort {
+                sort: Sort { ctx, z3_sort: s },
+                variants,
+            });
+        }
+        for l in ctor_lists {
+            unsafe { Z3_del_constructor_list(ctx.z3_ctx, l) };
+        }
+        result
+    }
+}
+
+impl<'ctx> DatatypeSort<'ctx> {
+    /// Look up the constructors of the datatype `sort`.
+    pub(crate) fn from_sort(sort: Sort<'ctx>) -> DatatypeSort<'ctx> {
+        let ctx = sort.ctx;
+        let num_variants =
+            unsafe { Z3_get_datatype_sort_num_constructors(ctx.z3_ctx, sort.z3_sort) };
+        let variants = (0..num_variants)
+            .map(|i| unsafe {
+                let constructor = FuncDecl::from_raw(
+                    ctx,
+                    Z3_get_datatype_sort_constructor(ctx.z3_ctx, sort.z3_sort, i),
+                );
+                let tester = FuncDecl::from_raw(
+                    ctx,
+                    Z3_get_datatype_sort_recognizer(ctx.z3_ctx, sort.z3_sort, i),
+                );
+                let accessors = (0..constructor.arity())
+                    .map(|j| {
+                        FuncDecl::from_raw(
+                            ctx,
+                            Z3_get_datatype_sort_constructor_accessor(
+                                ctx.z3_ctx,
+                                sort.z3_sort,
+                                i,
+                                j,
+                            ),
+                        )
+                    })
+                    .collect();
+                DatatypeVariant {
+                    constructor,
+                    tester,
+                    accessors,
+                }
+            })
+            .collect();
+        DatatypeSort { sort, variants }
+    }
+}
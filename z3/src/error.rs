@@ -0,0 +1,11 @@
+use std::error::Error;
+use std::fmt;
+use Z3Error;
+
+impl fmt::Display for Z3Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for Z3Error {}
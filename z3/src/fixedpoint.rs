@@ -0,0 +1,259 @@
+use std::ffi::{CStr, CString};
+use std::fmt;
+use z3_sys::*;
+use Ast;
+use Context;
+use Fixedpoint;
+use FuncDecl;
+use ParamDescrs;
+use Params;
+use SatResult;
+use Statistics;
+use Z3Error;
+
+impl<'ctx> Fixedpoint<'ctx> {
+    /// Create a new fixedpoint context.
+    pub fn new(ctx: &'ctx Context) -> Fixedpoint<'ctx> {
+        Fixedpoint {
+            ctx,
+            z3_fixedpoint: unsafe {
+                let f = Z3_mk_fixedpoint(ctx.z3_ctx);
+                Z3_fixedpoint_inc_ref(ctx.z3_ctx, f);
+                f
+            },
+        }
+    }
+
+    /// Register `f` as a relation whose interpretation is computed by the
+    /// fixedpoint engine, rather than an uninterpreted function.
+    pub fn register_relation(&self, f: &FuncDecl<'ctx>) {
+        unsafe {
+            Z3_fixedpoint_register_relation(self.ctx.z3_ctx, self.z3_fixedpoint, f.z3_func_decl)
+        };
+    }
+
+    /// Add a rule, i.e. a Horn clause such as `forall x. body(x) => head(x)`
+    /// or a fact `head(c)`, whose head is a registered relation.
+    ///
+    /// # See also:
+    ///
+    /// - [`Fixedpoint::add_named_rule()`](#method.add_named_rule)
+    /// - [`Fixedpoint::add_fact()`](#method.add_fact)
+    pub fn add_rule(&self, rule: &Ast<'ctx>) {
+        self.add_named_rule(rule, "");
+    }
+
+    /// Add a rule like [`Fixedpoint::add_rule()`](#method.add_rule),
+    /// giving it a name that identifies it when the context is printed.
+    pub fn add_named_rule(&self, rule: &Ast<'ctx>, name: &str) {
+        let name = CString::new(name).unwrap();
+        unsafe {
+            let name = Z3_mk_string_symbol(self.ctx.z3_ctx, name.as_ptr());
+            self.ctx.check_z3(|| {
+                Z3_fixedpoint_add_rule(self.ctx.z3_ctx, self.z3_fixedpoint, rule.z3_ast, name)
+            })
+        };
+    }
+
+    /// Add the fact `rel(args...)`, where each argument is a numeral of
+    /// the corresponding domain sort of `rel`, e.g. a bitvector or
+    /// finite domain element.
+    ///
+    /// # Panics
+    ///
+    /// If the number of arguments does not match the arity of `rel`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let node = ctx.bitvector_sort(4);
+    /// let bool_sort = ctx.bool_sort();
+    /// let edge = FuncDecl::new(&ctx, &ctx.str_sym("edge"), &[&node, &node], &bool_sort);
+    /// let path = FuncDecl::new(&ctx, &ctx.str_sym("path"), &[&node, &node], &bool_sort);
+    /// let (a, b, c) = (
+    ///     ctx.named_bitvector_const("a", 4),
+    ///     ctx.named_bitvector_const("b", 4),
+    ///     ctx.named_bitvector_const("c", 4),
+    /// );
+    ///
+    /// let fp = Fixedpoint::new(&ctx);
+    /// let mut params = Params::new(&ctx);
+    /// params.set_symbol("engine", "datalog");
+    /// fp.set_params(&params);
+    /// fp.register_relation(&edge);
+    /// fp.register_relation(&path);
+    /// fp.add_fact(&edge, &[1, 2]);
+    /// fp.add_fact(&edge, &[2, 3]);
+    /// let base = edge.apply(&[&a, &b]).implies(&path.apply(&[&a, &b]));
+    /// fp.add_named_rule(&Ast::forall_const(&ctx, &[&a, &b], &[], &base), "base");
+    /// let step = path.apply(&[&a, &b])
+    ///     .and(&[&path.apply(&[&b, &c])])
+    ///     .implies(&path.apply(&[&a, &c]));
+    /// fp.add_named_rule(&Ast::forall_const(&ctx, &[&a, &b, &c], &[], &step), "step");
+    ///
+    /// let reach = |from, to| {
+    ///     let q = path.apply(&[&node.from_u64(from), &node.from_u64(to)]);
+    ///     fp.query(&q).unwrap()
+    /// };
+    /// assert_eq!(reach(1, 3), SatResult::Sat);
+    /// assert_eq!(reach(3, 1), SatResult::Unsat);
+    /// ```
+    pub fn add_fact(&self, rel: &FuncDecl<'ctx>, args: &[u64]) {
+        assert_eq!(rel.arity() as usize, args.len());
+        let args: Vec<Ast<'ctx>> = args
+            .iter()
+            .enumerate()
+            .map(|(i, &a)| rel.domain(i as u32).from_u64(a))
+            .collect();
+        let args: Vec<&Ast<'ctx>> = args.iter().collect();
+        self.add_rule(&rel.apply(&args));
+    }
+
+    /// Assert a constraint that does not mention any registered relation.
+    pub fn assert(&self, ast: &Ast<'ctx>) {
+        unsafe {
+            self.ctx
+                .check_z3(|| Z3_fixedpoint_assert(self.ctx.z3_ctx, self.z3_fixedpoint, ast.z3_ast))
+        };
+    }
+
+    /// Check whether `query` is derivable from the rules.
+    ///
+    /// Returns [`SatResult::Sat`](enum.SatResult.html#variant.Sat) if it
+    /// is, [`SatResult::Unsat`](enum.SatResult.html#variant.Unsat) if it
+    /// is not, and
+    /// [`SatResult::Unknown`](enum.SatResult.html#variant.Unknown) if the
+    /// engine could not decide; the reason is then available from
+    /// [`Fixedpoint::get_reason_unknown()`](#method.get_reason_unknown).
+    ///
+    /// Returns an error if the engine does not support the rules or the
+    /// query, e.g. the `datalog` engine given relations over integers.
+    ///
+    /// # See also:
+    ///
+    /// - [`Fixedpoint::get_answer()`](#method.get_answer)
+    pub fn query(&self, query: &Ast<'ctx>) -> Result<SatResult, Z3Error> {
+        let r = unsafe {
+            self.ctx
+                .try_z3(|| Z3_fixedpoint_query(self.ctx.z3_ctx, self.z3_fixedpoint, query.z3_ast))
+        };
+        r.map(SatResult::from_lbool)
+    }
+
+    /// Retrieve the answer to the last [`Fixedpoint::query()`](#method.query).
+    ///
+    /// If the query was derivable, this is a derivation of it (depending on
+    /// the engine, a set of instances or a proof). Otherwise it is an
+    /// interpretation of the relations that excludes the query.
+    pub fn get_answer(&self) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_fixedpoint_get_answer(self.ctx.z3_ctx, self.z3_fixedpoint)
+        })
+    }
+
+    /// Return a brief justification for an unknown result of
+    /// [`Fixedpoint::query()`](#method.query).
+    pub fn get_reason_unknown(&self) -> String {
+        unsafe {
+            let p = Z3_fixedpoint_get_reason_unknown(self.ctx.z3_ctx, self.z3_fixedpoint);
+            CStr::from_ptr(p).to_string_lossy().into_owned()
+        }
+    }
+
+    /// Return the rules added so far.
+    pub fn get_rules(&self) -> Vec<Ast<'ctx>> {
+        let v = { unsafe { Z3_fixedpoint_get_rules(self.ctx.z3_ctx, self.z3_fixedpoint) } };
+        Ast::vec_from_ast_vector(self.ctx, v)
+    }
+
+    /// Return the constraints added with
+    /// [`Fixedpoint::assert()`](#method.assert).
+    pub fn get_assertions(&self) -> Vec<Ast<'ctx>> {
+        let v = { unsafe { Z3_fixedpoint_get_assertions(self.ctx.z3_ctx, self.z3_fixedpoint) } };
+        Ast::vec_from_ast_vector(self.ctx, v)
+    }
+
+    /// Apply the parameters in `params`, for instance `engine` (one of
+    /// `datalog`, `spacer`, ...).
+    ///
+    /// # Panics
+    ///
+    /// If the fixedpoint context does not accept one of the parameters,
+    /// or its value has the wrong kind.
+    pub fn set_params(&self, params: &Params<'ctx>) {
+        unsafe {
+            self.ctx.check_z3(|| {
+                Z3_fixedpoint_set_params(self.ctx.z3_ctx, self.z3_fixedpoint, params.z3_params)
+            })
+        };
+    }
+
+    /// Return descriptions of the parameters accepted by the fixedpoint
+    /// context.
+    pub fn get_param_descrs(&self) -> ParamDescrs<'ctx> {
+        unsafe {
+            let d = Z3_fixedpoint_get_param_descrs(self.ctx.z3_ctx, self.z3_fixedpoint);
+            ParamDescrs::from_raw(self.ctx, d)
+        }
+    }
+
+    /// Return a description of the parameters accepted by the fixedpoint
+    /// context.
+    pub fn get_help(&self) -> String {
+        unsafe {
+            let p = Z3_fixedpoint_get_help(self.ctx.z3_ctx, self.z3_fixedpoint);
+            CStr::from_ptr(p).to_string_lossy().into_owned()
+        }
+    }
+
+    /// Retrieve statistics for the last [`Fixedpoint::query()`](#method.query).
+    pub fn get_statistics(&self) -> Statistics<'ctx> {
+        unsafe {
+            let s = Z3_fixedpoint_get_statistics(self.ctx.z3_ctx, self.z3_fixedpoint);
+            Statistics::from_raw(self.ctx, s)
+        }
+    }
+
+    /// Parse rules, facts and queries in SMT-LIB2 format from `s` and add
+    /// the rules and facts to the context. Return the queries, or an error
+    /// if `s` is not valid SMT-LIB2.
+    pub fn from_string(&self, s: &str) -> Result<Vec<Ast<'ctx>>, Z3Error> {
+        let s = CString::new(s).unwrap();
+        let v = unsafe {
+            self.ctx.try_z3(|| {
+                Z3_fixedpoint_from_string(self.ctx.z3_ctx, self.z3_fixedpoint, s.as_ptr())
+            })?
+        };
+        Ok(Ast::vec_from_ast_vector(self.ctx, v))
+    }
+}
+
+impl<'ctx> fmt::Display for Fixedpoint<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let p = unsafe {
+            Z3_fixedpoint_to_string(
+                self.ctx.z3_ctx,
+                self.z3_fixedpoint,
+                0,
+                ::std::ptr::null_mut(),
+            )
+        };
+        if p.is_null() {
+            return Result::Err(fmt::Error);
+        }
+        match unsafe { CStr::from_ptr(p) }.to_str() {
+            Ok(s) => write!(f, "{}", s),
+            Err(_) => Result::Err(fmt::Error),
+        }
+    }
+}
+
+impl<'ctx> Drop for Fixedpoint<'ctx> {
+    fn drop(&mut self) {
+        unsafe { Z3_fixedpoint_dec_ref(self.ctx.z3_ctx, self.z3_fixedpoint) };
+    }
+}
@@ -0,0 +1,314 @@
+use std::fmt;
+use std::ops::Deref;
+use z3_sys::*;
+use Ast;
+use Context;
+use Float;
+use RoundingMode;
+use Sort;
+use BV;
+
+macro_rules! unop {
+    ( $f:ident, $z3fn:ident ) => {
+        pub fn $f(&self) -> Float<'ctx> {
+            Float::wrap(self.ctx, unsafe { $z3fn(self.ctx.z3_ctx, self.z3_ast) })
+        }
+    };
+}
+
+macro_rules! binop {
+    ( $f:ident, $z3fn:ident ) => {
+        pub fn $f(&self, other: &Float<'ctx>) -> Float<'ctx> {
+            Float::wrap(self.ctx, unsafe {
+                $z3fn(self.ctx.z3_ctx, self.z3_ast, other.z3_ast)
+            })
+        }
+    };
+}
+
+macro_rules! rm_unop {
+    ( $f:ident, $z3fn:ident ) => {
+        pub fn $f(&self, rm: &Ast<'ctx>) -> Float<'ctx> {
+            Float::wrap(self.ctx, unsafe {
+                $z3fn(self.ctx.z3_ctx, rm.z3_ast, self.z3_ast)
+            })
+        }
+    };
+}
+
+macro_rules! rm_binop {
+    ( $f:ident, $z3fn:ident ) => {
+        pub fn $f(&self, rm: &Ast<'ctx>, other: &Float<'ctx>) -> Float<'ctx> {
+            Float::wrap(self.ctx, unsafe {
+                $z3fn(self.ctx.z3_ctx, rm.z3_ast, self.z3_ast, other.z3_ast)
+            })
+        }
+    };
+}
+
+macro_rules! pred {
+    ( $f:ident, $z3fn:ident ) => {
+        pub fn $f(&self) -> Ast<'ctx> {
+            Ast::new(self.ctx, unsafe { $z3fn(self.ctx.z3_ctx, self.z3_ast) })
+        }
+    };
+}
+
+macro_rules! cmp {
+    ( $f:ident, $z3fn:ident ) => {
+        pub fn $f(&self, other: &Float<'ctx>) -> Ast<'ctx> {
+            Ast::new(self.ctx, unsafe {
+                $z3fn(self.ctx.z3_ctx, self.z3_ast, other.z3_ast)
+            })
+        }
+    };
+}
+
+impl RoundingMode {
+    /// Create the rounding-mode term corresponding to `self`.
+    pub fn to_ast(self, ctx: &Context) -> Ast<'_> {
+        Ast::new(ctx, unsafe {
+            match self {
+                RoundingMode::NearestTiesToEven => Z3_mk_fpa_rne(ctx.z3_ctx),
+                RoundingMode::NearestTiesToAway => Z3_mk_fpa_rna(ctx.z3_ctx),
+                RoundingMode::TowardPositive => Z3_mk_fpa_rtp(ctx.z3_ctx),
+                RoundingMode::TowardNegative => Z3_mk_fpa_rtn(ctx.z3_ctx),
+                RoundingMode::TowardZero => Z3_mk_fpa_rtz(ctx.z3_ctx),
+            }
+        })
+    }
+}
+
+impl<'ctx> Float<'ctx> {
+    fn wrap(ctx: &'ctx Context, ast: Z3_ast) -> Float<'ctx> {
+        Float {
+            ast: Ast::new(ctx, ast),
+        }
+    }
+
+    /// Wrap an `Ast` as a `Float`, returning `None` if it does not have
+    /// floating-point sort.
+    pub fn from_ast(ast: Ast<'ctx>) -> Option<Float<'ctx>> {
+        if ast.sort_kind() == SortKind::FloatingPoint {
+            Some(Float { ast })
+        } else {
+            None
+        }
+    }
+
+    /// Create a floating-point constant named `name` of sort `sort`.
+    ///
+    /// # See also:
+    ///
+    /// - [`Sort::float()`](struct.Sort.html#method.float)
+    pub fn new_const(ctx: &'ctx Context, name: &str, sort: &Sort<'ctx>) -> Float<'ctx> {
+        Float {
+            ast: Ast::new_const(&ctx.str_sym(name), sort),
+        }
+    }
+
+    /// Create a single precision floating-point numeral.
+    pub fn from_f32(ctx: &'ctx Context, v: f32) -> Float<'ctx> {
+        let sort = Sort::float32(ctx);
+        Float::wrap(ctx, unsafe {
+            Z3_mk_fpa_numeral_float(ctx.z3_ctx, v, sort.z3_sort)
+        })
+    }
+
+    /// Create a double precision floating-point numeral.
+    ///
+    /// # Example
+    ///
+    /// Floating-point addition is not associative:
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let rne = RoundingMode::NearestTiesToEven.to_ast(&ctx);
+    ///
+    /// let a = Float::from_f64(&ctx, 1e16);
+    /// let b = Float::from_f64(&ctx, -1e16);
+    /// let c = Float::from_f64(&ctx, 1.0);
+    ///
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&a.add(&rne, &b).add(&rne, &c)
+    ///     .fp_eq(&a.add(&rne, &b.add(&rne, &c))).not());
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    /// ```
+    pub fn from_f64(ctx: &'ctx Context, v: f64) -> Float<'ctx> {
+        let sort = Sort::float64(ctx);
+        Float::wrap(ctx, unsafe {
+            Z3_mk_fpa_numeral_double(ctx.z3_ctx, v, sort.z3_sort)
+        })
+    }
+
+    /// Create a NaN of sort `sort`.
+    pub fn nan(ctx: &'ctx Context, sort: &Sort<'ctx>) -> Float<'ctx> {
+        Float::wrap(ctx, unsafe { Z3_mk_fpa_nan(ctx.z3_ctx, sort.z3_sort) })
+    }
+
+    /// Create positive or negative infinity of sort `sort`.
+    pub fn infinity(ctx: &'ctx Context, sort: &Sort<'ctx>, negative: bool) -> Float<'ctx> {
+        Float::wrap(ctx, unsafe {
+            Z3_mk_fpa_inf(ctx.z3_ctx, sort.z3_sort, negative)
+        })
+    }
+
+    /// Create positive or negative zero of sort `sort`.
+    pub fn zero(ctx: &'ctx Context, sort: &Sort<'ctx>, negative: bool) -> Float<'ctx> {
+        Float::wrap(ctx, unsafe {
+            Z3_mk_fpa_zero(ctx.z3_ctx, sort.z3_sort, negative)
+        })
+    }
+
+    /// Return the number of exponent bits of this value's sort.
+    pub fn get_ebits(&self) -> u32 {
+        unsafe {
+            let s = Z3_get_sort(self.ctx.z3_ctx, self.z3_ast);
+            Z3_fpa_get_ebits(self.ctx.z3_ctx, s)
+        }
+    }
+
+    /// Return the number of significand bits (including the hidden bit)
+    /// of this value's sort.
+    pub fn get_sbits(&self) -> u32 {
+        unsafe {
+            let s = Z3_get_sort(self.ctx.z3_ctx, self.z3_ast);
+            Z3_fpa_get_sbits(self.ctx.z3_ctx, s)
+        }
+    }
+
+    // Arithmetic ops
+    unop!(abs, Z3_mk_fpa_abs);
+    unop!(neg, Z3_mk_fpa_neg);
+    rm_binop!(add, Z3_mk_fpa_add);
+    rm_binop!(sub, Z3_mk_fpa_sub);
+    rm_binop!(mul, Z3_mk_fpa_mul);
+    rm_binop!(div, Z3_mk_fpa_div);
+    rm_unop!(sqrt, Z3_mk_fpa_sqrt);
+    rm_unop!(round_to_integral, Z3_mk_fpa_round_to_integral);
+    binop!(rem, Z3_mk_fpa_rem);
+    binop!(min, Z3_mk_fpa_min);
+    binop!(max, Z3_mk_fpa_max);
+
+    /// Fused multiply-add, computing `self * b + c` with a single
+    /// rounding.
+    pub fn fma(&self, rm: &Ast<'ctx>, b: &Float<'ctx>, c: &Float<'ctx>) -> Float<'ctx> {
+        Float::wrap(self.ctx, unsafe {
+            Z3_mk_fpa_fma(self.ctx.z3_ctx, rm.z3_ast, self.z3_ast, b.z3_ast, c.z3_ast)
+        })
+    }
+
+    // Comparisons. Unlike `Ast::_eq()`, `fp_eq()` follows IEEE 754, so
+    // that NaN is not equal to itself and the two zeroes are equal.
+    cmp!(le, Z3_mk_fpa_leq);
+    cmp!(lt, Z3_mk_fpa_lt);
+    cmp!(ge, Z3_mk_fpa_geq);
+    cmp!(gt, Z3_mk_fpa_gt);
+    cmp!(fp_eq, Z3_mk_fpa_eq);
+
+    // Classification
+    pred!(is_normal, Z3_mk_fpa_is_normal);
+    pred!(is_subnormal, Z3_mk_fpa_is_subnormal);
+    pred!(is_zero, Z3_mk_fpa_is_zero);
+    pred!(is_infinite, Z3_mk_fpa_is_infinite);
+    pred!(is_nan, Z3_mk_fpa_is_nan);
+    pred!(is_negative, Z3_mk_fpa_is_negative);
+    pred!(is_positive, Z3_mk_fpa_is_positive);
+
+    // Conversions
+
+    /// Convert to the floating-point sort `sort`, rounding if necessary.
+    pub fn to_float(&self, rm: &Ast<'ctx>, sort: &Sort<'ctx>) -> Float<'ctx> {
+        Float::wrap(self.ctx, unsafe {
+            Z3_mk_fpa_to_fp_float(self.ctx.z3_ctx, rm.z3_ast, self.z3_ast, sort.z3_sort)
+        })
+    }
+
+    /// Convert a real-sorted term to the floating-point sort `sort`.
+    pub fn from_real(rm: &Ast<'ctx>, real: &Ast<'ctx>, sort: &Sort<'ctx>) -> Float<'ctx> {
+        Float::wrap(real.ctx, unsafe {
+            Z3_mk_fpa_to_fp_real(real.ctx.z3_ctx, rm.z3_ast, real.z3_ast, sort.z3_sort)
+        })
+    }
+
+    /// Convert a bitvector, interpreted as a signed integer, to the
+    /// floating-point sort `sort`.
+    pub fn from_sbv(rm: &Ast<'ctx>, bv: &BV<'ctx>, sort: &Sort<'ctx>) -> Float<'ctx> {
+        Float::wrap(bv.ctx, unsafe {
+            Z3_mk_fpa_to_fp_signed(bv.ctx.z3_ctx, rm.z3_ast, bv.z3_ast, sort.z3_sort)
+        })
+    }
+
+    /// Convert a bitvector, interpreted as an unsigned integer, to the
+    /// floating-point sort `sort`.
+    pub fn from_ubv(rm: &Ast<'ctx>, bv: &BV<'ctx>, sort: &Sort<'ctx>) -> Float<'ctx> {
+        Float::wrap(bv.ctx, unsafe {
+            Z3_mk_fpa_to_fp_unsigned(bv.ctx.z3_ctx, rm.z3_ast, bv.z3_ast, sort.z3_sort)
+        })
+    }
+
+    /// Reinterpret a bitvector in IEEE 754 interchange format as a value
+    /// of the floating-point sort `sort`. The size of `bv` must equal
+    /// `ebits + sbits` of `sort`.
+    pub fn from_ieee_bv(bv: &BV<'ctx>, sort: &Sort<'ctx>) -> Float<'ctx> {
+        Float::wrap(bv.ctx, unsafe {
+            Z3_mk_fpa_to_fp_bv(bv.ctx.z3_ctx, bv.z3_ast, sort.z3_sort)
+        })
+    }
+
+    /// Convert to an unsigned bitvector of `sz` bits, rounding if
+    /// necessary.
+    pub fn to_ubv(&self, rm: &Ast<'ctx>, sz: u32) -> BV<'ctx> {
+        let ast = Ast::new(self.ctx, unsafe {
+            Z3_mk_fpa_to_ubv(self.ctx.z3_ctx, rm.z3_ast, self.z3_ast, sz)
+        });
+        BV::from_ast(ast).unwrap()
+    }
+
+    /// Convert to a signed bitvector of `sz` bits, rounding if necessary.
+    pub fn to_sbv(&self, rm: &Ast<'ctx>, sz: u32) -> BV<'ctx> {
+        let ast = Ast::new(self.ctx, unsafe {
+            Z3_mk_fpa_to_sbv(self.ctx.z3_ctx, rm.z3_ast, self.z3_ast, sz)
+        });
+        BV::from_ast(ast).unwrap()
+    }
+
+    /// Reinterpret as a bitvector in IEEE 754 interchange format.
+    pub fn to_ieee_bv(&self) -> BV<'ctx> {
+        let ast = Ast::new(self.ctx, unsafe {
+            Z3_mk_fpa_to_ieee_bv(self.ctx.z3_ctx, self.z3_ast)
+        });
+        BV::from_ast(ast).unwrap()
+    }
+
+    /// Convert to a real-sorted term. The result is unspecified for NaN
+    /// and the infinities.
+    pub fn to_real(&self) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_fpa_to_real(self.ctx.z3_ctx, self.z3_ast)
+        })
+    }
+}
+
+impl<'ctx> Deref for Float<'ctx> {
+    type Target = Ast<'ctx>;
+
+    fn deref(&self) -> &Ast<'ctx> {
+        &self.ast
+    }
+}
+
+impl<'ctx> From<Float<'ctx>> for Ast<'ctx> {
+    fn from(float: Float<'ctx>) -> Ast<'ctx> {
+        float.ast
+    }
+}
+
+impl<'ctx> fmt::Display for Float<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt::Display::fmt(&self.ast, f)
+    }
+}
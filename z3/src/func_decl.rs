@@ -0,0 +1,328 @@
+use std::ffi::{CStr, CString};
+use std::fmt;
+use z3_sys::*;
+use Ast;
+use Context;
+use FuncDecl;
+use Sort;
+use Symbol;
+
+macro_rules! special_relation {
+    ( $(#[$meta:meta])* $f:ident, $z3fn:ident ) => {
+        $(#[$meta])*
+        pub fn $f(sort: &Sort<'ctx>, id: u32) -> FuncDecl<'ctx> {
+            unsafe { FuncDecl::from_raw(sort.ctx, $z3fn(sort.ctx.z3_ctx, sort.z3_sort, id)) }
+        }
+    };
+}
+
+impl<'ctx> FuncDecl<'ctx> {
+    /// Wrap a function declaration returned by Z3.
+    pub(crate) unsafe fn from_raw(ctx: &'ctx Context, f: Z3_func_decl) -> FuncDecl<'ctx> {
+        ctx.check_null(f);
+        Z3_inc_ref(ctx.z3_ctx, Z3_func_decl_to_ast(ctx.z3_ctx, f));
+        FuncDecl {
+            ctx,
+            z3_func_decl: f,
+        }
+    }
+
+    /// Declare a function named `name` taking arguments of the sorts in
+    /// `domain` and returning a value of sort `range`. With an empty
+    /// `domain`, this declares a constant.
+    ///
+    /// The function is uninterpreted: Z3 only assumes that it returns
+    /// equal results for equal arguments.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    ///
+    /// let int = ctx.int_sort();
+    /// let f = FuncDecl::new(&ctx, &ctx.str_sym("f"), &[&int], &int);
+    /// let x = ctx.named_int_const("x");
+    /// let y = ctx.named_int_const("y");
+    ///
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&x._eq(&y));
+    /// solver.assert(&f.apply(&[&x])._eq(&f.apply(&[&y])).not());
+    /// assert_eq!(solver.check(), SatResult::Unsat);
+    /// ```
+    pub fn new(
+        ctx: &'ctx Context,
+        name: &Symbol<'ctx>,
+        domain: &[&Sort<'ctx>],
+        range: &Sort<'ctx>,
+    ) -> FuncDecl<'ctx> {
+        let domain: Vec<Z3_sort> = domain.iter().map(|s| s.z3_sort).collect();
+        unsafe {
+            let f = Z3_mk_func_decl(
+                ctx.z3_ctx,
+                name.z3_sym,
+                domain.len() as u32,
+                domain.as_ptr(),
+                range.z3_sort,
+            );
+            FuncDecl::from_raw(ctx, f)
+        }
+    }
+
+    /// Declare a function like [`FuncDecl::new()`](#method.new), but
+    /// with a fresh name that starts with `prefix` and is guaranteed not
+    /// to clash with any other declaration.
+    ///
+    /// # See also:
+    ///
+    /// - [`Ast::fresh_const()`](struct.Ast.html#method.fresh_const)
+    pub fn fresh(
+        ctx: &'ctx Context,
+        prefix: &str,
+        domain: &[&Sort<'ctx>],
+        range: &Sort<'ctx>,
+    ) -> FuncDecl<'ctx> {
+        let prefix = CString::new(prefix).unwrap();
+        let domain: Vec<Z3_sort> = domain.iter().map(|s| s.z3_sort).collect();
+        unsafe {
+            let f = Z3_mk_fresh_func_decl(
+                ctx.z3_ctx,
+                prefix.as_ptr(),
+                domain.len() as u32,
+                domain.as_ptr(),
+                range.z3_sort,
+            );
+            FuncDecl::from_raw(ctx, f)
+        }
+    }
+
+    /// Declare a recursive function named `name` taking arguments of the
+    /// sorts in `domain` and returning a value of sort `range`.
+    ///
+    /// The function must then be given a body with
+    /// [`FuncDecl::add_rec_def()`](#method.add_rec_def), which Z3
+    /// unfolds on demand during solving.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    ///
+    /// // (define-fun-rec fact ((n Int)) Int (ite (<= n 0) 1 (* n (fact (- n 1)))))
+    /// let int = ctx.int_sort();
+    /// let fact = FuncDecl::new_recursive(&ctx, &ctx.str_sym("fact"), &[&int], &int);
+    /// let n = ctx.named_int_const("n");
+    /// let one = ctx.from_i64(1);
+    /// let body = n.le(&ctx.from_i64(0))
+    ///     .ite(&one, &n.mul(&[&fact.apply(&[&n.sub(&[&one])])]));
+    /// fact.add_rec_def(&[&n], &body);
+    ///
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&fact.apply(&[&ctx.from_i64(5)])._eq(&ctx.from_i64(120)).not());
+    /// assert_eq!(solver.check(), SatResult::Unsat);
+    /// ```
+    pub fn new_recursive(
+        ctx: &'ctx Context,
+        name: &Symbol<'ctx>,
+        domain: &[&Sort<'ctx>],
+        range: &Sort<'ctx>,
+    ) -> FuncDecl<'ctx> {
+        let domain: Vec<Z3_sort> = domain.iter().map(|s| s.z3_sort).collect();
+        unsafe {
+            let f = Z3_mk_rec_func_decl(
+                ctx.z3_ctx,
+                name.z3_sym,
+                domain.len() as u32,
+                domain.as_ptr(),
+                range.z3_sort,
+            );
+            FuncDecl::from_raw(ctx, f)
+        }
+    }
+
+    /// Define the body of a function declared with
+    /// [`FuncDecl::new_recursive()`](#method.new_recursive). `args` are
+    /// the constants standing for the formal parameters in `body`.
+    ///
+    /// # Preconditions:
+    ///
+    /// - `args.len() == self.arity()`
+    /// - the sorts of `args` match the domain of the declaration, and
+    ///   the sort of `body` matches its range
+    pub fn add_rec_def(&self, args: &[&Ast<'ctx>], body: &Ast<'ctx>) {
+        let mut args: Vec<Z3_ast> = args.iter().map(|a| a.z3_ast).collect();
+        unsafe {
+            Z3_add_rec_def(
+                self.ctx.z3_ctx,
+                self.z3_func_decl,
+                args.len() as u32,
+                args.as_mut_ptr(),
+                body.z3_ast,
+            );
+        }
+    }
+
+    // Special relations. Each is a binary relation over `sort`, and
+    // relations of the same kind and sort are distinguished by `id`.
+    // Z3 decides them with dedicated procedures rather than by
+    // instantiating the order axioms.
+
+    special_relation!(
+        /// Create a partial order: a reflexive, antisymmetric and
+        /// transitive relation.
+        ///
+        /// # Example
+        ///
+        /// ```
+        /// use z3::*;
+        ///
+        /// let cfg = Config::new();
+        /// let ctx = Context::new(&cfg);
+        ///
+        /// let node = ctx.uninterpreted_sort(&ctx.str_sym("Node"));
+        /// let le = FuncDecl::partial_order(&node, 0);
+        /// let a = Ast::new_const(&ctx.str_sym("a"), &node);
+        /// let b = Ast::new_const(&ctx.str_sym("b"), &node);
+        ///
+        /// let solver = Solver::new(&ctx);
+        /// solver.assert(&le.apply(&[&a, &b]));
+        /// solver.assert(&le.apply(&[&b, &a]));
+        /// solver.assert(&a._eq(&b).not());
+        /// assert_eq!(solver.check(), SatResult::Unsat);
+        /// ```
+        partial_order,
+        Z3_mk_partial_order
+    );
+    special_relation!(
+        /// Create a linear order: a partial order in which any two
+        /// elements are comparable.
+        linear_order,
+        Z3_mk_linear_order
+    );
+    special_relation!(
+        /// Create a tree order: a partial order in which the elements
+        /// below any element are linearly ordered.
+        tree_order,
+        Z3_mk_tree_order
+    );
+    special_relation!(
+        /// Create a piecewise linear order: a partial order that is a
+        /// disjoint union of linear orders.
+        piecewise_linear_order,
+        Z3_mk_piecewise_linear_order
+    );
+
+    /// Return the transitive closure `f+` of this binary relation, e.g.
+    /// reachability for an edge relation.
+    ///
+    /// # Preconditions:
+    ///
+    /// - `self` is a binary relation whose arguments have the same sort
+    pub fn transitive_closure(&self) -> FuncDecl<'ctx> {
+        unsafe {
+            FuncDecl::from_raw(
+                self.ctx,
+                Z3_mk_transitive_closure(self.ctx.z3_ctx, self.z3_func_decl),
+            )
+        }
+    }
+
+    /// Return the name of this declaration.
+    pub fn name(&self) -> String {
+        self.symbol().to_string()
+    }
+
+    /// Return the symbol naming this declaration.
+    pub fn symbol(&self) -> Symbol<'ctx> {
+        Symbol::wrap(self.ctx, unsafe {
+            Z3_get_decl_name(self.ctx.z3_ctx, self.z3_func_decl)
+        })
+    }
+
+    /// Return the sort of the `i`-th argument of this declaration.
+    ///
+    /// # Preconditions:
+    ///
+    /// - `i < self.arity()`
+    pub fn domain(&self, i: u32) -> Sort<'ctx> {
+        Sort {
+            ctx: self.ctx,
+            z3_sort: unsafe {
+                let s = Z3_get_domain(self.ctx.z3_ctx, self.z3_func_decl, i);
+                Z3_inc_ref(self.ctx.z3_ctx, Z3_sort_to_ast(self.ctx.z3_ctx, s));
+                s
+            },
+        }
+    }
+
+    /// Return the sort of the result of this declaration.
+    pub fn range(&self) -> Sort<'ctx> {
+        Sort {
+            ctx: self.ctx,
+            z3_sort: unsafe {
+                let s = Z3_get_range(self.ctx.z3_ctx, self.z3_func_decl);
+                Z3_inc_ref(self.ctx.z3_ctx, Z3_sort_to_ast(self.ctx.z3_ctx, s));
+                s
+            },
+        }
+    }
+
+    /// Return the number of arguments of this declaration.
+    pub fn arity(&self) -> u32 {
+        unsafe { Z3_get_arity(self.ctx.z3_ctx, self.z3_func_decl) }
+    }
+
+    /// Create the application of this declaration to `args`. For a
+    /// constant, `args` is empty.
+    ///
+    /// # Preconditions:
+    ///
+    /// - `args.len() == self.arity()`
+    /// - the sorts of `args` match the domain of the declaration
+    pub fn apply(&self, args: &[&Ast<'ctx>]) -> Ast<'ctx> {
+        let args: Vec<Z3_ast> = args.iter().map(|a| a.z3_ast).collect();
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_app(
+                self.ctx.z3_ctx,
+                self.z3_func_decl,
+                args.len() as u32,
+                args.as_ptr(),
+            )
+        })
+    }
+}
+
+impl<'ctx> fmt::Display for FuncDecl<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let p = unsafe { Z3_func_decl_to_string(self.ctx.z3_ctx, self.z3_func_decl) };
+        if p.is_null() {
+            return Result::Err(fmt::Error);
+        }
+        match unsafe { CStr::from_ptr(p) }.to_str() {
+            Ok(s) => write!(f, "{}", s),
+            Err(_) => Result::Err(fmt::Error),
+        }
+    }
+}
+
+impl<'ctx> Clone for FuncDecl<'ctx> {
+    fn clone(&self) -> FuncDecl<'ctx> {
+        unsafe { FuncDecl::from_raw(self.ctx, self.z3_func_decl) }
+    }
+}
+
+impl<'ctx> Drop for FuncDecl<'ctx> {
+    fn drop(&mut self) {
+        unsafe {
+            Z3_dec_ref(
+                self.ctx.z3_ctx,
+                Z3_func_decl_to_ast(self.ctx.z3_ctx, self.z3_func_decl),
+            )
+        };
+    }
+}
@@ -0,0 +1,151 @@
+use z3_sys::*;
+use Ast;
+use AstVector;
+use Context;
+use FuncEntry;
+use FuncInterp;
+
+impl<'ctx> FuncInterp<'ctx> {
+    /// Wrap a function interpretation returned by Z3.
+    pub(crate) unsafe fn from_raw(ctx: &'ctx Context, f: Z3_func_interp) -> FuncInterp<'ctx> {
+        ctx.check_null(f);
+        Z3_func_interp_inc_ref(ctx.z3_ctx, f);
+        FuncInterp {
+            ctx,
+            z3_func_interp: f,
+        }
+    }
+
+    /// Return the number of explicit entries in the interpretation.
+    pub fn num_entries(&self) -> u32 {
+        unsafe { Z3_func_interp_get_num_entries(self.ctx.z3_ctx, self.z3_func_interp) }
+    }
+
+    /// Return the number of arguments the interpreted function takes.
+    pub fn arity(&self) -> u32 {
+        unsafe { Z3_func_interp_get_arity(self.ctx.z3_ctx, self.z3_func_interp) }
+    }
+
+    /// Return the `i`-th entry of the interpretation, or `None` if `i`
+    /// is not below [`num_entries`](#method.num_entries).
+    pub fn entry(&self, i: u32) -> Option<FuncEntry<'ctx>> {
+        unsafe {
+            if i >= Z3_func_interp_get_num_entries(self.ctx.z3_ctx, self.z3_func_interp) {
+                return None;
+            }
+            let e = Z3_func_interp_get_entry(self.ctx.z3_ctx, self.z3_func_interp, i);
+            Some(FuncEntry::from_raw(self.ctx, e))
+        }
+    }
+
+    /// Return the entries of the interpretation.
+    ///
+    /// # See also:
+    ///
+    /// - [`FuncInterp::entry()`](#method.entry)
+    pub fn entries(&self) -> Vec<FuncEntry<'ctx>> {
+        unsafe {
+            let n = Z3_func_interp_get_num_entries(self.ctx.z3_ctx, self.z3_func_interp);
+            (0..n)
+                .map(|i| {
+                    let e = Z3_func_interp_get_entry(self.ctx.z3_ctx, self.z3_func_interp, i);
+                    FuncEntry::from_raw(self.ctx, e)
+                })
+                .collect()
+        }
+    }
+
+    /// Add an entry mapping `args`, which should be values such as
+    /// numerals, to `value`. An existing entry for the same arguments is
+    /// replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of arguments does not match the
+    /// [`arity`](#method.arity).
+    pub fn add_entry(&mut self, args: &[&Ast<'ctx>], value: &Ast<'ctx>) {
+        assert_eq!(args.len() as u32, self.arity(), "wrong number of arguments");
+        let v = AstVector::from_slice(self.ctx, args);
+        unsafe {
+            Z3_func_interp_add_entry(
+                self.ctx.z3_ctx,
+                self.z3_func_interp,
+                v.z3_ast_vector,
+                value.z3_ast,
+            )
+        };
+    }
+
+    /// Set the value of the function for arguments not covered by any
+    /// of the [`entries`](#method.entries).
+    pub fn set_else(&mut self, value: &Ast<'ctx>) {
+        unsafe { Z3_func_interp_set_else(self.ctx.z3_ctx, self.z3_func_interp, value.z3_ast) };
+    }
+
+    /// Return the value of the function for arguments not covered by
+    /// any of the [`entries`](#method.entries).
+    pub fn else_value(&self) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_func_interp_get_else(self.ctx.z3_ctx, self.z3_func_interp)
+        })
+    }
+}
+
+impl<'ctx> Clone for FuncInterp<'ctx> {
+    fn clone(&self) -> FuncInterp<'ctx> {
+        unsafe { FuncInterp::from_raw(self.ctx, self.z3_func_interp) }
+    }
+}
+
+impl<'ctx> Drop for FuncInterp<'ctx> {
+    fn drop(&mut self) {
+        unsafe { Z3_func_interp_dec_ref(self.ctx.z3_ctx, self.z3_func_interp) };
+    }
+}
+
+impl<'ctx> FuncEntry<'ctx> {
+    /// Wrap a function entry returned by Z3.
+    pub(crate) unsafe fn from_raw(ctx: &'ctx Context, e: Z3_func_entry) -> FuncEntry<'ctx> {
+        ctx.check_null(e);
+        Z3_func_entry_inc_ref(ctx.z3_ctx, e);
+        FuncEntry {
+            ctx,
+            z3_func_entry: e,
+        }
+    }
+
+    /// Return the number of arguments of the entry.
+    pub fn num_args(&self) -> u32 {
+        unsafe { Z3_func_entry_get_num_args(self.ctx.z3_ctx, self.z3_func_entry) }
+    }
+
+    /// Return the arguments of the entry.
+    pub fn args(&self) -> Vec<Ast<'ctx>> {
+        let raw: Vec<Z3_ast> = unsafe {
+            let n = Z3_func_entry_get_num_args(self.ctx.z3_ctx, self.z3_func_entry);
+            (0..n)
+                .map(|i| Z3_func_entry_get_arg(self.ctx.z3_ctx, self.z3_func_entry, i))
+                .collect()
+        };
+        raw.into_iter().map(|a| Ast::new(self.ctx, a)).collect()
+    }
+
+    /// Return the value of the function at [`args`](#method.args).
+    pub fn value(&self) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_func_entry_get_value(self.ctx.z3_ctx, self.z3_func_entry)
+        })
+    }
+}
+
+impl<'ctx> Clone for FuncEntry<'ctx> {
+    fn clone(&self) -> FuncEntry<'ctx> {
+        unsafe { FuncEntry::from_raw(self.ctx, self.z3_func_entry) }
+    }
+}
+
+impl<'ctx> Drop for FuncEntry<'ctx> {
+    fn drop(&mut self) {
+        unsafe { Z3_func_entry_dec_ref(self.ctx.z3_ctx, self.z3_func_entry) };
+    }
+}
@@ -0,0 +1,101 @@
+use rug::{Integer, Rational};
+use std::ffi::CStr;
+use z3_sys::*;
+use Ast;
+use Context;
+
+impl<'ctx> Ast<'ctx> {
+    /// Create an integer numeral with the value `i`.
+    ///
+    /// Z3 4.8.12 takes no GMP numbers and only parses numerals in
+    /// decimal, so values that do not fit in an `i64` are built from
+    /// their bits instead, as the bit-vector numeral of `|i|` converted to
+    /// an integer.
+    ///
+    /// Requires the `rug` feature.
+    pub fn from_rug_integer(ctx: &'ctx Context, i: &Integer) -> Ast<'ctx> {
+        if let Some(i) = i.to_i64() {
+            return Ast::from_i64(ctx, i);
+        }
+        let abs = Integer::from(i.abs_ref());
+        let bits: Vec<bool> = (0..abs.significant_bits())
+            .map(|n| abs.get_bit(n))
+            .collect();
+        let bv = Ast::new(ctx, unsafe {
+            Z3_mk_bv_numeral(ctx.z3_ctx, bits.len() as u32, bits.as_ptr())
+        });
+        let int = Ast::new(ctx, unsafe { Z3_mk_bv2int(ctx.z3_ctx, bv.z3_ast, false) });
+        if *i < 0 {
+            int.minus().simplify()
+        } else {
+            int.simplify()
+        }
+    }
+
+    /// Create a real numeral with the value `r`.
+    ///
+    /// Requires the `rug` feature.
+    pub fn from_rug_rational(ctx: &'ctx Context, r: &Rational) -> Ast<'ctx> {
+        let num = Ast::from_rug_integer(ctx, r.numer()).int2real();
+        let den = Ast::from_rug_integer(ctx, r.denom()).int2real();
+        num.div(&den).simplify()
+    }
+
+    /// Return the value of an integer-valued numeral, or `None` if
+    /// `self` is not a numeral or its value is not an integer.
+    ///
+    /// The value is read in binary, as Z3 4.8.12 has no way to return it
+    /// as a GMP number. Bit-vector numerals are read as unsigned.
+    ///
+    /// Requires the `rug` feature.
+    pub fn as_rug_integer(&self) -> Option<Integer> {
+        if !self.is_numeral() {
+            return None;
+        }
+        if let Some(i) = self.binary_digits() {
+            return Some(i);
+        }
+        // Z3 only gives the digits of non-negative values.
+        match self.sort_kind() {
+            SortKind::Int | SortKind::Real => self.minus().simplify().binary_digits().map(|i| -i),
+            _ => None,
+        }
+    }
+
+    /// Return the value of an integer or real numeral as a fraction, or
+    /// `None` if `self` is not such a numeral.
+    ///
+    /// Requires the `rug` feature.
+    pub fn as_rug_rational(&self) -> Option<Rational> {
+        let (num, den) = self.numerator_denominator()?;
+        Some(Rational::from((
+            num.as_rug_integer()?,
+            den.as_rug_integer()?,
+        )))
+    }
+
+    /// Return the value of a non-negative integer-valued numeral, which
+    /// Z3 writes in binary.
+    fn binary_digits(&self) -> Option<Integer> {
+        let s = unsafe {
+            let p = self
+                .ctx
+                .try_z3(|| Z3_get_numeral_binary_string(self.ctx.z3_ctx, self.z3_ast))
+                .ok()?;
+            CStr::from_ptr(p).to_str().ok()?.to_owned()
+        };
+        Integer::from_str_radix(&s, 2).ok()
+    }
+}
+
+impl<'a, 'ctx> From<(&'ctx Context, &'a Integer)> for Ast<'ctx> {
+    fn from((ctx, i): (&'ctx Context, &'a Integer)) -> Ast<'ctx> {
+        Ast::from_rug_integer(ctx, i)
+    }
+}
+
+impl<'a, 'ctx> From<(&'ctx Context, &'a Rational)> for Ast<'ctx> {
+    fn from((ctx, r): (&'ctx Context, &'a Rational)) -> Ast<'ctx> {
+        Ast::from_rug_rational(ctx, r)
+    }
+}
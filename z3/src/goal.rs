@@ -0,0 +1,157 @@
+use std::ffi::CStr;
+use std::fmt;
+use z3_sys::*;
+use Ast;
+use Context;
+use Goal;
+use GoalPrec;
+use Z3Error;
+
+impl<'ctx> Goal<'ctx> {
+    /// Create an empty goal.
+    ///
+    /// If `models` is `true`, models can be extracted from the goal once
+    /// tactics have been applied to it. Likewise `unsat_cores` and
+    /// `proofs` enable unsat cores and proofs; proofs also require a
+    /// context created with proof generation enabled.
+    pub fn new(ctx: &'ctx Context, models: bool, unsat_cores: bool, proofs: bool) -> Goal<'ctx> {
+        unsafe { Goal::from_raw(ctx, Z3_mk_goal(ctx.z3_ctx, models, unsat_cores, proofs)) }
+    }
+
+    /// Wrap a goal returned by Z3.
+    pub(crate) unsafe fn from_raw(ctx: &'ctx Context, g: Z3_goal) -> Goal<'ctx> {
+        ctx.check_null(g);
+        Z3_goal_inc_ref(ctx.z3_ctx, g);
+        Goal { ctx, z3_goal: g }
+    }
+
+    /// Add the Boolean formula `ast` to the goal.
+    pub fn assert(&self, ast: &Ast<'ctx>) {
+        unsafe {
+            self.ctx
+                .check_z3(|| Z3_goal_assert(self.ctx.z3_ctx, self.z3_goal, ast.z3_ast))
+        };
+    }
+
+    /// Return the number of formulas in the goal.
+    pub fn len(&self) -> usize {
+        unsafe { Z3_goal_size(self.ctx.z3_ctx, self.z3_goal) as usize }
+    }
+
+    /// Return `true` if the goal contains no formulas.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the number of formulas, subformulas and terms in the goal.
+    pub fn num_exprs(&self) -> usize {
+        unsafe { Z3_goal_num_exprs(self.ctx.z3_ctx, self.z3_goal) as usize }
+    }
+
+    /// Iterate over the formulas in the goal.
+    pub fn formulas<'a>(&'a self) -> impl Iterator<Item = Ast<'ctx>> + 'a {
+        (0..self.len() as u32).map(move |i| {
+            Ast::new(self.ctx, unsafe {
+                Z3_goal_formula(self.ctx.z3_ctx, self.z3_goal, i)
+            })
+        })
+    }
+
+    /// Return `true` if the goal contains the formula `false`.
+    pub fn is_inconsistent(&self) -> bool {
+        unsafe { Z3_goal_inconsistent(self.ctx.z3_ctx, self.z3_goal) }
+    }
+
+    /// Return `true` if the goal is empty and its precision is
+    /// [`GoalPrec::Precise`] or [`GoalPrec::Under`].
+    ///
+    /// [`GoalPrec::Precise`]: enum.GoalPrec.html#variant.Precise
+    /// [`GoalPrec::Under`]: enum.GoalPrec.html#variant.Under
+    pub fn is_decided_sat(&self) -> bool {
+        unsafe { Z3_goal_is_decided_sat(self.ctx.z3_ctx, self.z3_goal) }
+    }
+
+    /// Return `true` if the goal contains `false` and its precision is
+    /// [`GoalPrec::Precise`] or [`GoalPrec::Over`].
+    ///
+    /// [`GoalPrec::Precise`]: enum.GoalPrec.html#variant.Precise
+    /// [`GoalPrec::Over`]: enum.GoalPrec.html#variant.Over
+    pub fn is_decided_unsat(&self) -> bool {
+        unsafe { Z3_goal_is_decided_unsat(self.ctx.z3_ctx, self.z3_goal) }
+    }
+
+    /// Return the depth of the goal, i.e. the number of tactics that were
+    /// applied to produce it.
+    pub fn depth(&self) -> u32 {
+        unsafe { Z3_goal_depth(self.ctx.z3_ctx, self.z3_goal) }
+    }
+
+    /// Return whether the goal is precise, or the result of an under- or
+    /// over-approximation.
+    pub fn precision(&self) -> GoalPrec {
+        unsafe { Z3_goal_precision(self.ctx.z3_ctx, self.z3_goal) }
+    }
+
+    /// Convert the goal into a CNF formula in DIMACS format, for use with
+    /// external SAT solvers.
+    ///
+    /// The goal must already be in CNF, e.g. by applying the `simplify`,
+    /// `bit-blast` and `tseitin-cnf` tactics. Theory atoms that have not
+    /// been bit-blasted are encoded as opaque Boolean variables. If
+    /// `include_names` is true, the names of the Boolean constants are
+    /// emitted as comments mapping them to DIMACS variables. Returns an
+    /// error if the goal cannot be converted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_bitvector_const("x", 2);
+    /// let goal = Goal::new(&ctx, true, false, false);
+    /// goal.assert(&x.bvugt(&ctx.bitvector_sort(2).from_u64(1)));
+    ///
+    /// let cnf = Tactic::new(&ctx, "simplify")
+    ///     .and_then(&Tactic::new(&ctx, "bit-blast"))
+    ///     .and_then(&Tactic::new(&ctx, "tseitin-cnf"))
+    ///     .apply(&goal)
+    ///     .unwrap()
+    ///     .subgoal(0);
+    /// assert!(cnf.to_dimacs_string(false).unwrap().starts_with("p cnf"));
+    /// ```
+    pub fn to_dimacs_string(&self, include_names: bool) -> Result<String, Z3Error> {
+        unsafe {
+            let c = self.ctx.z3_ctx;
+            let p = self
+                .ctx
+                .try_z3(|| Z3_goal_to_dimacs_string(c, self.z3_goal, include_names))?;
+            Ok(CStr::from_ptr(p).to_string_lossy().into_owned())
+        }
+    }
+
+    /// Remove all formulas from the goal.
+    pub fn reset(&self) {
+        unsafe { Z3_goal_reset(self.ctx.z3_ctx, self.z3_goal) };
+    }
+}
+
+impl<'ctx> fmt::Display for Goal<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let p = unsafe { Z3_goal_to_string(self.ctx.z3_ctx, self.z3_goal) };
+        if p.is_null() {
+            return Result::Err(fmt::Error);
+        }
+        match unsafe { CStr::from_ptr(p) }.to_str() {
+            Ok(s) => write!(f, "{}", s),
+            Err(_) => Result::Err(fmt::Error),
+        }
+    }
+}
+
+impl<'ctx> Drop for Goal<'ctx> {
+    fn drop(&mut self) {
+        unsafe { Z3_goal_dec_ref(self.ctx.z3_ctx, self.z3_goal) };
+    }
+}
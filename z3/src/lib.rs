@@ -9,21 +9,102 @@ extern crate lazy_static;
 
 extern crate z3_sys;
 
+#[cfg(feature = "num")]
+extern crate num_bigint;
+#[cfg(feature = "num")]
+extern crate num_rational;
+#[cfg(feature = "rug")]
+extern crate rug;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "derive")]
+extern crate z3_derive;
+
+use std::cell::RefCell;
 use std::ffi::CString;
-use std::sync::Mutex;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
 use z3_sys::*;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+pub use z3_sys::AstKind;
+pub use z3_sys::AstPrintMode;
+pub use z3_sys::ErrorCode;
+pub use z3_sys::GoalPrec;
+pub use z3_sys::ParamKind;
+pub use z3_sys::SymbolKind;
+
+pub use memory::{estimated_alloc_size, finalize_memory, reset_memory};
+pub use params::{get_global_param, reset_all_global_params, set_global_param};
+pub use propagator::UserPropagator;
+pub use version::{full_version, version};
+#[cfg(feature = "derive")]
+pub use z3_derive::Z3Sort;
+pub use z3_sort::{Z3Datatype, Z3Sort};
+
+#[doc(hidden)]
+pub use macro_term::MacroContext;
+#[doc(hidden)]
+pub use z3_sort::DatatypeGroup;
+
+#[cfg(feature = "z3_4_13")]
+use on_clause::InstalledOnClause;
+use propagator::InstalledPropagator;
+
+#[macro_use]
+mod macros;
+
+mod apply_result;
+mod array;
 mod ast;
+mod ast_vector;
+#[cfg(feature = "num")]
+mod bignum;
+mod bv;
+mod check_result;
 mod config;
 mod context;
+mod datatype;
+mod error;
+mod fixedpoint;
+mod float;
+mod func_decl;
+mod func_interp;
+#[cfg(feature = "rug")]
+mod gmp;
+mod goal;
+mod macro_term;
+mod maxsat;
+mod memory;
 mod model;
+#[cfg(feature = "z3_4_13")]
+mod on_clause;
 mod optimize;
+mod owned;
+mod params;
+mod pattern;
+mod portfolio;
+mod probe;
+mod propagator;
+mod regexp;
+mod seq;
+#[cfg(feature = "serde")]
+mod serialize;
+mod set;
 mod solver;
 mod sort;
+mod statistics;
 mod symbol;
+mod tactic;
+mod version;
+mod z3_sort;
 
-// Z3 appears to be only mostly-threadsafe, a few initializers
-// and such race; so we mutex-guard all access to the library.
+// Z3 is threadsafe across contexts, except for process-wide state such
+// as configurations and global parameters; access to that is guarded by
+// this mutex. A context itself is only ever used by one thread at a time,
+// since `Context` is not `Sync`.
 lazy_static! {
     static ref Z3_MUTEX: Mutex<()> = Mutex::new(());
 }
@@ -34,17 +115,74 @@ pub struct Config {
     z3_cfg: Z3_config,
 }
 
+/// Version of the linked Z3 library. Versions compare by their numbers
+/// in order, so features can be enabled depending on the version.
+///
+/// # See also:
+///
+/// - [`version()`](fn.version.html)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub build_number: u32,
+    pub revision_number: u32,
+}
+
+/// Error reported by Z3 for a fallible operation, such as parsing
+/// malformed input or requesting a model that does not exist.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Z3Error {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
 /// Manager of all other Z3 objects, global configuration options, etc.
 pub struct Context {
     z3_ctx: Z3_context,
 }
 
+/// Handle to a [`Context`](struct.Context.html) that can be shared with
+/// other threads, e.g. a watchdog, in order to interrupt it. Unlike the
+/// context itself it is `Send` and `Sync`, and it offers nothing but
+/// [`interrupt()`](#method.interrupt).
+///
+/// # See also:
+///
+/// - [`Context::handle()`](struct.Context.html#method.handle)
+pub struct ContextHandle<'ctx> {
+    z3_ctx: Z3_context,
+    ctx: PhantomData<&'ctx Context>,
+}
+
+/// Result of a satisfiability check, together with the model if one is
+/// available.
 pub enum CheckResult<'a> {
     Satisfiable(Model<'a>),
-    Unknown(Model<'a>),
+    /// The result could not be determined. The model, if any, is partial,
+    /// and the string gives the reason, for instance `"timeout"` or
+    /// `"canceled"`.
+    Unknown(Option<Model<'a>>, String),
     Unsatisfiable
 }
 
+/// Outcome of a satisfiability check, without the model.
+///
+/// # See also:
+///
+/// - [`Solver::check()`](struct.Solver.html#method.check)
+/// - [`Optimize::check()`](struct.Optimize.html#method.check)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SatResult {
+    /// The constraints are satisfiable.
+    Sat,
+    /// The constraints are unsatisfiable.
+    Unsat,
+    /// Z3 could not decide the constraints, e.g. because of a timeout or
+    /// incomplete quantifier reasoning.
+    Unknown,
+}
+
 /// Symbols are used to name several term and type constructors.
 ///
 /// # Creation:
@@ -73,10 +211,411 @@ pub struct Ast<'ctx> {
     z3_ast: Z3_ast,
 }
 
+/// A vector of [`Ast`](struct.Ast.html)s owned by Z3, as used by APIs
+/// such as unsat cores and assertion lists.
+///
+/// # Example
+///
+/// ```
+/// use z3::*;
+///
+/// let cfg = Config::new();
+/// let ctx = Context::new(&cfg);
+/// let mut v = AstVector::new(&ctx);
+/// v.push(&ctx.named_int_const("x"));
+/// v.push(&ctx.from_i64(1));
+/// assert_eq!(v.len(), 2);
+/// assert_eq!(v.get(1).unwrap().as_i64(), Some(1));
+/// assert_eq!(v.to_string(), "(ast-vector\n  x\n  1)");
+/// ```
+pub struct AstVector<'ctx> {
+    ctx: &'ctx Context,
+    z3_ast_vector: Z3_ast_vector,
+}
+
+/// Function declaration. Every constant and function application is
+/// built from a `FuncDecl`, using [`FuncDecl::apply()`].
+///
+/// Uninterpreted functions are declared with [`FuncDecl::new()`];
+/// datatype constructors, testers and accessors are returned by
+/// [`DatatypeBuilder`](struct.DatatypeBuilder.html).
+///
+/// [`FuncDecl::apply()`]: struct.FuncDecl.html#method.apply
+/// [`FuncDecl::new()`]: struct.FuncDecl.html#method.new
+pub struct FuncDecl<'ctx> {
+    ctx: &'ctx Context,
+    z3_func_decl: Z3_func_decl,
+}
+
+/// Pattern (also known as a trigger) used to guide the instantiation of
+/// quantifiers.
+///
+/// # See also:
+///
+/// - [`Ast::forall_const()`](struct.Ast.html#method.forall_const)
+/// - [`Ast::exists_const()`](struct.Ast.html#method.exists_const)
+pub struct Pattern<'ctx> {
+    ctx: &'ctx Context,
+    z3_patt: Z3_pattern,
+}
+
+/// Bitvector expression, that is an [`Ast`](struct.Ast.html) whose sort
+/// is a bitvector sort of a fixed size.
+///
+/// A `BV` dereferences to its underlying `Ast`, so all of the generic
+/// operations remain available.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct BV<'ctx> {
+    ast: Ast<'ctx>,
+}
+
+/// Array expression, that is an [`Ast`](struct.Ast.html) whose sort is an
+/// array sort mapping a domain sort to a range sort.
+///
+/// Arrays in Z3 are total maps, which makes them suitable for modelling
+/// memories and dictionaries. An `Array` dereferences to its underlying
+/// `Ast`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Array<'ctx> {
+    ast: Ast<'ctx>,
+}
+
+/// Sequence expression, that is an [`Ast`](struct.Ast.html) whose sort is
+/// a sequence sort. Strings are sequences of characters, so string
+/// expressions are also represented as a `Seq`.
+///
+/// A `Seq` dereferences to its underlying `Ast`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Seq<'ctx> {
+    ast: Ast<'ctx>,
+}
+
+/// Regular expression over sequences, that is an [`Ast`](struct.Ast.html)
+/// of regular expression sort.
+///
+/// # See also:
+///
+/// - [`Seq::in_re()`](struct.Seq.html#method.in_re)
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Regexp<'ctx> {
+    ast: Ast<'ctx>,
+}
+
+/// Set expression, that is an [`Ast`](struct.Ast.html) whose sort is a
+/// set sort over some element sort.
+///
+/// Z3 represents sets as arrays from the element sort to `Bool`, so a
+/// `Set` dereferences to its underlying `Ast`, which is also a valid
+/// [`Array`](struct.Array.html).
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Set<'ctx> {
+    ast: Ast<'ctx>,
+}
+
+/// Floating-point expression, that is an [`Ast`](struct.Ast.html) of
+/// floating-point sort with IEEE 754 semantics.
+///
+/// A `Float` dereferences to its underlying `Ast`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Float<'ctx> {
+    ast: Ast<'ctx>,
+}
+
+/// IEEE 754 rounding modes, used by floating-point arithmetic and
+/// conversions.
+///
+/// Use [`RoundingMode::to_ast()`](enum.RoundingMode.html#method.to_ast)
+/// to obtain the corresponding rounding-mode term.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum RoundingMode {
+    /// Round to the nearest value, with ties going to the even one.
+    NearestTiesToEven,
+    /// Round to the nearest value, with ties going away from zero.
+    NearestTiesToAway,
+    /// Round towards positive infinity.
+    TowardPositive,
+    /// Round towards negative infinity.
+    TowardNegative,
+    /// Round towards zero.
+    TowardZero,
+}
+
+/// SMT-LIB logics for which Z3 has a specialized solver, for use with
+/// [`Solver::new_for_logic()`](struct.Solver.html#method.new_for_logic).
+///
+/// Each variant is named after the logic. Logics not listed here can
+/// be passed by name as a string.
+#[allow(non_camel_case_types)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Logic {
+    /// Quantifier-free uninterpreted functions.
+    QF_UF,
+    /// Quantifier-free bit-vectors.
+    QF_BV,
+    /// Quantifier-free arrays of bit-vectors.
+    QF_ABV,
+    /// Quantifier-free arrays, uninterpreted functions and bit-vectors.
+    QF_AUFBV,
+    /// Quantifier-free uninterpreted functions and bit-vectors.
+    QF_UFBV,
+    /// Quantifier-free integer difference logic.
+    QF_IDL,
+    /// Quantifier-free real difference logic.
+    QF_RDL,
+    /// Quantifier-free linear integer arithmetic.
+    QF_LIA,
+    /// Quantifier-free linear real arithmetic.
+    QF_LRA,
+    /// Quantifier-free non-linear integer arithmetic.
+    QF_NIA,
+    /// Quantifier-free non-linear real arithmetic.
+    QF_NRA,
+    /// Quantifier-free uninterpreted functions and linear integer
+    /// arithmetic.
+    QF_UFLIA,
+    /// Quantifier-free floating point.
+    QF_FP,
+    /// Quantifier-free floating point and bit-vectors.
+    QF_FPBV,
+    /// Quantifier-free strings.
+    QF_S,
+    /// Linear integer arithmetic.
+    LIA,
+    /// Linear real arithmetic.
+    LRA,
+    /// Non-linear integer arithmetic.
+    NIA,
+    /// Non-linear real arithmetic.
+    NRA,
+    /// Uninterpreted functions and linear integer arithmetic.
+    UFLIA,
+    /// Arrays, uninterpreted functions and linear integer arithmetic.
+    AUFLIA,
+    /// Uninterpreted functions and bit-vectors.
+    UFBV,
+}
+
+/// Builder for an algebraic datatype sort, such as a record, an
+/// enumeration or a (possibly recursive) union of records.
+///
+/// Each call to [`DatatypeBuilder::variant()`] adds a constructor. Use
+/// [`DatatypeBuilder::finish()`] to declare a single datatype, or
+/// [`DatatypeBuilder::create_datatypes()`] to declare several mutually
+/// recursive ones.
+///
+/// [`DatatypeBuilder::variant()`]: struct.DatatypeBuilder.html#method.variant
+/// [`DatatypeBuilder::finish()`]: struct.DatatypeBuilder.html#method.finish
+/// [`DatatypeBuilder::create_datatypes()`]: struct.DatatypeBuilder.html#method.create_datatypes
+pub struct DatatypeBuilder<'ctx> {
+    ctx: &'ctx Context,
+    name: String,
+    constructors: Vec<(String, Vec<(String, DatatypeAccessor<'ctx>)>)>,
+}
+
+/// Sort of a field of a datatype constructor.
+pub enum DatatypeAccessor<'ctx> {
+    /// A field of an already existing sort.
+    Sort(Sort<'ctx>),
+    /// A field whose sort is one of the datatypes being declared, referred
+    /// to by name. This is how recursive datatypes are expressed.
+    Datatype(String),
+}
+
+/// The function declarations associated with one constructor of a
+/// datatype.
+pub struct DatatypeVariant<'ctx> {
+    /// Constructor, taking one argument per field.
+    pub constructor: FuncDecl<'ctx>,
+    /// Recognizer, the predicate that holds of values built with
+    /// `constructor`.
+    pub tester: FuncDecl<'ctx>,
+    /// Field accessors, in declaration order.
+    pub accessors: Vec<FuncDecl<'ctx>>,
+}
+
+/// A datatype sort together with the declarations of its constructors,
+/// as returned by [`DatatypeBuilder`](struct.DatatypeBuilder.html).
+pub struct DatatypeSort<'ctx> {
+    /// The datatype sort itself.
+    pub sort: Sort<'ctx>,
+    /// One entry per constructor, in declaration order.
+    pub variants: Vec<DatatypeVariant<'ctx>>,
+}
+
+/// Handle to a soft constraint of an [`Optimize`](struct.Optimize.html)
+/// context, as returned by
+/// [`Optimize::add_soft()`](struct.Optimize.html#method.add_soft).
+///
+/// Like an [`OptimizeObjective`](struct.OptimizeObjective.html), the
+/// handle of a constraint added after a `push()` is no longer valid once
+/// the matching `pop()` removes its group's objective.
+pub struct SoftConstraint<'ctx> {
+    ctx: &'ctx Context,
+    z3_opt: Z3_optimize,
+    idx: u32,
+    constraint: Ast<'ctx>,
+}
+
+/// Statistics collected by a solver or optimization context, such as
+/// the number of conflicts and decisions.
+///
+/// # See also:
+///
+/// - [`Optimize::get_statistics()`](struct.Optimize.html#method.get_statistics)
+pub struct Statistics<'ctx> {
+    ctx: &'ctx Context,
+    z3_stats: Z3_stats,
+}
+
+/// Value of a [`Statistics`](struct.Statistics.html) entry.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StatisticsValue {
+    UInt(u32),
+    Double(f64),
+}
+
+/// A key-value pair of a [`Statistics`](struct.Statistics.html) object.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatisticsEntry {
+    pub key: String,
+    pub value: StatisticsValue,
+}
+
+/// Set of parameters used to configure solvers, tactics and other Z3
+/// objects.
+///
+/// Unlike a [`Config`](struct.Config.html), which is only consulted when
+/// a context is created, `Params` can be applied to individual objects
+/// at any time, e.g. with
+/// [`Optimize::set_params()`](struct.Optimize.html#method.set_params).
+pub struct Params<'ctx> {
+    ctx: &'ctx Context,
+    z3_params: Z3_params,
+}
+
+/// Descriptions of the parameters accepted by a solver, tactic or other
+/// configurable Z3 object.
+///
+/// # See also:
+///
+/// - [`Solver::get_param_descrs()`](struct.Solver.html#method.get_param_descrs)
+/// - [`Optimize::get_param_descrs()`](struct.Optimize.html#method.get_param_descrs)
+pub struct ParamDescrs<'ctx> {
+    ctx: &'ctx Context,
+    z3_param_descrs: Z3_param_descrs,
+}
+
 /// (Incremental) solver, possibly specialized by a particular tactic or logic.
 pub struct Solver<'ctx> {
     ctx: &'ctx Context,
     z3_slv: Z3_solver,
+    propagator: Option<RefCell<InstalledPropagator>>,
+    #[cfg(feature = "z3_4_13")]
+    on_clause: Option<RefCell<InstalledOnClause>>,
+}
+
+/// Handle passed to the callbacks of a
+/// [`UserPropagator`](trait.UserPropagator.html), used to report
+/// consequences and conflicts to the solver.
+pub struct PropagatorCallback<'ctx> {
+    ctx: &'ctx Context,
+    z3_cb: Z3_solver_callback,
+}
+
+/// Basic building block for creating custom solvers for specific problem
+/// domains.
+///
+/// Z3 provides many built-in tactics, identified by name (see
+/// [`Tactic::list_all()`](struct.Tactic.html#method.list_all)), which
+/// can be combined into more complex strategies with combinators such as
+/// [`Tactic::and_then()`](struct.Tactic.html#method.and_then) and
+/// [`Tactic::or_else()`](struct.Tactic.html#method.or_else).
+pub struct Tactic<'ctx> {
+    ctx: &'ctx Context,
+    z3_tactic: Z3_tactic,
+}
+
+/// Collection of formulas that a [`Tactic`](struct.Tactic.html) can be
+/// applied to.
+///
+/// # Example
+///
+/// ```
+/// use z3::*;
+///
+/// let cfg = Config::new();
+/// let ctx = Context::new(&cfg);
+/// let x = ctx.named_int_const("x");
+///
+/// let goal = Goal::new(&ctx, true, false, false);
+/// goal.assert(&x.gt(&ctx.from_i64(0)));
+/// goal.assert(&x.lt(&ctx.from_i64(10)));
+/// assert_eq!(goal.len(), 2);
+/// assert!(!goal.is_decided_sat());
+/// ```
+pub struct Goal<'ctx> {
+    ctx: &'ctx Context,
+    z3_goal: Z3_goal,
+}
+
+/// Result of applying a [`Tactic`](struct.Tactic.html) to a
+/// [`Goal`](struct.Goal.html): the goal is satisfiable if and only if one
+/// of the subgoals is.
+///
+/// # See also:
+///
+/// - [`Tactic::apply()`](struct.Tactic.html#method.apply)
+pub struct ApplyResult<'ctx> {
+    ctx: &'ctx Context,
+    z3_apply_result: Z3_apply_result,
+}
+
+/// Function that measures a [`Goal`](struct.Goal.html), e.g. its number
+/// of constants or whether it is propositional.
+///
+/// Probes are evaluated with
+/// [`Probe::apply()`](struct.Probe.html#method.apply), where Boolean
+/// probes return `1.0` for true and `0.0` for false, and can steer
+/// tactics through [`Tactic::when()`](struct.Tactic.html#method.when)
+/// and [`Tactic::cond()`](struct.Tactic.html#method.cond).
+pub struct Probe<'ctx> {
+    ctx: &'ctx Context,
+    z3_probe: Z3_probe,
+}
+
+/// Context for the recursive predicate solvers of Z3, used to answer
+/// queries over Datalog programs and constrained Horn clauses.
+///
+/// # Example
+///
+/// ```
+/// use z3::*;
+///
+/// let cfg = Config::new();
+/// let ctx = Context::new(&cfg);
+/// let int = ctx.int_sort();
+/// let inv = FuncDecl::new(&ctx, &ctx.str_sym("inv"), &[&int], &ctx.bool_sort());
+/// let x = ctx.named_int_const("x");
+///
+/// let fp = Fixedpoint::new(&ctx);
+/// fp.register_relation(&inv);
+/// // inv(0), and inv(x) && x < 10 => inv(x + 1)
+/// fp.add_rule(&inv.apply(&[&ctx.from_i64(0)]));
+/// let step = inv.apply(&[&x])
+///     .and(&[&x.lt(&ctx.from_i64(10))])
+///     .implies(&inv.apply(&[&x.add(&[&ctx.from_i64(1)])]));
+/// fp.add_rule(&Ast::forall_const(&ctx, &[&x], &[], &step));
+///
+/// let reach = |n| {
+///     let q = inv.apply(&[&x]).and(&[&x._eq(&ctx.from_i64(n))]);
+///     fp.query(&Ast::exists_const(&ctx, &[&x], &[], &q)).unwrap()
+/// };
+/// assert_eq!(reach(10), SatResult::Sat);
+/// assert_eq!(reach(11), SatResult::Unsat);
+/// ```
+pub struct Fixedpoint<'ctx> {
+    ctx: &'ctx Context,
+    z3_fixedpoint: Z3_fixedpoint,
 }
 
 /// Model for the constraints inserted into the logical context.
@@ -85,8 +624,299 @@ pub struct Model<'ctx> {
     z3_mdl: Z3_model,
 }
 
+/// The value of a constant in a [`Model`](struct.Model.html), as
+/// returned by [`Model::values()`](struct.Model.html#method.values).
+///
+/// Numbers are kept as decimal strings so that they do not lose
+/// precision. In serde formats such as JSON, a value is written as its
+/// sort and value, e.g. `{"sort":"int","value":"-12"}`.
+///
+/// Requires the `serde` feature.
+#[cfg(feature = "serde")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "sort", content = "value", rename_all = "lowercase")]
+pub enum ModelValue {
+    Bool(bool),
+    /// An integer, e.g. `-12`.
+    Int(String),
+    /// A rational number, e.g. `-1/3`.
+    Real(String),
+    /// A bit-vector of `width` bits, with its unsigned value.
+    BitVec {
+        width: u32,
+        value: String,
+    },
+    /// A string, with non-printable characters escaped as in SMT-LIB2.
+    String(String),
+    /// A value of any other sort, such as a datatype value or an array,
+    /// in Z3's syntax.
+    Other(String),
+}
+
+/// Interpretation of a function in a [`Model`](struct.Model.html): a
+/// finite list of [`FuncEntry`](struct.FuncEntry.html)s mapping
+/// arguments to values, and an "else" value for all other arguments.
+///
+/// # See also:
+///
+/// - [`Model::get_func_interp()`](struct.Model.html#method.get_func_interp)
+pub struct FuncInterp<'ctx> {
+    ctx: &'ctx Context,
+    z3_func_interp: Z3_func_interp,
+}
+
+/// A single entry `f(args) = value` of a
+/// [`FuncInterp`](struct.FuncInterp.html).
+pub struct FuncEntry<'ctx> {
+    ctx: &'ctx Context,
+    z3_func_entry: Z3_func_entry,
+}
+
+/// Iterator over the cubes of a [`Solver`](struct.Solver.html), as
+/// returned by [`Solver::cube()`](struct.Solver.html#method.cube).
+pub struct Cubes<'a, 'ctx: 'a> {
+    solver: &'a Solver<'ctx>,
+    vars: AstVector<'ctx>,
+    done: bool,
+}
+
 /// Context for solving optimization queries.
 pub struct Optimize<'ctx> {
     ctx: &'ctx Context,
     z3_opt: Z3_optimize,
 }
+
+/// Iterator over the Pareto-optimal models of an
+/// [`Optimize`](struct.Optimize.html) context, as returned by
+/// [`Optimize::pareto_iter()`](struct.Optimize.html#method.pareto_iter).
+pub struct ParetoIter<'a, 'ctx: 'a> {
+    opt: &'a Optimize<'ctx>,
+    done: bool,
+}
+
+/// Handle to an objective of an [`Optimize`](struct.Optimize.html)
+/// context, as returned by [`Optimize::maximize()`] and
+/// [`Optimize::minimize()`].
+///
+/// An objective added after a [`push()`] is removed by the matching
+/// [`pop()`], after which the handle is no longer valid: its bounds can
+/// no longer be queried, or belong to an objective added later in its
+/// place.
+///
+/// [`push()`]: struct.Optimize.html#method.push
+/// [`pop()`]: struct.Optimize.html#method.pop
+/// [`Optimize::maximize()`]: struct.Optimize.html#method.maximize
+/// [`Optimize::minimize()`]: struct.Optimize.html#method.minimize
+pub struct OptimizeObjective<'ctx> {
+    ctx: &'ctx Context,
+    z3_opt: Z3_optimize,
+    idx: u32,
+    term: Ast<'ctx>,
+}
+
+/// Weighted MaxSAT problem: hard constraints that must hold, and
+/// weighted soft constraints of which as many as possible should hold.
+///
+/// It is solved with Z3's core-guided MaxRes engine, on top of an
+/// [`Optimize`](struct.Optimize.html) context.
+///
+/// # Example
+///
+/// ```
+/// use z3::*;
+///
+/// let cfg = Config::new();
+/// let ctx = Context::new(&cfg);
+/// let a = ctx.named_bool_const("a");
+/// let b = ctx.named_bool_const("b");
+///
+/// let mut maxsat = MaxSat::new(&ctx);
+/// maxsat.assert(&a.xor(&b));
+/// maxsat.add_soft(&a, 2);
+/// maxsat.add_soft(&b, 3);
+///
+/// let solution = maxsat.solve().unwrap();
+/// assert_eq!(solution.cost, 2);
+/// assert_eq!(solution.satisfied, vec![1]);
+/// ```
+pub struct MaxSat<'ctx> {
+    opt: Optimize<'ctx>,
+    soft: Vec<(SoftConstraint<'ctx>, i64)>,
+}
+
+/// Optimal solution of a [`MaxSat`](struct.MaxSat.html) problem.
+pub struct MaxSatSolution<'ctx> {
+    /// Total weight of the soft constraints that are violated.
+    pub cost: i64,
+    /// Indices of the soft constraints that hold, in the order they were
+    /// added.
+    pub satisfied: Vec<usize>,
+    /// A model of the hard constraints that achieves the cost.
+    pub model: Model<'ctx>,
+}
+
+/// Runs the assertions of a solver under several strategies at once,
+/// and returns the first definitive answer.
+///
+/// Each strategy builds a solver, such as one using a particular tactic,
+/// logic or random seed, in a context of its own. The assertions are
+/// translated into each of these contexts and checked on separate
+/// threads. Once one of them is satisfiable or unsatisfiable, the others
+/// are interrupted.
+///
+/// # Example
+///
+/// ```
+/// use z3::*;
+///
+/// let cfg = Config::new();
+/// let ctx = Context::new(&cfg);
+/// let x = ctx.named_bitvector_const("x", 16);
+/// let bv16 = ctx.bitvector_sort(16);
+/// let solver = Solver::new(&ctx);
+/// solver.assert(&x.bvmul(&x)._eq(&bv16.from_u64(1369)));
+///
+/// let portfolio = Portfolio::new()
+///     .logic(Logic::QF_BV)
+///     .tactic("qfbv")
+///     .strategy(|ctx| {
+///         let solver = Solver::new(ctx);
+///         let mut params = Params::new(ctx);
+///         params.set_u32("random_seed", 7);
+///         solver.set_params(&params);
+///         solver
+///     });
+///
+/// let result = portfolio.check(&cfg, &solver);
+/// assert_eq!(result.result, SatResult::Sat);
+/// assert!(result.winner.is_some());
+/// let model = result.model.unwrap();
+/// let xv = model.eval(&x, true).unwrap().as_u64().unwrap();
+/// assert_eq!(xv.wrapping_mul(xv) % 65536, 1369);
+/// ```
+pub struct Portfolio<'a> {
+    strategies: Vec<PortfolioStrategy<'a>>,
+}
+
+/// Builds the solver of one portfolio strategy in a given context.
+type PortfolioStrategy<'a> = Box<dyn for<'c> Fn(&'c Context) -> Solver<'c> + Sync + 'a>;
+
+/// Outcome of [`Portfolio::check()`](struct.Portfolio.html#method.check).
+pub struct PortfolioResult<'ctx> {
+    /// The first definitive answer, or `SatResult::Unknown` if no
+    /// strategy found one.
+    pub result: SatResult,
+    /// Index of the strategy that found the answer.
+    pub winner: Option<usize>,
+    /// If the answer is satisfiable, the model found by the winning
+    /// strategy, translated back into the solver's context.
+    pub model: Option<Model<'ctx>>,
+}
+
+/// A term built by the [`z3!`](macro.z3.html) macro. Integer literals,
+/// and operations on nothing but literals, are kept apart until they
+/// meet another term, so that they can take its sort.
+#[doc(hidden)]
+pub enum MacroTerm<'ctx> {
+    Ast(Ast<'ctx>),
+    Num(&'ctx Context, i64),
+    Op(
+        fn(&Ast<'ctx>, &Ast<'ctx>) -> Ast<'ctx>,
+        Box<MacroTerm<'ctx>>,
+        Box<MacroTerm<'ctx>>,
+    ),
+    Neg(Box<MacroTerm<'ctx>>),
+}
+
+/// An [`Ast`](struct.Ast.html) that keeps its context alive through an
+/// `Arc<Context>` instead of borrowing it, so it has no lifetime
+/// parameter and can be stored in long-lived structs.
+///
+/// Use [`get()`](#method.get) to borrow it as an ordinary `Ast`.
+///
+/// Owned handles share their context, and a Z3 context must not be used
+/// from two threads at once, so neither `Context` nor the owned handles
+/// are `Send` or `Sync`, even though they hold an `Arc`. To work on a
+/// problem in another thread, copy it into a context of its own with a
+/// [`DetachedSolver`](struct.DetachedSolver.html).
+///
+/// ```compile_fail
+/// fn assert_send<T: Send>() {}
+/// assert_send::<z3::OwnedSolver>();
+/// ```
+///
+/// # Examples
+///
+/// ```
+/// # use z3::{Config, Context, OwnedAst, SatResult, Solver};
+/// struct Problem {
+///     x: OwnedAst,
+/// }
+///
+/// let ctx = Context::new_shared(&Config::new());
+/// let problem = Problem {
+///     x: OwnedAst::new(&ctx, &ctx.named_int_const("x")),
+/// };
+///
+/// let solver = Solver::new(&ctx);
+/// solver.assert(&problem.x.get().gt(&ctx.from_i64(3)));
+/// assert_eq!(solver.check(), SatResult::Sat);
+/// ```
+///
+/// # See also:
+///
+/// - [`OwnedSolver`](struct.OwnedSolver.html)
+/// - [`OwnedModel`](struct.OwnedModel.html)
+pub struct OwnedAst {
+    // Declared before `ctx` so it is dropped while the context is alive.
+    ast: Ast<'static>,
+    ctx: Arc<Context>,
+}
+
+/// A [`Solver`](struct.Solver.html) that keeps its context alive through
+/// an `Arc<Context>`. See [`OwnedAst`](struct.OwnedAst.html).
+pub struct OwnedSolver {
+    solver: Solver<'static>,
+    ctx: Arc<Context>,
+}
+
+/// A [`Model`](struct.Model.html) that keeps its context alive through
+/// an `Arc<Context>`. See [`OwnedAst`](struct.OwnedAst.html).
+pub struct OwnedModel {
+    model: Model<'static>,
+    ctx: Arc<Context>,
+}
+
+/// A solver, and terms to go with it, copied into a new context that
+/// nothing else refers to, so that unlike the owned handles it can be
+/// sent to another thread. There it is turned into owned handles with
+/// [`attach()`](#method.attach).
+///
+/// # Example
+///
+/// ```
+/// # use z3::{Config, Context, DetachedSolver, SatResult, Solver};
+/// use std::thread;
+///
+/// let cfg = Config::new();
+/// let ctx = Context::new(&cfg);
+/// let x = ctx.named_int_const("x");
+/// let solver = Solver::new(&ctx);
+/// solver.assert(&x.gt(&ctx.from_i64(41)));
+///
+/// let detached = DetachedSolver::new(&solver, &[&x], &cfg);
+/// let worker = thread::spawn(move || {
+///     let (solver, terms) = detached.attach();
+///     let solver_ref = solver.get();
+///     solver_ref.assert(&terms[0].get().lt(&solver.context().from_i64(43)));
+///     assert_eq!(solver_ref.check(), SatResult::Sat);
+///     let model = solver_ref.get_model().unwrap();
+///     let value = model.eval(terms[0].get(), true).unwrap().as_i64();
+///     value
+/// });
+/// assert_eq!(worker.join().unwrap(), Some(42));
+/// ```
+pub struct DetachedSolver {
+    solver: OwnedSolver,
+    terms: Vec<OwnedAst>,
+}
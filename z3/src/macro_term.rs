@@ -0,0 +1,244 @@
+use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Not, Rem, Shl, Shr, Sub};
+use z3_sys::*;
+use Ast;
+use Context;
+use FuncDecl;
+use MacroTerm;
+use Sort;
+
+/// Context argument of the [`z3!`](macro.z3.html) macro, which can be a
+/// `Context` or a reference to one.
+#[doc(hidden)]
+pub trait MacroContext<'ctx> {
+    fn macro_context(self) -> &'ctx Context;
+}
+
+impl<'ctx> MacroContext<'ctx> for &'ctx Context {
+    fn macro_context(self) -> &'ctx Context {
+        self
+    }
+}
+
+impl<'ctx> MacroContext<'ctx> for &&'ctx Context {
+    fn macro_context(self) -> &'ctx Context {
+        self
+    }
+}
+
+#[doc(hidden)]
+impl<'ctx> MacroTerm<'ctx> {
+    /// Convert the term, giving literals that never met another term
+    /// the sort `Int`.
+    pub fn into_ast(self) -> Ast<'ctx> {
+        match self {
+            MacroTerm::Ast(ast) => ast,
+            _ => {
+                let int = Sort::int(self.ctx());
+                self.to_sort(&int)
+            }
+        }
+    }
+
+    fn ctx(&self) -> &'ctx Context {
+        match *self {
+            MacroTerm::Ast(ref ast) => ast.ctx,
+            MacroTerm::Num(ctx, _) => ctx,
+            MacroTerm::Op(_, ref a, _) | MacroTerm::Neg(ref a) => a.ctx(),
+        }
+    }
+
+    fn is_sorted(&self) -> bool {
+        matches!(*self, MacroTerm::Ast(_))
+    }
+
+    fn to_sort(&self, sort: &Sort<'ctx>) -> Ast<'ctx> {
+        match *self {
+            MacroTerm::Ast(ref ast) => ast.clone(),
+            MacroTerm::Num(_, n) => sort.from_i64(n),
+            MacroTerm::Op(f, ref a, ref b) => f(&a.to_sort(sort), &b.to_sort(sort)),
+            MacroTerm::Neg(ref a) => -a.to_sort(sort),
+        }
+    }
+
+    /// Convert both operands of a binary operation, giving literals the
+    /// sort of the other operand.
+    fn unify(&self, other: &MacroTerm<'ctx>) -> (Ast<'ctx>, Ast<'ctx>) {
+        match (self, other) {
+            (MacroTerm::Ast(a), _) => (a.clone(), other.to_sort(&a.get_sort())),
+            (_, MacroTerm::Ast(b)) => (self.to_sort(&b.get_sort()), b.clone()),
+            _ => (self.clone().into_ast(), other.clone().into_ast()),
+        }
+    }
+
+    /// Apply a binary operation, computing it right away if both
+    /// operands are literals and `fold` does not overflow. Otherwise, if
+    /// neither operand has a sort yet, the operation waits for one too.
+    fn op(
+        &self,
+        other: &MacroTerm<'ctx>,
+        fold: fn(i64, i64) -> Option<i64>,
+        f: fn(&Ast<'ctx>, &Ast<'ctx>) -> Ast<'ctx>,
+    ) -> MacroTerm<'ctx> {
+        if let (&MacroTerm::Num(ctx, a), &MacroTerm::Num(_, b)) = (self, other) {
+            if let Some(n) = fold(a, b) {
+                return MacroTerm::Num(ctx, n);
+            }
+        }
+        if !self.is_sorted() && !other.is_sorted() {
+            return MacroTerm::Op(f, Box::new(self.clone()), Box::new(other.clone()));
+        }
+        let (a, b) = self.unify(other);
+        MacroTerm::Ast(f(&a, &b))
+    }
+
+    fn compare<F, G>(&self, other: &MacroTerm<'ctx>, f: F, bv: G) -> MacroTerm<'ctx>
+    where
+        F: FnOnce(&Ast<'ctx>, &Ast<'ctx>) -> Ast<'ctx>,
+        G: FnOnce(&Ast<'ctx>, &Ast<'ctx>) -> Ast<'ctx>,
+    {
+        let (a, b) = self.unify(other);
+        MacroTerm::Ast(if a.sort_kind() == SortKind::BV {
+            bv(&a, &b)
+        } else {
+            f(&a, &b)
+        })
+    }
+
+    pub fn _eq(&self, other: &MacroTerm<'ctx>) -> MacroTerm<'ctx> {
+        self.compare(other, Ast::_eq, Ast::_eq)
+    }
+
+    pub fn ne(&self, other: &MacroTerm<'ctx>) -> MacroTerm<'ctx> {
+        let (a, b) = self.unify(other);
+        MacroTerm::Ast(a._eq(&b).not())
+    }
+
+    pub fn lt(&self, other: &MacroTerm<'ctx>) -> MacroTerm<'ctx> {
+        self.compare(other, Ast::lt, Ast::bvult)
+    }
+
+    pub fn le(&self, other: &MacroTerm<'ctx>) -> MacroTerm<'ctx> {
+        self.compare(other, Ast::le, Ast::bvule)
+    }
+
+    pub fn gt(&self, other: &MacroTerm<'ctx>) -> MacroTerm<'ctx> {
+        self.compare(other, Ast::gt, Ast::bvugt)
+    }
+
+    pub fn ge(&self, other: &MacroTerm<'ctx>) -> MacroTerm<'ctx> {
+        self.compare(other, Ast::ge, Ast::bvuge)
+    }
+
+    pub fn implies(self, other: MacroTerm<'ctx>) -> MacroTerm<'ctx> {
+        MacroTerm::Ast(self.into_ast().implies(&other.into_ast()))
+    }
+
+    pub fn and(terms: Vec<MacroTerm<'ctx>>) -> MacroTerm<'ctx> {
+        let asts: Vec<Ast> = terms.into_iter().map(MacroTerm::into_ast).collect();
+        let rest: Vec<&Ast> = asts[1..].iter().collect();
+        MacroTerm::Ast(asts[0].and(&rest))
+    }
+
+    pub fn or(terms: Vec<MacroTerm<'ctx>>) -> MacroTerm<'ctx> {
+        let asts: Vec<Ast> = terms.into_iter().map(MacroTerm::into_ast).collect();
+        let rest: Vec<&Ast> = asts[1..].iter().collect();
+        MacroTerm::Ast(asts[0].or(&rest))
+    }
+
+    pub fn apply(f: &FuncDecl<'ctx>, args: Vec<MacroTerm<'ctx>>) -> MacroTerm<'ctx> {
+        let args: Vec<Ast> = args
+            .iter()
+            .enumerate()
+            .map(|(i, arg)| arg.to_sort(&f.domain(i as u32)))
+            .collect();
+        let args: Vec<&Ast> = args.iter().collect();
+        MacroTerm::Ast(f.apply(&args))
+    }
+}
+
+impl<'ctx> Clone for MacroTerm<'ctx> {
+    fn clone(&self) -> MacroTerm<'ctx> {
+        match *self {
+            MacroTerm::Ast(ref ast) => MacroTerm::Ast(ast.clone()),
+            MacroTerm::Num(ctx, n) => MacroTerm::Num(ctx, n),
+            MacroTerm::Op(f, ref a, ref b) => MacroTerm::Op(f, a.clone(), b.clone()),
+            MacroTerm::Neg(ref a) => MacroTerm::Neg(a.clone()),
+        }
+    }
+}
+
+fn no_fold(a: i64, b: i64) -> Option<i64> {
+    None
+}
+
+// Operations on the converted operands, with the semantics of the
+// operators on `Ast` and unsigned division for bit-vectors.
+
+fn add<'ctx>(a: &Ast<'ctx>, b: &Ast<'ctx>) -> Ast<'ctx> {
+    a + b
+}
+
+fn sub<'ctx>(a: &Ast<'ctx>, b: &Ast<'ctx>) -> Ast<'ctx> {
+    a - b
+}
+
+fn mul<'ctx>(a: &Ast<'ctx>, b: &Ast<'ctx>) -> Ast<'ctx> {
+    a * b
+}
+
+fn div<'ctx>(a: &Ast<'ctx>, b: &Ast<'ctx>) -> Ast<'ctx> {
+    if a.sort_kind() == SortKind::BV {
+        a.bvudiv(b)
+    } else {
+        a.div(b)
+    }
+}
+
+fn modulo<'ctx>(a: &Ast<'ctx>, b: &Ast<'ctx>) -> Ast<'ctx> {
+    if a.sort_kind() == SortKind::BV {
+        a.bvurem(b)
+    } else {
+        a.modulo(b)
+    }
+}
+
+fn bitand<'ctx>(a: &Ast<'ctx>, b: &Ast<'ctx>) -> Ast<'ctx> {
+    a & b
+}
+
+fn bitor<'ctx>(a: &Ast<'ctx>, b: &Ast<'ctx>) -> Ast<'ctx> {
+    a | b
+}
+
+fn bitxor<'ctx>(a: &Ast<'ctx>, b: &Ast<'ctx>) -> Ast<'ctx> {
+    a ^ b
+}
+
+fn shl<'ctx>(a: &Ast<'ctx>, b: &Ast<'ctx>) -> Ast<'ctx> {
+    a << b
+}
+
+fn shr<'ctx>(a: &Ast<'ctx>, b: &Ast<'ctx>) -> Ast<'ctx> {
+    a >> b
+}
+
+impl_binary_op!(MacroTerm, Add, add, |a, b| a.op(b, i64::checked_add, add));
+impl_binary_op!(MacroTerm, Sub, sub, |a, b| a.op(b, i64::checked_sub, sub));
+impl_binary_op!(MacroTerm, Mul, mul, |a, b| a.op(b, i64::checked_mul, mul));
+impl_binary_op!(MacroTerm, Div, div, |a, b| a.op(b, no_fold, div));
+impl_binary_op!(MacroTerm, Rem, rem, |a, b| a.op(b, no_fold, modulo));
+impl_binary_op!(MacroTerm, BitAnd, bitand, |a, b| a.op(b, no_fold, bitand));
+impl_binary_op!(MacroTerm, BitOr, bitor, |a, b| a.op(b, no_fold, bitor));
+impl_binary_op!(MacroTerm, BitXor, bitxor, |a, b| a.op(b, no_fold, bitxor));
+impl_binary_op!(MacroTerm, Shl, shl, |a, b| a.op(b, no_fold, shl));
+impl_binary_op!(MacroTerm, Shr, shr, |a, b| a.op(b, no_fold, shr));
+
+impl_unary_op!(MacroTerm, Neg, neg, |a| match *a {
+    MacroTerm::Num(ctx, n) if n != i64::MIN => MacroTerm::Num(ctx, -n),
+    MacroTerm::Ast(ref ast) => MacroTerm::Ast(-ast.clone()),
+    _ => MacroTerm::Neg(Box::new(a.clone())),
+});
+impl_unary_op!(MacroTerm, Not, not, |a| {
+    let ast = a.clone().into_ast();
+    MacroTerm::Ast(!ast)
+});
@@ -0,0 +1,296 @@
+// Implement an operator trait for all four combinations of owned and
+// borrowed operands, in terms of the borrowed/borrowed case.
+macro_rules! impl_binary_op {
+    ( $ty:ident, $trait:ident, $f:ident, | $a:ident, $b:ident | $body:expr ) => {
+        impl<'a, 'b, 'ctx> $trait<&'b $ty<'ctx>> for &'a $ty<'ctx> {
+            type Output = $ty<'ctx>;
+
+            fn $f(self, other: &'b $ty<'ctx>) -> $ty<'ctx> {
+                let $a = self;
+                let $b = other;
+                $body
+            }
+        }
+
+        impl<'a, 'ctx> $trait<$ty<'ctx>> for &'a $ty<'ctx> {
+            type Output = $ty<'ctx>;
+
+            fn $f(self, other: $ty<'ctx>) -> $ty<'ctx> {
+                $trait::$f(self, &other)
+            }
+        }
+
+        impl<'b, 'ctx> $trait<&'b $ty<'ctx>> for $ty<'ctx> {
+            type Output = $ty<'ctx>;
+
+            fn $f(self, other: &'b $ty<'ctx>) -> $ty<'ctx> {
+                $trait::$f(&self, other)
+            }
+        }
+
+        impl<'ctx> $trait<$ty<'ctx>> for $ty<'ctx> {
+            type Output = $ty<'ctx>;
+
+            fn $f(self, other: $ty<'ctx>) -> $ty<'ctx> {
+                $trait::$f(&self, &other)
+            }
+        }
+    };
+}
+
+macro_rules! impl_unary_op {
+    ( $ty:ident, $trait:ident, $f:ident, | $a:ident | $body:expr ) => {
+        impl<'a, 'ctx> $trait for &'a $ty<'ctx> {
+            type Output = $ty<'ctx>;
+
+            fn $f(self) -> $ty<'ctx> {
+                let $a = self;
+                $body
+            }
+        }
+
+        impl<'ctx> $trait for $ty<'ctx> {
+            type Output = $ty<'ctx>;
+
+            fn $f(self) -> $ty<'ctx> {
+                $trait::$f(&self)
+            }
+        }
+    };
+}
+
+/// Build a formula from an expression syntax similar to Rust's, instead
+/// of nesting calls to [`Ast`](struct.Ast.html) methods.
+///
+/// The first argument is the context, and the second the expression,
+/// which can contain:
+///
+/// - Rust variables holding `Ast`s, and `{ ... }` blocks evaluating to
+///   one, e.g. `{ x.bvashr(&y) }` for an operation without syntax of
+///   its own;
+/// - integer literals, which take the sort of the term they are combined
+///   with, and `true` and `false`;
+/// - applications `f(a, b)` of `FuncDecl`s held in Rust variables;
+/// - the arithmetic and bitwise operators of `Ast`, as well as `/` and
+///   `%` for division and modulo;
+/// - the comparisons `==`, `!=`, `<`, `<=`, `>` and `>=`;
+/// - `&&`, `||`, and `=>` for implication;
+/// - quantifiers `forall x: Int, y: Int . body` and `exists`, with bound
+///   variables of sort `Bool`, `Int`, `Real`, `BitVec(n)` or a
+///   `{ ... }` block evaluating to a `Sort`. The body extends as far as
+///   possible.
+///
+/// Operators bind as in Rust, with `=>` binding least and grouping to the
+/// right. Comparisons of bit-vectors, `/`, `%` and `>>` are unsigned.
+///
+/// The macro reads the expression one token at a time, so expressions
+/// of more than about a hundred tokens outside of parentheses need a
+/// higher `#![recursion_limit]`.
+///
+/// # Example
+///
+/// ```
+/// use z3::*;
+///
+/// let cfg = Config::new();
+/// let ctx = Context::new(&cfg);
+/// let x = ctx.named_int_const("x");
+/// let f = ctx.func_decl(&ctx.str_sym("f"), &[&ctx.int_sort()], &ctx.int_sort());
+///
+/// let solver = Solver::new(&ctx);
+/// solver.assert(&z3!(ctx, forall y: Int . y + 1 > y));
+/// solver.assert(&z3!(ctx, f(x) == 2 * x - 1 && (x > 3 || x < -3)));
+/// assert_eq!(solver.check(), SatResult::Sat);
+///
+/// solver.assert(&z3!(ctx, exists y: Int . f(y) % 2 == 0));
+/// assert_eq!(solver.check(), SatResult::Sat);
+/// ```
+#[macro_export]
+macro_rules! z3 {
+    // Scan the expression from left to right. The state is made of the
+    // operands of `||` so far, the operands of `&&` within the current
+    // disjunct, the left-hand side and method of a comparison, and the
+    // arithmetic expression being read, as Rust code over `MacroTerm`s.
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt $cur:tt => $($rest:tt)+) => {
+        $crate::z3!(@end $ctx $or $and $lhs $op $cur)
+            .implies($crate::z3!(@scan $ctx [] [] [] [] [] $($rest)+))
+    };
+    (@scan $ctx:ident [$($or:tt)*] $and:tt $lhs:tt $op:tt $cur:tt || $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx
+            [$($or)* ($crate::z3!(@and $ctx $and $lhs $op $cur))] [] [] [] []
+            $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt [$($and:tt)*] $lhs:tt $op:tt $cur:tt && $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx
+            $or [$($and)* ($crate::z3!(@cmp $ctx $lhs $op $cur))] [] [] []
+            $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt [] [] $cur:tt == $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $cur [_eq] [] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt [] [] $cur:tt != $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $cur [ne] [] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt [] [] $cur:tt < $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $cur [lt] [] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt [] [] $cur:tt <= $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $cur [le] [] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt [] [] $cur:tt > $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $cur [gt] [] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt [] [] $cur:tt >= $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $cur [ge] [] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] forall $($rest:tt)+) => {
+        $crate::z3!(@end $ctx $or $and $lhs $op
+            [$($cur)* ($crate::z3!(@quant $ctx forall_const [] $($rest)+))])
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] exists $($rest:tt)+) => {
+        $crate::z3!(@end $ctx $or $and $lhs $op
+            [$($cur)* ($crate::z3!(@quant $ctx exists_const [] $($rest)+))])
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] $f:ident ($($args:tt)*) $($rest:tt)*) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op
+            [$($cur)* ($crate::z3!(@apply $ctx $f [] [] $($args)*))] $($rest)*)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] ($($e:tt)+) $($rest:tt)*) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op
+            [$($cur)* ($crate::z3!(@scan $ctx [] [] [] [] [] $($e)+))] $($rest)*)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] { $e:expr } $($rest:tt)*) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op
+            [$($cur)* ($crate::MacroTerm::Ast(($e).clone()))] $($rest)*)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] true $($rest:tt)*) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op
+            [$($cur)* ($crate::MacroTerm::Ast($ctx.from_bool(true)))] $($rest)*)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] false $($rest:tt)*) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op
+            [$($cur)* ($crate::MacroTerm::Ast($ctx.from_bool(false)))] $($rest)*)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] + $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op [$($cur)* +] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] - $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op [$($cur)* -] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] * $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op [$($cur)* *] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] / $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op [$($cur)* /] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] % $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op [$($cur)* %] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] ! $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op [$($cur)* !] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] & $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op [$($cur)* &] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] | $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op [$($cur)* |] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] ^ $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op [$($cur)* ^] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] << $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op [$($cur)* <<] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] >> $($rest:tt)+) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op [$($cur)* >>] $($rest)+)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] $n:literal $($rest:tt)*) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op
+            [$($cur)* ($crate::MacroTerm::Num($ctx, $n))] $($rest)*)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt [$($cur:tt)*] $x:ident $($rest:tt)*) => {
+        $crate::z3!(@scan $ctx $or $and $lhs $op
+            [$($cur)* ($crate::MacroTerm::Ast($x.clone()))] $($rest)*)
+    };
+    (@scan $ctx:ident $or:tt $and:tt $lhs:tt $op:tt $cur:tt) => {
+        $crate::z3!(@end $ctx $or $and $lhs $op $cur)
+    };
+
+    // Combine the state at the end of the expression.
+    (@end $ctx:ident [] $and:tt $lhs:tt $op:tt $cur:tt) => {
+        $crate::z3!(@and $ctx $and $lhs $op $cur)
+    };
+    (@end $ctx:ident [$($or:tt)+] $and:tt $lhs:tt $op:tt $cur:tt) => {
+        $crate::MacroTerm::or(vec![$($or,)+ $crate::z3!(@and $ctx $and $lhs $op $cur)])
+    };
+    (@and $ctx:ident [] $lhs:tt $op:tt $cur:tt) => {
+        $crate::z3!(@cmp $ctx $lhs $op $cur)
+    };
+    (@and $ctx:ident [$($and:tt)+] $lhs:tt $op:tt $cur:tt) => {
+        $crate::MacroTerm::and(vec![$($and,)+ $crate::z3!(@cmp $ctx $lhs $op $cur)])
+    };
+    (@cmp $ctx:ident [] [] [$($cur:tt)+]) => {
+        ($($cur)+)
+    };
+    (@cmp $ctx:ident [$($lhs:tt)+] [$op:ident] [$($cur:tt)+]) => {
+        ($($lhs)+).$op(&($($cur)+))
+    };
+
+    // Arguments of a function application.
+    (@apply $ctx:ident $f:ident [$(($($arg:tt)+))*] []) => {
+        $crate::MacroTerm::apply(&$f, vec![$($crate::z3!(@scan $ctx [] [] [] [] [] $($arg)+)),*])
+    };
+    (@apply $ctx:ident $f:ident [$($args:tt)*] [$($cur:tt)*] , $($rest:tt)*) => {
+        $crate::z3!(@apply $ctx $f [$($args)* ($($cur)*)] [] $($rest)*)
+    };
+    (@apply $ctx:ident $f:ident $args:tt [$($cur:tt)*] $t:tt $($rest:tt)*) => {
+        $crate::z3!(@apply $ctx $f $args [$($cur)* $t] $($rest)*)
+    };
+    (@apply $ctx:ident $f:ident [$($args:tt)*] [$($cur:tt)+]) => {
+        $crate::z3!(@apply $ctx $f [$($args)* ($($cur)+)] [])
+    };
+
+    // Bound variables of a quantifier.
+    (@quant $ctx:ident $q:ident [$($b:tt)*] $x:ident : BitVec($n:expr) , $($rest:tt)+) => {
+        $crate::z3!(@quant $ctx $q [$($b)* $x (BitVec($n))] $($rest)+)
+    };
+    (@quant $ctx:ident $q:ident [$($b:tt)*] $x:ident : $s:tt , $($rest:tt)+) => {
+        $crate::z3!(@quant $ctx $q [$($b)* $x ($s)] $($rest)+)
+    };
+    (@quant $ctx:ident $q:ident [$($b:tt)*] $x:ident : BitVec($n:expr) . $($body:tt)+) => {
+        $crate::z3!(@quant_body $ctx $q [$($b)* $x (BitVec($n))] $($body)+)
+    };
+    (@quant $ctx:ident $q:ident [$($b:tt)*] $x:ident : $s:tt . $($body:tt)+) => {
+        $crate::z3!(@quant_body $ctx $q [$($b)* $x ($s)] $($body)+)
+    };
+    (@quant_body $ctx:ident $q:ident [$($x:ident $s:tt)+] $($body:tt)+) => {{
+        $(
+            let $x = $crate::Ast::new_const(
+                &$crate::Symbol::from_string($ctx, stringify!($x)),
+                &$crate::z3!(@sort $ctx $s),
+            );
+        )+
+        let body = $crate::z3!(@scan $ctx [] [] [] [] [] $($body)+).into_ast();
+        $crate::MacroTerm::Ast($crate::Ast::$q($ctx, &[$(&$x),+], &[], &body))
+    }};
+    (@sort $ctx:ident (Bool)) => {
+        $crate::Sort::bool($ctx)
+    };
+    (@sort $ctx:ident (Int)) => {
+        $crate::Sort::int($ctx)
+    };
+    (@sort $ctx:ident (Real)) => {
+        $crate::Sort::real($ctx)
+    };
+    (@sort $ctx:ident (BitVec($n:expr))) => {
+        $crate::Sort::bitvector($ctx, $n)
+    };
+    (@sort $ctx:ident ({ $s:expr })) => {
+        $s
+    };
+
+    ($ctx:expr, $($t:tt)+) => {{
+        let ctx = $crate::MacroContext::macro_context(&$ctx);
+        $crate::z3!(@scan ctx [] [] [] [] [] $($t)+).into_ast()
+    }};
+}
@@ -0,0 +1,70 @@
+use Ast;
+use Context;
+use MaxSat;
+use MaxSatSolution;
+use Optimize;
+use Params;
+use SatResult;
+
+impl<'ctx> MaxSat<'ctx> {
+    /// Create an empty MaxSAT problem.
+    pub fn new(ctx: &'ctx Context) -> MaxSat<'ctx> {
+        let opt = Optimize::new(ctx);
+        let mut params = Params::new(ctx);
+        params.set_symbol("maxsat_engine", "maxres");
+        opt.set_params(&params);
+        MaxSat { opt, soft: vec![] }
+    }
+
+    /// Add a hard constraint, which every solution satisfies.
+    pub fn assert(&self, ast: &Ast<'ctx>) {
+        self.opt.assert(ast);
+    }
+
+    /// Add a soft constraint that costs `weight` if it is violated, and
+    /// return its index in [`MaxSatSolution::satisfied`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weight` is not positive.
+    ///
+    /// [`MaxSatSolution::satisfied`]: struct.MaxSatSolution.html#structfield.satisfied
+    pub fn add_soft(&mut self, ast: &Ast<'ctx>, weight: i64) -> usize {
+        assert!(weight > 0, "soft constraint weights must be positive");
+        let soft = self.opt.add_soft(ast, weight, None);
+        self.soft.push((soft, weight));
+        self.soft.len() - 1
+    }
+
+    /// Find an assignment of minimal cost.
+    ///
+    /// Returns `None` if the hard constraints are unsatisfiable, or if
+    /// the search was inconclusive, e.g. because of a timeout set
+    /// through [`MaxSat::optimize()`](#method.optimize).
+    pub fn solve(&self) -> Option<MaxSatSolution<'ctx>> {
+        if self.opt.check() != SatResult::Sat {
+            return None;
+        }
+        let model = self.opt.get_model().ok()?;
+        let mut satisfied = vec![];
+        let mut cost = 0;
+        for (i, &(ref soft, weight)) in self.soft.iter().enumerate() {
+            if soft.is_satisfied(&model) == Some(true) {
+                satisfied.push(i);
+            } else {
+                cost += weight;
+            }
+        }
+        Some(MaxSatSolution {
+            cost,
+            satisfied,
+            model,
+        })
+    }
+
+    /// Return the underlying optimization context, e.g. to set a
+    /// timeout or retrieve the reason for an inconclusive result.
+    pub fn optimize(&self) -> &Optimize<'ctx> {
+        &self.opt
+    }
+}
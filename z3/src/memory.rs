@@ -0,0 +1,85 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use z3_sys::*;
+use Z3_MUTEX;
+
+/// Number of configurations and contexts alive in the process. Every
+/// other Z3 object belongs to a context, so when this is zero nothing
+/// refers to memory allocated by Z3.
+///
+/// It is incremented with `Z3_MUTEX` held, so that no object can be
+/// created while the memory is being reset, and decremented once the
+/// object has been deleted.
+static LIVE_OBJECTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Record the creation of a configuration or context. Must be called
+/// with `Z3_MUTEX` held.
+pub(crate) fn object_created() {
+    LIVE_OBJECTS.fetch_add(1, Ordering::SeqCst);
+}
+
+/// Record that a configuration or context has been deleted.
+pub(crate) fn object_deleted() {
+    LIVE_OBJECTS.fetch_sub(1, Ordering::SeqCst);
+}
+
+/// Return an estimate of the number of bytes currently allocated by Z3
+/// in this process, across all contexts.
+///
+/// # See also:
+///
+/// - [`reset_memory()`](fn.reset_memory.html)
+pub fn estimated_alloc_size() -> u64 {
+    unsafe { Z3_get_estimated_alloc_size() }
+}
+
+/// Release all memory allocated by Z3, such as caches shared by all
+/// contexts, so that a long-running process can start afresh. Global
+/// parameters are reset to their defaults as well.
+///
+/// This is only done if no [`Config`](struct.Config.html) or
+/// [`Context`](struct.Context.html) is alive, as the memory of every Z3
+/// object would be freed with it. Returns whether the memory was reset.
+///
+/// # Example
+///
+/// ```
+/// # use z3::*;
+/// {
+///     let cfg = Config::new();
+///     let ctx = Context::new(&cfg);
+///     let solver = Solver::new(&ctx);
+///     solver.assert(&ctx.named_int_const("x").gt(&ctx.from_i64(0)));
+///     assert_eq!(solver.check(), SatResult::Sat);
+///     assert!(!z3::reset_memory());
+/// }
+/// assert!(z3::reset_memory());
+/// ```
+///
+/// # See also:
+///
+/// - [`estimated_alloc_size()`](fn.estimated_alloc_size.html)
+/// - [`finalize_memory()`](fn.finalize_memory.html)
+pub fn reset_memory() -> bool {
+    let guard = Z3_MUTEX.lock().unwrap();
+    if LIVE_OBJECTS.load(Ordering::SeqCst) != 0 {
+        return false;
+    }
+    unsafe { Z3_reset_memory() };
+    true
+}
+
+/// Like [`reset_memory()`](fn.reset_memory.html), but also release the
+/// memory Z3 keeps for its own bookkeeping, e.g. before checking the
+/// process for leaks. Z3 can still be used afterwards.
+///
+/// Returns whether the memory was released, which is only done if no
+/// [`Config`](struct.Config.html) or [`Context`](struct.Context.html)
+/// is alive.
+pub fn finalize_memory() -> bool {
+    let guard = Z3_MUTEX.lock().unwrap();
+    if LIVE_OBJECTS.load(Ordering::SeqCst) != 0 {
+        return false;
+    }
+    unsafe { Z3_finalize_memory() };
+    true
+}
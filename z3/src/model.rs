@@ -1,40 +1,126 @@
+use std::ffi::CStr;
+use std::fmt;
 use z3_sys::*;
 use Ast;
+use Context;
+use FuncDecl;
+use FuncInterp;
 use Model;
 use Optimize;
 use Solver;
-use Z3_MUTEX;
+use Z3Error;
 
 impl<'ctx> Model<'ctx> {
-    pub fn of_solver(slv: &Solver<'ctx>) -> Model<'ctx> {
-        Model {
-            ctx: slv.ctx,
-            z3_mdl: unsafe {
-                let guard = Z3_MUTEX.lock().unwrap();
-                let m = Z3_solver_get_model(slv.ctx.z3_ctx, slv.z3_slv);
-                Z3_model_inc_ref(slv.ctx.z3_ctx, m);
-                m
-            },
+    /// Create an empty model, to be filled in with
+    /// [`add_const_interp()`](#method.add_const_interp) and
+    /// [`add_func_interp()`](#method.add_func_interp).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let int = ctx.int_sort();
+    /// let x = ctx.named_int_const("x");
+    /// let f = ctx.func_decl(&ctx.str_sym("f"), &[&int], &int);
+    ///
+    /// let mut model = Model::new(&ctx);
+    /// model.add_const_interp(&x.decl(), &ctx.from_i64(2));
+    /// let mut interp = model.add_func_interp(&f, &ctx.from_i64(0));
+    /// interp.add_entry(&[&ctx.from_i64(2)], &ctx.from_i64(7));
+    ///
+    /// let e = f.apply(&[&x]).add(&[&f.apply(&[&ctx.from_i64(5)])]);
+    /// assert_eq!(model.eval(&e, false).unwrap().as_i64(), Some(7));
+    /// ```
+    pub fn new(ctx: &'ctx Context) -> Model<'ctx> {
+        unsafe {
+            let m = Z3_mk_model(ctx.z3_ctx);
+            Z3_model_inc_ref(ctx.z3_ctx, m);
+            Model { ctx, z3_mdl: m }
+        }
+    }
+
+    /// Retrieve the model for the last check of `slv`.
+    ///
+    /// Returns an error if no model is available, e.g. because the
+    /// solver has not been checked or the result was unsatisfiable.
+    pub fn of_solver(slv: &Solver<'ctx>) -> Result<Model<'ctx>, Z3Error> {
+        unsafe {
+            let c = slv.ctx.z3_ctx;
+            let m = slv.ctx.try_z3(|| Z3_solver_get_model(c, slv.z3_slv))?;
+            Z3_model_inc_ref(c, m);
+            Ok(Model {
+                ctx: slv.ctx,
+                z3_mdl: m,
+            })
+        }
+    }
+
+    /// Retrieve the model for the last check of `opt`.
+    ///
+    /// Returns an error if no model is available, e.g. because the
+    /// optimization context has not been checked.
+    pub fn of_optimize(opt: &Optimize<'ctx>) -> Result<Model<'ctx>, Z3Error> {
+        unsafe {
+            let c = opt.ctx.z3_ctx;
+            let m = opt.ctx.try_z3(|| Z3_optimize_get_model(c, opt.z3_opt))?;
+            Z3_model_inc_ref(c, m);
+            Ok(Model {
+                ctx: opt.ctx,
+                z3_mdl: m,
+            })
         }
     }
 
-    pub fn of_optimize(opt: &Optimize<'ctx>) -> Model<'ctx> {
+    /// Copy the model into the context `dest`.
+    pub fn translate<'dest>(&self, dest: &'dest Context) -> Model<'dest> {
         Model {
-            ctx: opt.ctx,
+            ctx: dest,
             z3_mdl: unsafe {
-                let guard = Z3_MUTEX.lock().unwrap();
-                let m = Z3_optimize_get_model(opt.ctx.z3_ctx, opt.z3_opt);
-                Z3_model_inc_ref(opt.ctx.z3_ctx, m);
+                let m = Z3_model_translate(self.ctx.z3_ctx, self.z3_mdl, dest.z3_ctx);
+                Z3_model_inc_ref(dest.z3_ctx, m);
                 m
             },
         }
     }
 
-    pub fn eval(&self, ast: &Ast<'ctx>) -> Option<Ast<'ctx>> {
+    /// Evaluate `ast` in the model, returning `None` if evaluation fails.
+    ///
+    /// If `model_completion` is `true`, constants and functions without
+    /// an interpretation are assigned a default value, which is added to
+    /// the model. Otherwise they are left uninterpreted in the result.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_int_const("x");
+    /// let y = ctx.named_int_const("y");
+    ///
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&x._eq(&ctx.from_i64(1)));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    ///
+    /// let model = solver.get_model().unwrap();
+    /// let sum = x.add(&[&y]);
+    /// assert_eq!(model.eval(&sum, false).unwrap().to_string(), "(+ 1 y)");
+    /// assert!(model.eval(&sum, true).unwrap().as_i64().is_some());
+    /// ```
+    pub fn eval(&self, ast: &Ast<'ctx>, model_completion: bool) -> Option<Ast<'ctx>> {
         let mut tmp: Z3_ast = ast.z3_ast;
-        let res = {
-            let guard = Z3_MUTEX.lock().unwrap();
-            unsafe { Z3_model_eval(self.ctx.z3_ctx, self.z3_mdl, ast.z3_ast, true, &mut tmp) }
+        let res = unsafe {
+            Z3_model_eval(
+                self.ctx.z3_ctx,
+                self.z3_mdl,
+                ast.z3_ast,
+                model_completion,
+                &mut tmp,
+            )
         };
         if res {
             Some(Ast::new(self.ctx, tmp))
@@ -44,9 +130,186 @@ impl<'ctx> Model<'ctx> {
     }
 }
 
+impl<'ctx> Model<'ctx> {
+    /// Return the number of constants assigned by the model.
+    pub fn num_consts(&self) -> u32 {
+        unsafe { Z3_model_get_num_consts(self.ctx.z3_ctx, self.z3_mdl) }
+    }
+
+    /// Return the number of function interpretations in the model.
+    pub fn num_funcs(&self) -> u32 {
+        unsafe { Z3_model_get_num_funcs(self.ctx.z3_ctx, self.z3_mdl) }
+    }
+
+    /// Return the declarations of the constants assigned by the model.
+    pub fn const_decls(&self) -> Vec<FuncDecl<'ctx>> {
+        unsafe {
+            let n = Z3_model_get_num_consts(self.ctx.z3_ctx, self.z3_mdl);
+            (0..n)
+                .map(|i| {
+                    let f = Z3_model_get_const_decl(self.ctx.z3_ctx, self.z3_mdl, i);
+                    FuncDecl::from_raw(self.ctx, f)
+                })
+                .collect()
+        }
+    }
+
+    /// Return the declarations of the functions interpreted by the model.
+    pub fn func_decls(&self) -> Vec<FuncDecl<'ctx>> {
+        unsafe {
+            let n = Z3_model_get_num_funcs(self.ctx.z3_ctx, self.z3_mdl);
+            (0..n)
+                .map(|i| {
+                    let f = Z3_model_get_func_decl(self.ctx.z3_ctx, self.z3_mdl, i);
+                    FuncDecl::from_raw(self.ctx, f)
+                })
+                .collect()
+        }
+    }
+
+    /// Iterate over all declarations in the model, constants first.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_int_const("x");
+    /// let f = ctx.func_decl(&ctx.str_sym("f"), &[&ctx.int_sort()], &ctx.int_sort());
+    ///
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&f.apply(&[&x])._eq(&ctx.from_i64(3)));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    ///
+    /// let model = solver.get_model().unwrap();
+    /// for decl in model.iter() {
+    ///     if decl.arity() == 0 {
+    ///         println!("{} = {}", decl.name(), model.get_const_interp(&decl).unwrap());
+    ///     } else {
+    ///         let interp = model.get_func_interp(&decl).unwrap();
+    ///         println!("{} has {} entries", decl.name(), interp.entries().len());
+    ///     }
+    /// }
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = FuncDecl<'ctx>> {
+        self.const_decls().into_iter().chain(self.func_decls())
+    }
+
+    /// Return whether the model assigns an interpretation to `decl`,
+    /// which may be a constant or a function.
+    pub fn has_interp(&self, decl: &FuncDecl<'ctx>) -> bool {
+        unsafe { Z3_model_has_interp(self.ctx.z3_ctx, self.z3_mdl, decl.z3_func_decl) }
+    }
+
+    /// Return the value the model assigns to the constant `decl`, or
+    /// `None` if it has none or `decl` is not a constant.
+    pub fn get_const_interp(&self, decl: &FuncDecl<'ctx>) -> Option<Ast<'ctx>> {
+        if decl.arity() != 0 {
+            return None;
+        }
+        let a =
+            unsafe { Z3_model_get_const_interp(self.ctx.z3_ctx, self.z3_mdl, decl.z3_func_decl) };
+        if a.is_null() {
+            None
+        } else {
+            Some(Ast::new(self.ctx, a))
+        }
+    }
+
+    /// Return the interpretation of the function `decl`, or `None` if
+    /// it has none or `decl` is a constant.
+    pub fn get_func_interp(&self, decl: &FuncDecl<'ctx>) -> Option<FuncInterp<'ctx>> {
+        if decl.arity() == 0 {
+            return None;
+        }
+        unsafe {
+            let f = Z3_model_get_func_interp(self.ctx.z3_ctx, self.z3_mdl, decl.z3_func_decl);
+            if f.is_null() {
+                None
+            } else {
+                Some(FuncInterp::from_raw(self.ctx, f))
+            }
+        }
+    }
+
+    /// Assign `value` to the constant `decl`, replacing any previous
+    /// interpretation.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `decl` is not a constant or `value` does not have its
+    /// sort.
+    pub fn add_const_interp(&mut self, decl: &FuncDecl<'ctx>, value: &Ast<'ctx>) {
+        assert!(decl.arity() == 0, "{} is not a constant", decl.name());
+        let (range, sort) = (decl.range(), value.get_sort());
+        assert!(
+            range == sort,
+            "value of sort {} assigned to constant of sort {}",
+            sort,
+            range
+        );
+        unsafe {
+            Z3_add_const_interp(
+                self.ctx.z3_ctx,
+                self.z3_mdl,
+                decl.z3_func_decl,
+                value.z3_ast,
+            )
+        };
+    }
+
+    /// Add an interpretation for the function `decl` that maps every
+    /// argument to `default`. Entries for particular arguments can then
+    /// be added with [`FuncInterp::add_entry()`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `decl` is a constant or `default` does not have its
+    /// range sort.
+    ///
+    /// [`FuncInterp::add_entry()`]: struct.FuncInterp.html#method.add_entry
+    pub fn add_func_interp(
+        &mut self,
+        decl: &FuncDecl<'ctx>,
+        default: &Ast<'ctx>,
+    ) -> FuncInterp<'ctx> {
+        assert!(decl.arity() != 0, "{} is a constant", decl.name());
+        let (range, sort) = (decl.range(), default.get_sort());
+        assert!(
+            range == sort,
+            "default of sort {} for function with range {}",
+            sort,
+            range
+        );
+        unsafe {
+            let f = Z3_add_func_interp(
+                self.ctx.z3_ctx,
+                self.z3_mdl,
+                decl.z3_func_decl,
+                default.z3_ast,
+            );
+            FuncInterp::from_raw(self.ctx, f)
+        }
+    }
+}
+
+impl<'ctx> fmt::Display for Model<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let p = unsafe { Z3_model_to_string(self.ctx.z3_ctx, self.z3_mdl) };
+        if p.is_null() {
+            return Result::Err(fmt::Error);
+        }
+        match unsafe { CStr::from_ptr(p) }.to_str() {
+            Ok(s) => write!(f, "{}", s),
+            Err(_) => Result::Err(fmt::Error),
+        }
+    }
+}
+
 impl<'ctx> Drop for Model<'ctx> {
     fn drop(&mut self) {
-        let guard = Z3_MUTEX.lock().unwrap();
         unsafe { Z3_model_dec_ref(self.ctx.z3_ctx, self.z3_mdl) };
     }
 }
@@ -0,0 +1,97 @@
+use std::any::Any;
+use std::os::raw::{c_uint, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+use z3_sys::*;
+use Ast;
+use Context;
+
+/// Callback taking the proof hint, dependencies and literals of a clause.
+type OnClause<'ctx> = dyn FnMut(Option<&Ast<'ctx>>, &[u32], &[Ast<'ctx>]) + 'ctx;
+
+/// An on-clause callback registered with a solver, together with the
+/// context it uses and a panic it raised.
+struct OnClauseState<'ctx> {
+    ctx: &'ctx Context,
+    callback: Box<OnClause<'ctx>>,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+/// Owner of the state of an on-clause callback registered with a solver,
+/// which is the user context Z3 passes to it.
+///
+/// Like the state of an `InstalledPropagator`, it is kept behind an
+/// untyped pointer so that `Solver` stays covariant in its lifetime.
+pub(crate) struct InstalledOnClause {
+    state: *mut c_void,
+}
+
+impl InstalledOnClause {
+    /// Register `callback` with `slv`. The caller must drop the result
+    /// only after the solver, or after registering another callback.
+    pub(crate) unsafe fn install<'ctx, F>(
+        ctx: &'ctx Context,
+        slv: Z3_solver,
+        callback: F,
+    ) -> InstalledOnClause
+    where
+        F: FnMut(Option<&Ast<'ctx>>, &[u32], &[Ast<'ctx>]) + 'ctx,
+    {
+        let installed = InstalledOnClause {
+            state: Box::into_raw(Box::new(OnClauseState {
+                ctx,
+                callback: Box::new(callback),
+                panic: None,
+            })) as *mut c_void,
+        };
+        ctx.check_z3(|| {
+            Z3_solver_register_on_clause(ctx.z3_ctx, slv, installed.state, Some(on_clause_eh))
+        });
+        installed
+    }
+
+    /// Return the panic raised by the callback during the last search.
+    pub(crate) fn take_panic(&mut self) -> Option<Box<dyn Any + Send>> {
+        unsafe { (*(self.state as *mut OnClauseState)).panic.take() }
+    }
+}
+
+impl Drop for InstalledOnClause {
+    fn drop(&mut self) {
+        unsafe { drop(Box::from_raw(self.state as *mut OnClauseState)) };
+    }
+}
+
+// After a panic the search is interrupted and further clauses are
+// skipped, as for the callbacks of a user propagator.
+unsafe extern "C" fn on_clause_eh(
+    ctx: *mut c_void,
+    proof_hint: Z3_ast,
+    n: c_uint,
+    deps: *const c_uint,
+    literals: Z3_ast_vector,
+) {
+    let state = &mut *(ctx as *mut OnClauseState);
+    if state.panic.is_some() {
+        return;
+    }
+    let ctx = state.ctx;
+    let proof_hint = if proof_hint.is_null() {
+        None
+    } else {
+        Some(Ast::new(ctx, proof_hint))
+    };
+    let deps = if n == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(deps, n as usize)
+    };
+    let literals = Ast::vec_from_ast_vector(ctx, literals);
+    let callback = &mut state.callback;
+    if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| {
+        callback(proof_hint.as_ref(), deps, &literals)
+    })) {
+        state.panic = Some(payload);
+        ctx.interrupt();
+    }
+}
@@ -1,12 +1,23 @@
-use CheckResult;
+use context::path_to_cstring;
 use std::ffi::{CStr, CString};
 use std::fmt;
+use std::path::Path;
 use z3_sys::*;
 use Ast;
+use AstVector;
+use CheckResult;
 use Context;
 use Model;
 use Optimize;
-use Z3_MUTEX;
+use OptimizeObjective;
+use ParamDescrs;
+use Params;
+use ParetoIter;
+use SatResult;
+use SoftConstraint;
+use Statistics;
+use Symbol;
+use Z3Error;
 
 impl<'ctx> Optimize<'ctx> {
     /// Create a new optimize context.
@@ -14,7 +25,6 @@ impl<'ctx> Optimize<'ctx> {
         Optimize {
             ctx,
             z3_opt: unsafe {
-                let guard = Z3_MUTEX.lock().unwrap();
                 let opt = Z3_mk_optimize(ctx.z3_ctx);
                 Z3_optimize_inc_ref(ctx.z3_ctx, opt);
                 opt
@@ -22,14 +32,48 @@ impl<'ctx> Optimize<'ctx> {
         }
     }
 
+    /// Set the timeout of the optimization context, in milliseconds.
     pub fn set_timeout(&self, timeout: u32) {
+        let mut params = Params::new(self.ctx);
+        params.set_u32("timeout", timeout);
+        self.set_params(&params);
+    }
+
+    /// Apply the parameters in `params` to the optimization context, for
+    /// instance `priority` or `maxsat_engine`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let opt = Optimize::new(&ctx);
+    ///
+    /// let mut params = Params::new(&ctx);
+    /// params.set_symbol("priority", "box");
+    /// params.set_symbol("maxsat_engine", "wmax");
+    /// opt.set_params(&params);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the optimization context does not accept one of the
+    /// parameters, or its value has the wrong kind.
+    pub fn set_params(&self, params: &Params<'ctx>) {
+        unsafe {
+            self.ctx
+                .check_z3(|| Z3_optimize_set_params(self.ctx.z3_ctx, self.z3_opt, params.z3_params))
+        };
+    }
+
+    /// Return descriptions of the parameters accepted by the optimization
+    /// context.
+    pub fn get_param_descrs(&self) -> ParamDescrs<'ctx> {
         unsafe {
-            let guard = Z3_MUTEX.lock().unwrap();
-            let params = Z3_mk_params(self.ctx.z3_ctx);
-            let cstr_timeout = CString::new("timeout").unwrap();
-            let timeout_symbol = Z3_mk_string_symbol(self.ctx.z3_ctx, cstr_timeout.as_ptr());
-            Z3_params_set_uint(self.ctx.z3_ctx, params, timeout_symbol, timeout);
-            Z3_optimize_set_params(self.ctx.z3_ctx, self.z3_opt, params);
+            let d = Z3_optimize_get_param_descrs(self.ctx.z3_ctx, self.z3_opt);
+            ParamDescrs::from_raw(self.ctx, d)
         }
     }
 
@@ -40,47 +84,217 @@ impl<'ctx> Optimize<'ctx> {
     /// - [`Optimize::maximize()`](#method.maximize)
     /// - [`Optimize::minimize()`](#method.minimize)
     pub fn assert(&self, ast: &Ast<'ctx>) {
-        let guard = Z3_MUTEX.lock().unwrap();
-        unsafe { Z3_optimize_assert(self.ctx.z3_ctx, self.z3_opt, ast.z3_ast) };
+        unsafe {
+            self.ctx
+                .check_z3(|| Z3_optimize_assert(self.ctx.z3_ctx, self.z3_opt, ast.z3_ast))
+        };
+    }
+
+    /// Assert hard constraint `ast` to the optimization context, tracked
+    /// by the Boolean constant `p`.
+    ///
+    /// If the hard constraints are unsatisfiable, `p` may appear in
+    /// [`Optimize::get_unsat_core()`](#method.get_unsat_core), identifying
+    /// `ast` as one of the constraints responsible.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_int_const("x");
+    /// let p1 = ctx.named_bool_const("p1");
+    /// let p2 = ctx.named_bool_const("p2");
+    ///
+    /// let opt = Optimize::new(&ctx);
+    /// opt.assert_and_track(&x.gt(&ctx.from_i64(10)), &p1);
+    /// opt.assert_and_track(&x.lt(&ctx.from_i64(5)), &p2);
+    /// opt.maximize(&x);
+    /// assert_eq!(opt.check(), SatResult::Unsat);
+    ///
+    /// let core = opt.get_unsat_core();
+    /// assert!(core.contains(&p1) && core.contains(&p2));
+    /// ```
+    ///
+    /// # See also:
+    ///
+    /// - [`Optimize::assert()`](#method.assert)
+    pub fn assert_and_track(&self, ast: &Ast<'ctx>, p: &Ast<'ctx>) {
+        unsafe {
+            self.ctx.check_z3(|| {
+                Z3_optimize_assert_and_track(self.ctx.z3_ctx, self.z3_opt, ast.z3_ast, p.z3_ast)
+            })
+        };
     }
 
     /// Assert soft constraint to the optimization context.
     ///
+    /// Violating the constraint incurs a penalty of `weight`. Soft
+    /// constraints sharing the same `group` form a single objective,
+    /// minimizing the total penalty of the group; without a group, the
+    /// constraint belongs to a default group.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let a = ctx.named_bool_const("a");
+    /// let b = ctx.named_bool_const("b");
+    ///
+    /// let opt = Optimize::new(&ctx);
+    /// opt.assert(&a.xor(&b));
+    /// let group = ctx.str_sym("prefs");
+    /// let soft_a = opt.add_soft(&a, 1, Some(&group));
+    /// let soft_b = opt.add_soft(&b, 4, Some(&group));
+    /// assert_eq!(opt.check(), SatResult::Sat);
+    ///
+    /// let model = opt.get_model().unwrap();
+    /// assert_eq!(soft_a.is_satisfied(&model), Some(false));
+    /// assert_eq!(soft_b.is_satisfied(&model), Some(true));
+    /// assert_eq!(soft_a.penalty().as_i64(), Some(1));
+    /// ```
+    ///
     /// # See also:
     ///
     /// - [`Optimize::maximize()`](#method.maximize)
     /// - [`Optimize::minimize()`](#method.minimize)
-    pub fn add_soft(&self, ast: &Ast<'ctx>, weight: i64) {
-        let guard = Z3_MUTEX.lock().unwrap();
+    pub fn add_soft(
+        &self,
+        ast: &Ast<'ctx>,
+        weight: i64,
+        group: Option<&Symbol<'ctx>>,
+    ) -> SoftConstraint<'ctx> {
+        let constraint = ast.clone();
+        // Z3 takes soft-constraint weights only as decimal strings; unlike
+        // numerals there is no Z3_mk_int64-style entry point for them.
         let cstr_weight = CString::new(weight.to_string()).unwrap();
-        let symbol_ptr = std::ptr::null_mut();
-        unsafe { Z3_optimize_assert_soft(self.ctx.z3_ctx,
-                                         self.z3_opt,
-                                         ast.z3_ast,
-                                         cstr_weight.as_ptr(),
-                                         symbol_ptr) };
+        let group = group.map_or(::std::ptr::null_mut(), |g| g.z3_sym);
+        let idx = unsafe {
+            Z3_optimize_inc_ref(self.ctx.z3_ctx, self.z3_opt);
+            Z3_optimize_assert_soft(
+                self.ctx.z3_ctx,
+                self.z3_opt,
+                ast.z3_ast,
+                cstr_weight.as_ptr(),
+                group,
+            )
+        };
+        SoftConstraint {
+            ctx: self.ctx,
+            z3_opt: self.z3_opt,
+            idx,
+            constraint,
+        }
     }
 
     /// Add a maximization constraint.
     ///
+    /// The returned handle can be used to query the bounds found for
+    /// this objective after a call to [`Optimize::check()`](#method.check).
+    ///
     /// # See also:
     ///
     /// - [`Optimize::assert()`](#method.assert)
     /// - [`Optimize::minimize()`](#method.minimize)
-    pub fn maximize(&self, ast: &Ast<'ctx>) {
-        let guard = Z3_MUTEX.lock().unwrap();
-        unsafe { Z3_optimize_maximize(self.ctx.z3_ctx, self.z3_opt, ast.z3_ast) };
+    pub fn maximize(&self, ast: &Ast<'ctx>) -> OptimizeObjective<'ctx> {
+        let term = ast.clone();
+        let idx = unsafe {
+            Z3_optimize_inc_ref(self.ctx.z3_ctx, self.z3_opt);
+            Z3_optimize_maximize(self.ctx.z3_ctx, self.z3_opt, ast.z3_ast)
+        };
+        OptimizeObjective {
+            ctx: self.ctx,
+            z3_opt: self.z3_opt,
+            idx,
+            term,
+        }
     }
 
     /// Add a minimization constraint.
     ///
+    /// The returned handle can be used to query the bounds found for
+    /// this objective after a call to [`Optimize::check()`](#method.check).
+    ///
     /// # See also:
     ///
     /// - [`Optimize::assert()`](#method.assert)
     /// - [`Optimize::maximize()`](#method.maximize)
-    pub fn minimize(&self, ast: &Ast<'ctx>) {
-        let guard = Z3_MUTEX.lock().unwrap();
-        unsafe { Z3_optimize_minimize(self.ctx.z3_ctx, self.z3_opt, ast.z3_ast) };
+    pub fn minimize(&self, ast: &Ast<'ctx>) -> OptimizeObjective<'ctx> {
+        let term = ast.clone();
+        let idx = unsafe {
+            Z3_optimize_inc_ref(self.ctx.z3_ctx, self.z3_opt);
+            Z3_optimize_minimize(self.ctx.z3_ctx, self.z3_opt, ast.z3_ast)
+        };
+        OptimizeObjective {
+            ctx: self.ctx,
+            z3_opt: self.z3_opt,
+            idx,
+            term,
+        }
+    }
+
+    /// Parse an SMT-LIB2 string with assertions, soft constraints and
+    /// optimization objectives, and add them to the optimization context.
+    ///
+    /// Objectives added this way can be queried by index with
+    /// [`Optimize::get_lower()`](#method.get_lower) and
+    /// [`Optimize::get_upper()`](#method.get_upper).
+    ///
+    /// Returns an error if the string is not valid SMT-LIB2, in which case
+    /// the commands before the error have still been added.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let opt = Optimize::new(&ctx);
+    /// opt.from_string(
+    ///     "(declare-const x Int)
+    ///      (assert (< x 10))
+    ///      (assert-soft (< x 5) :weight 2)
+    ///      (maximize x)",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(opt.check(), SatResult::Sat);
+    ///
+    /// // The soft constraint comes first, so it takes priority.
+    /// assert_eq!(opt.get_upper(1).as_i64(), Some(4));
+    /// ```
+    ///
+    /// # See also:
+    ///
+    /// - [`Optimize::from_file()`](#method.from_file)
+    pub fn from_string(&self, s: &str) -> Result<(), Z3Error> {
+        let s = CString::new(s).unwrap();
+        unsafe {
+            self.ctx
+                .try_z3(|| Z3_optimize_from_string(self.ctx.z3_ctx, self.z3_opt, s.as_ptr()))
+        }
+    }
+
+    /// Parse an SMT-LIB2 file with assertions, soft constraints and
+    /// optimization objectives, and add them to the optimization context.
+    ///
+    /// Returns an error if the file cannot be read or is not valid
+    /// SMT-LIB2.
+    ///
+    /// # See also:
+    ///
+    /// - [`Optimize::from_string()`](#method.from_string)
+    pub fn from_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Z3Error> {
+        let path = path_to_cstring(path.as_ref())?;
+        unsafe {
+            self.ctx
+                .try_z3(|| Z3_optimize_from_file(self.ctx.z3_ctx, self.z3_opt, path.as_ptr()))
+        }
     }
 
     /// Create a backtracking point.
@@ -93,7 +307,6 @@ impl<'ctx> Optimize<'ctx> {
     ///
     /// - [`Optimize::pop()`](#method.pop)
     pub fn push(&self) {
-        let guard = Z3_MUTEX.lock().unwrap();
         unsafe { Z3_optimize_push(self.ctx.z3_ctx, self.z3_opt) };
     }
 
@@ -108,7 +321,6 @@ impl<'ctx> Optimize<'ctx> {
     ///
     /// - [`Optimize::push()`](#method.push)
     pub fn pop(&self) {
-        let guard = Z3_MUTEX.lock().unwrap();
         unsafe { Z3_optimize_pop(self.ctx.z3_ctx, self.z3_opt) };
     }
 
@@ -117,11 +329,10 @@ impl<'ctx> Optimize<'ctx> {
     /// # See also:
     ///
     /// - [`Optimize::get_model()`](#method.get_model)
-    pub fn check(&self) -> bool {
-        let guard = Z3_MUTEX.lock().unwrap();
-        unsafe {
-            Z3_optimize_check(self.ctx.z3_ctx, self.z3_opt) == Z3_L_TRUE
-        }
+    pub fn check(&self) -> SatResult {
+        SatResult::from_lbool(unsafe {
+            Z3_optimize_check(self.ctx.z3_ctx, self.z3_opt, 0, ::std::ptr::null())
+        })
     }
 
     /// Check consistency and produce optimal values.
@@ -132,37 +343,362 @@ impl<'ctx> Optimize<'ctx> {
     /// - [`Optimize::get_model()`](#method.get_model)
     pub fn check_get_model(&self) -> CheckResult<'ctx> {
         let lbool = unsafe {
-            let guard = Z3_MUTEX.lock().unwrap();
-            Z3_optimize_check(self.ctx.z3_ctx, self.z3_opt)
+            Z3_optimize_check(self.ctx.z3_ctx, self.z3_opt, 0, ::std::ptr::null())
         };
 
         match lbool {
-            Z3_L_TRUE => CheckResult::Satisfiable(self.get_model()),
+            Z3_L_TRUE => CheckResult::Satisfiable(self.get_model().unwrap()),
             Z3_L_FALSE => CheckResult::Unsatisfiable,
-            Z3_L_UNDEF => CheckResult::Unknown(self.get_model()),
+            Z3_L_UNDEF => {
+                CheckResult::Unknown(self.get_model().ok(), self.get_reason_unknown())
+            }
             _ => panic!("Bad check result from z3 api!")
         }
     }
 
+    /// Enumerate the Pareto-optimal models of the objectives.
+    ///
+    /// This sets the `priority` parameter to `pareto`; each step of the
+    /// iterator performs one [`Optimize::check()`](#method.check) and
+    /// yields the model found. The iterator ends once no further
+    /// Pareto-optimal model exists, or the result is unknown.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_int_const("x");
+    /// let y = ctx.named_int_const("y");
+    ///
+    /// let opt = Optimize::new(&ctx);
+    /// opt.assert(&x.ge(&ctx.from_i64(0)));
+    /// opt.assert(&y.ge(&ctx.from_i64(0)));
+    /// opt.assert(&x.add(&[&y]).le(&ctx.from_i64(2)));
+    /// opt.maximize(&x);
+    /// opt.maximize(&y);
+    ///
+    /// // (2, 0), (1, 1) and (0, 2), in some order.
+    /// assert_eq!(opt.pareto_iter().count(), 3);
+    /// ```
+    pub fn pareto_iter<'a>(&'a self) -> ParetoIter<'a, 'ctx> {
+        let mut params = Params::new(self.ctx);
+        params.set_symbol("priority", "pareto");
+        self.set_params(&params);
+        ParetoIter {
+            opt: self,
+            done: false,
+        }
+    }
+
+    /// Check consistency and produce optimal values under the given
+    /// assumptions, which must be Boolean constants or negations of
+    /// Boolean constants.
+    ///
+    /// If the result is unsatisfiable,
+    /// [`Optimize::get_unsat_core()`](#method.get_unsat_core) retrieves
+    /// the subset of the assumptions responsible.
+    pub fn check_assumptions(&self, assumptions: &[&Ast<'ctx>]) -> CheckResult<'ctx> {
+        let a: Vec<Z3_ast> = assumptions.iter().map(|a| a.z3_ast).collect();
+        let lbool = unsafe {
+            Z3_optimize_check(self.ctx.z3_ctx, self.z3_opt, a.len() as u32, a.as_ptr())
+        };
+
+        match lbool {
+            Z3_L_TRUE => CheckResult::Satisfiable(self.get_model().unwrap()),
+            Z3_L_FALSE => CheckResult::Unsatisfiable,
+            Z3_L_UNDEF => {
+                CheckResult::Unknown(self.get_model().ok(), self.get_reason_unknown())
+            }
+            _ => panic!("Bad check result from z3 api!"),
+        }
+    }
+
+    /// Return a brief justification for an "unknown" result of the last
+    /// check, such as `"timeout"`, `"canceled"` or `"incomplete"`.
+    pub fn get_reason_unknown(&self) -> String {
+        unsafe {
+            let p = Z3_optimize_get_reason_unknown(self.ctx.z3_ctx, self.z3_opt);
+            CStr::from_ptr(p).to_string_lossy().into_owned()
+        }
+    }
+
+    /// Retrieve the unsat core for the last
+    /// [`Optimize::check_assumptions()`](#method.check_assumptions), that
+    /// is a subset of the assumptions which is already unsatisfiable
+    /// together with the hard constraints.
+    ///
+    /// The result is empty if the last check was not unsatisfiable.
+    pub fn get_unsat_core(&self) -> Vec<Ast<'ctx>> {
+        let core = unsafe { Z3_optimize_get_unsat_core(self.ctx.z3_ctx, self.z3_opt) };
+        Ast::vec_from_ast_vector(self.ctx, core)
+    }
+
+    /// Return the formulas currently asserted to the optimization context, including
+    /// those asserted with [`Optimize::assert_and_track()`](#method.assert_and_track).
+    pub fn assertions(&self) -> impl Iterator<Item = Ast<'ctx>> {
+        let v = unsafe { Z3_optimize_get_assertions(self.ctx.z3_ctx, self.z3_opt) };
+        Ast::vec_from_ast_vector(self.ctx, v).into_iter()
+    }
+
+    /// Retrieve the objectives of the optimization context, in the order
+    /// in which they were added.
+    ///
+    /// Every objective is returned as a minimization objective: a
+    /// maximization objective `t` is returned as `-t`, and a group of
+    /// soft constraints as the pseudo-Boolean sum of the weights of the
+    /// violated constraints.
+    pub fn get_objectives(&self) -> Vec<Ast<'ctx>> {
+        let objectives = unsafe { Z3_optimize_get_objectives(self.ctx.z3_ctx, self.z3_opt) };
+        Ast::vec_from_ast_vector(self.ctx, objectives)
+    }
+
+    /// Retrieve the lower bound found for the `idx`-th objective by the
+    /// last [`Optimize::check()`](#method.check). Objectives are numbered
+    /// from 0 in the order in which they were added.
+    ///
+    /// The bound may be an expression involving `oo` (infinity) and
+    /// `epsilon` when the objective is unbounded or the optimum is not
+    /// attained.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no `idx`-th objective.
+    ///
+    /// # See also:
+    ///
+    /// - [`Optimize::get_upper()`](#method.get_upper)
+    /// - [`Optimize::get_lower_as_vector()`](#method.get_lower_as_vector)
+    pub fn get_lower(&self, idx: u32) -> Ast<'ctx> {
+        check_objective(self.ctx, self.z3_opt, idx);
+        Ast::new(self.ctx, unsafe {
+            Z3_optimize_get_lower(self.ctx.z3_ctx, self.z3_opt, idx)
+        })
+    }
+
+    /// Retrieve the upper bound found for the `idx`-th objective by the
+    /// last [`Optimize::check()`](#method.check).
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no `idx`-th objective.
+    ///
+    /// # See also:
+    ///
+    /// - [`Optimize::get_lower()`](#method.get_lower)
+    /// - [`Optimize::get_upper_as_vector()`](#method.get_upper_as_vector)
+    pub fn get_upper(&self, idx: u32) -> Ast<'ctx> {
+        check_objective(self.ctx, self.z3_opt, idx);
+        Ast::new(self.ctx, unsafe {
+            Z3_optimize_get_upper(self.ctx.z3_ctx, self.z3_opt, idx)
+        })
+    }
+
+    /// Retrieve the lower bound of the `idx`-th objective as three
+    /// numerals `[a, b, c]`, encoding the bound
+    /// `a * infinity + b + c * epsilon`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no `idx`-th objective.
+    pub fn get_lower_as_vector(&self, idx: u32) -> Vec<Ast<'ctx>> {
+        check_objective(self.ctx, self.z3_opt, idx);
+        let v = unsafe { Z3_optimize_get_lower_as_vector(self.ctx.z3_ctx, self.z3_opt, idx) };
+        Ast::vec_from_ast_vector(self.ctx, v)
+    }
+
+    /// Retrieve the upper bound of the `idx`-th objective as three
+    /// numerals `[a, b, c]`, encoding the bound
+    /// `a * infinity + b + c * epsilon`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no `idx`-th objective.
+    pub fn get_upper_as_vector(&self, idx: u32) -> Vec<Ast<'ctx>> {
+        check_objective(self.ctx, self.z3_opt, idx);
+        let v = unsafe { Z3_optimize_get_upper_as_vector(self.ctx.z3_ctx, self.z3_opt, idx) };
+        Ast::vec_from_ast_vector(self.ctx, v)
+    }
+
+    /// Retrieve statistics for the last [`Optimize::check()`](#method.check),
+    /// such as the number of conflicts and MaxSAT engine counters.
+    pub fn get_statistics(&self) -> Statistics<'ctx> {
+        unsafe {
+            let s = Z3_optimize_get_statistics(self.ctx.z3_ctx, self.z3_opt);
+            Statistics::from_raw(self.ctx, s)
+        }
+    }
+
+    /// Return the assertions and objectives of the optimization context
+    /// as a complete SMT-LIB2 benchmark, ending in `(check-sat)`, that
+    /// can be run with the Z3 command line tool.
+    pub fn to_smt2(&self) -> String {
+        self.to_string()
+    }
+
+    /// Interrupt a running [`Optimize::check()`](#method.check).
+    ///
+    /// This interrupts every operation running in the optimization
+    /// context's [`Context`](struct.Context.html); see
+    /// [`Context::interrupt()`](struct.Context.html#method.interrupt).
+    pub fn interrupt(&self) {
+        self.ctx.interrupt();
+    }
+
     /// Retrieve the model for the last [`Optimize::check()`](#method.check)
     ///
-    /// The error handler is invoked if a model is not available because
-    /// the commands above were not invoked for the given optimization
-    /// solver, or if the result was `Z3_L_FALSE`.
-    pub fn get_model(&self) -> Model<'ctx> {
+    /// Returns an error if a model is not available because the command
+    /// above was not invoked for the given optimization solver, or if the
+    /// result was `Z3_L_FALSE`.
+    pub fn get_model(&self) -> Result<Model<'ctx>, Z3Error> {
         Model::of_optimize(self)
     }
 }
 
+impl<'a, 'ctx> Iterator for ParetoIter<'a, 'ctx> {
+    type Item = Model<'ctx>;
+
+    fn next(&mut self) -> Option<Model<'ctx>> {
+        if self.done {
+            return None;
+        }
+        let model = match self.opt.check() {
+            SatResult::Sat => self.opt.get_model().ok(),
+            _ => None,
+        };
+        self.done = model.is_none();
+        model
+    }
+}
+
+/// Panic unless `idx` is the index of one of the objectives of `opt`.
+fn check_objective(ctx: &Context, opt: Z3_optimize, idx: u32) {
+    let objectives = unsafe {
+        let v = Z3_optimize_get_objectives(ctx.z3_ctx, opt);
+        AstVector::from_raw(ctx, v)
+    };
+    assert!(
+        (idx as usize) < objectives.len(),
+        "objective index {} out of range for {} objectives",
+        idx,
+        objectives.len()
+    );
+}
+
+impl<'ctx> OptimizeObjective<'ctx> {
+    /// Return the index of this objective, as used by
+    /// [`Optimize::get_lower()`](struct.Optimize.html#method.get_lower)
+    /// and [`Optimize::get_upper()`](struct.Optimize.html#method.get_upper).
+    pub fn index(&self) -> u32 {
+        self.idx
+    }
+
+    /// Retrieve the lower bound found for this objective by the last
+    /// check.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_int_const("x");
+    /// let y = ctx.named_int_const("y");
+    ///
+    /// let opt = Optimize::new(&ctx);
+    /// opt.assert(&x.add(&[&y]).le(&ctx.from_i64(10)));
+    /// opt.assert(&y.ge(&ctx.from_i64(3)));
+    /// let max_x = opt.maximize(&x);
+    /// let min_y = opt.minimize(&y);
+    /// assert_eq!(opt.check(), SatResult::Sat);
+    ///
+    /// assert_eq!(max_x.upper().as_i64(), Some(7));
+    /// assert_eq!(min_y.lower().as_i64(), Some(3));
+    /// let model = opt.get_model().unwrap();
+    /// assert_eq!(min_y.value_in(&model).and_then(|v| v.as_i64()), Some(3));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the objective was removed by
+    /// [`Optimize::pop()`](struct.Optimize.html#method.pop).
+    pub fn lower(&self) -> Ast<'ctx> {
+        check_objective(self.ctx, self.z3_opt, self.idx);
+        Ast::new(self.ctx, unsafe {
+            Z3_optimize_get_lower(self.ctx.z3_ctx, self.z3_opt, self.idx)
+        })
+    }
+
+    /// Retrieve the upper bound found for this objective by the last
+    /// check.
+    ///
+    /// # Panics
+    ///
+    /// If the objective was removed by
+    /// [`Optimize::pop()`](struct.Optimize.html#method.pop).
+    pub fn upper(&self) -> Ast<'ctx> {
+        check_objective(self.ctx, self.z3_opt, self.idx);
+        Ast::new(self.ctx, unsafe {
+            Z3_optimize_get_upper(self.ctx.z3_ctx, self.z3_opt, self.idx)
+        })
+    }
+
+    /// Evaluate the objective term in `model`.
+    pub fn value_in(&self, model: &Model<'ctx>) -> Option<Ast<'ctx>> {
+        model.eval(&self.term, true)
+    }
+}
+
+impl<'ctx> Drop for OptimizeObjective<'ctx> {
+    fn drop(&mut self) {
+        unsafe { Z3_optimize_dec_ref(self.ctx.z3_ctx, self.z3_opt) };
+    }
+}
+
+impl<'ctx> SoftConstraint<'ctx> {
+    /// Return the index of the objective formed by this constraint's
+    /// group.
+    pub fn group_index(&self) -> u32 {
+        self.idx
+    }
+
+    /// Check whether the constraint holds in `model`, typically the model
+    /// of the last [`Optimize::check()`](struct.Optimize.html#method.check).
+    pub fn is_satisfied(&self, model: &Model<'ctx>) -> Option<bool> {
+        model.eval(&self.constraint, true).and_then(|v| v.as_bool())
+    }
+
+    /// Retrieve the total penalty of the violated constraints in this
+    /// constraint's group, as found by the last check.
+    ///
+    /// # Panics
+    ///
+    /// If the group's objective was removed by
+    /// [`Optimize::pop()`](struct.Optimize.html#method.pop).
+    pub fn penalty(&self) -> Ast<'ctx> {
+        check_objective(self.ctx, self.z3_opt, self.idx);
+        Ast::new(self.ctx, unsafe {
+            Z3_optimize_get_upper(self.ctx.z3_ctx, self.z3_opt, self.idx)
+        })
+    }
+}
+
+impl<'ctx> Drop for SoftConstraint<'ctx> {
+    fn drop(&mut self) {
+        unsafe { Z3_optimize_dec_ref(self.ctx.z3_ctx, self.z3_opt) };
+    }
+}
+
 impl<'ctx> fmt::Display for Optimize<'ctx> {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        let p = unsafe {
-            CStr::from_ptr(Z3_optimize_to_string(self.ctx.z3_ctx, self.z3_opt) as *mut i8)
-        };
-        if p.as_ptr().is_null() {
+        let p = unsafe { Z3_optimize_to_string(self.ctx.z3_ctx, self.z3_opt) };
+        if p.is_null() {
             return Result::Err(fmt::Error);
         }
-        match p.to_str() {
+        match unsafe { CStr::from_ptr(p) }.to_str() {
             Ok(s) => write!(f, "{}", s),
             Err(_) => Result::Err(fmt::Error),
         }
@@ -171,7 +707,6 @@ impl<'ctx> fmt::Display for Optimize<'ctx> {
 
 impl<'ctx> Drop for Optimize<'ctx> {
     fn drop(&mut self) {
-        let guard = Z3_MUTEX.lock().unwrap();
         unsafe { Z3_optimize_dec_ref(self.ctx.z3_ctx, self.z3_opt) };
     }
 }
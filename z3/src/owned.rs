@@ -0,0 +1,180 @@
+use std::fmt;
+use std::ptr;
+use std::sync::Arc;
+use z3_sys::*;
+use Ast;
+use Config;
+use Context;
+use DetachedSolver;
+use Model;
+use OwnedAst;
+use OwnedModel;
+use OwnedSolver;
+use Solver;
+use Z3Error;
+
+/// Borrow a shared context for `'static`. The result must only be stored
+/// in a field declared before a clone of `ctx`, so that it is dropped
+/// while the context is still alive.
+unsafe fn extend(ctx: &Arc<Context>) -> &'static Context {
+    &*(&**ctx as *const Context)
+}
+
+impl OwnedAst {
+    /// Take a new reference to `ast`, which must belong to `ctx`.
+    ///
+    /// # Panics
+    ///
+    /// If `ast` belongs to a different context.
+    pub fn new(ctx: &Arc<Context>, ast: &Ast<'_>) -> OwnedAst {
+        assert!(
+            ptr::eq(ast.ctx, &**ctx),
+            "ast belongs to a different context"
+        );
+        OwnedAst {
+            ast: Ast::new(unsafe { extend(ctx) }, ast.z3_ast),
+            ctx: ctx.clone(),
+        }
+    }
+
+    /// Borrow the term as an ordinary `Ast`.
+    pub fn get(&self) -> &Ast<'_> {
+        &self.ast
+    }
+
+    /// The context the term belongs to.
+    pub fn context(&self) -> &Arc<Context> {
+        &self.ctx
+    }
+}
+
+impl Clone for OwnedAst {
+    fn clone(&self) -> OwnedAst {
+        OwnedAst {
+            ast: self.ast.clone(),
+            ctx: self.ctx.clone(),
+        }
+    }
+}
+
+impl fmt::Display for OwnedAst {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        self.ast.fmt(f)
+    }
+}
+
+impl OwnedSolver {
+    /// Create a new solver in `ctx`.
+    ///
+    /// # See also:
+    ///
+    /// - [`Solver::new()`](struct.Solver.html#method.new)
+    pub fn new(ctx: &Arc<Context>) -> OwnedSolver {
+        OwnedSolver {
+            solver: Solver::new(unsafe { extend(ctx) }),
+            ctx: ctx.clone(),
+        }
+    }
+
+    /// Borrow the solver as an ordinary `Solver`.
+    pub fn get(&self) -> &Solver<'_> {
+        &self.solver
+    }
+
+    /// The context the solver belongs to.
+    pub fn context(&self) -> &Arc<Context> {
+        &self.ctx
+    }
+
+    /// Retrieve the model for the last check, like
+    /// [`Solver::get_model()`](struct.Solver.html#method.get_model), as an
+    /// owned model.
+    pub fn get_model(&self) -> Result<OwnedModel, Z3Error> {
+        self.solver.get_model().map(|model| OwnedModel {
+            model,
+            ctx: self.ctx.clone(),
+        })
+    }
+}
+
+impl fmt::Display for OwnedSolver {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        self.solver.fmt(f)
+    }
+}
+
+impl OwnedModel {
+    /// Take a new reference to `model`, which must belong to `ctx`.
+    ///
+    /// # Panics
+    ///
+    /// If `model` belongs to a different context.
+    pub fn new(ctx: &Arc<Context>, model: &Model<'_>) -> OwnedModel {
+        assert!(
+            ptr::eq(model.ctx, &**ctx),
+            "model belongs to a different context"
+        );
+        let model = Model {
+            ctx: unsafe { extend(ctx) },
+            z3_mdl: unsafe {
+                Z3_model_inc_ref(ctx.z3_ctx, model.z3_mdl);
+                model.z3_mdl
+            },
+        };
+        OwnedModel {
+            model,
+            ctx: ctx.clone(),
+        }
+    }
+
+    /// Borrow the model as an ordinary `Model`.
+    pub fn get(&self) -> &Model<'_> {
+        &self.model
+    }
+
+    /// The context the model belongs to.
+    pub fn context(&self) -> &Arc<Context> {
+        &self.ctx
+    }
+}
+
+impl DetachedSolver {
+    /// Copy `solver`, and `terms` from any context, into a new context
+    /// configured by `cfg`.
+    ///
+    /// # Panics
+    ///
+    /// Like [`Solver::translate()`](struct.Solver.html#method.translate),
+    /// if the solver has backtracking points that have not been popped or
+    /// a user propagator.
+    pub fn new(solver: &Solver<'_>, terms: &[&Ast<'_>], cfg: &Config) -> DetachedSolver {
+        let ctx = Context::new_shared(cfg);
+        let dest = unsafe { extend(&ctx) };
+        DetachedSolver {
+            solver: OwnedSolver {
+                solver: solver.translate(dest),
+                ctx: ctx.clone(),
+            },
+            terms: terms
+                .iter()
+                .map(|t| OwnedAst {
+                    ast: t.translate(dest),
+                    ctx: ctx.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Turn the copy into owned handles for the thread that calls this:
+    /// the solver and the copies of the terms, in order.
+    pub fn attach(self) -> (OwnedSolver, Vec<OwnedAst>) {
+        (self.solver, self.terms)
+    }
+}
+
+// Every reference to the context of a detached solver, including each
+// clone of its `Arc`, is inside the `DetachedSolver`, which gives no
+// access to them until it is consumed by `attach()`. Sending it thus
+// moves the context together with everything that uses it, and a Z3
+// context is not tied to the thread that created it.
+unsafe impl Send for DetachedSolver {}
@@ -0,0 +1,216 @@
+use std::ffi::{CStr, CString};
+use std::fmt;
+use z3_sys::*;
+use Context;
+use ParamDescrs;
+use ParamKind;
+use Params;
+use Z3_MUTEX;
+
+/// Set a global (or module) parameter, e.g. `parallel.enable` or
+/// `verbose`. The setting is shared by all contexts in the process.
+///
+/// # Example
+///
+/// ```
+/// z3::set_global_param("verbose", "1");
+/// assert_eq!(z3::get_global_param("verbose"), Some("1".to_string()));
+/// z3::reset_all_global_params();
+/// ```
+///
+/// # See also:
+///
+/// - [`get_global_param()`](fn.get_global_param.html)
+/// - [`reset_all_global_params()`](fn.reset_all_global_params.html)
+pub fn set_global_param(k: &str, v: &str) {
+    let k = CString::new(k).unwrap();
+    let v = CString::new(v).unwrap();
+    let guard = Z3_MUTEX.lock().unwrap();
+    unsafe { Z3_global_param_set(k.as_ptr(), v.as_ptr()) };
+}
+
+/// Get the value of a global (or module) parameter, or `None` if there
+/// is no such parameter.
+///
+/// # See also:
+///
+/// - [`set_global_param()`](fn.set_global_param.html)
+pub fn get_global_param(k: &str) -> Option<String> {
+    let k = CString::new(k).unwrap();
+    let guard = Z3_MUTEX.lock().unwrap();
+    let mut v: Z3_string = ::std::ptr::null();
+    unsafe {
+        if Z3_global_param_get(k.as_ptr(), &mut v) && !v.is_null() {
+            Some(CStr::from_ptr(v).to_string_lossy().into_owned())
+        } else {
+            None
+        }
+    }
+}
+
+/// Restore all global (and module) parameters to their default values.
+/// Objects that were already created are not affected.
+///
+/// # See also:
+///
+/// - [`set_global_param()`](fn.set_global_param.html)
+pub fn reset_all_global_params() {
+    let guard = Z3_MUTEX.lock().unwrap();
+    unsafe { Z3_global_param_reset_all() };
+}
+
+impl<'ctx> Params<'ctx> {
+    /// Create an empty parameter set.
+    pub fn new(ctx: &'ctx Context) -> Params<'ctx> {
+        Params {
+            ctx,
+            z3_params: unsafe {
+                let p = Z3_mk_params(ctx.z3_ctx);
+                Z3_params_inc_ref(ctx.z3_ctx, p);
+                p
+            },
+        }
+    }
+
+    unsafe fn mk_sym(&self, s: &str) -> Z3_symbol {
+        let s = CString::new(s).unwrap();
+        Z3_mk_string_symbol(self.ctx.z3_ctx, s.as_ptr())
+    }
+
+    /// Set the Boolean parameter `k` to `v`.
+    pub fn set_bool(&mut self, k: &str, v: bool) {
+        unsafe {
+            let k = self.mk_sym(k);
+            Z3_params_set_bool(self.ctx.z3_ctx, self.z3_params, k, v)
+        };
+    }
+
+    /// Set the unsigned integer parameter `k` to `v`.
+    pub fn set_u32(&mut self, k: &str, v: u32) {
+        unsafe {
+            let k = self.mk_sym(k);
+            Z3_params_set_uint(self.ctx.z3_ctx, self.z3_params, k, v)
+        };
+    }
+
+    /// Set the floating-point parameter `k` to `v`.
+    pub fn set_f64(&mut self, k: &str, v: f64) {
+        unsafe {
+            let k = self.mk_sym(k);
+            Z3_params_set_double(self.ctx.z3_ctx, self.z3_params, k, v)
+        };
+    }
+
+    /// Set the symbol parameter `k` to `v`, e.g. `opt.priority` to
+    /// `pareto`.
+    pub fn set_symbol(&mut self, k: &str, v: &str) {
+        unsafe {
+            let k = self.mk_sym(k);
+            let v = self.mk_sym(v);
+            Z3_params_set_symbol(self.ctx.z3_ctx, self.z3_params, k, v)
+        };
+    }
+}
+
+impl<'ctx> ParamDescrs<'ctx> {
+    /// Wrap parameter descriptions returned by Z3.
+    pub(crate) unsafe fn from_raw(ctx: &'ctx Context, d: Z3_param_descrs) -> ParamDescrs<'ctx> {
+        ctx.check_null(d);
+        Z3_param_descrs_inc_ref(ctx.z3_ctx, d);
+        ParamDescrs {
+            ctx,
+            z3_param_descrs: d,
+        }
+    }
+
+    /// Return the number of described parameters.
+    pub fn len(&self) -> usize {
+        unsafe { Z3_param_descrs_size(self.ctx.z3_ctx, self.z3_param_descrs) as usize }
+    }
+
+    /// Return `true` if no parameters are described.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the names of all described parameters.
+    pub fn names(&self) -> Vec<String> {
+        let n = self.len() as u32;
+        (0..n)
+            .map(|i| unsafe {
+                let s = Z3_param_descrs_get_name(self.ctx.z3_ctx, self.z3_param_descrs, i);
+                let p = Z3_get_symbol_string(self.ctx.z3_ctx, s);
+                CStr::from_ptr(p).to_string_lossy().into_owned()
+            })
+            .collect()
+    }
+
+    /// Return the kind of the parameter `name`, or `None` if there is no
+    /// such parameter.
+    pub fn kind(&self, name: &str) -> Option<ParamKind> {
+        let name = CString::new(name).unwrap();
+        let kind = unsafe {
+            let s = Z3_mk_string_symbol(self.ctx.z3_ctx, name.as_ptr());
+            Z3_param_descrs_get_kind(self.ctx.z3_ctx, self.z3_param_descrs, s)
+        };
+        match kind {
+            ParamKind::Invalid => None,
+            k => Some(k),
+        }
+    }
+
+    /// Return the documentation string of the parameter `name`, or `None`
+    /// if there is no such parameter.
+    pub fn documentation(&self, name: &str) -> Option<String> {
+        // Z3 reports an error for unknown names, so check first.
+        self.kind(name)?;
+        let name = CString::new(name).unwrap();
+        unsafe {
+            let s = Z3_mk_string_symbol(self.ctx.z3_ctx, name.as_ptr());
+            let p = Z3_param_descrs_get_documentation(self.ctx.z3_ctx, self.z3_param_descrs, s);
+            if p.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(p).to_string_lossy().into_owned())
+            }
+        }
+    }
+}
+
+impl<'ctx> fmt::Display for ParamDescrs<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let p = unsafe { Z3_param_descrs_to_string(self.ctx.z3_ctx, self.z3_param_descrs) };
+        if p.is_null() {
+            return Result::Err(fmt::Error);
+        }
+        match unsafe { CStr::from_ptr(p) }.to_str() {
+            Ok(s) => write!(f, "{}", s),
+            Err(_) => Result::Err(fmt::Error),
+        }
+    }
+}
+
+impl<'ctx> Drop for ParamDescrs<'ctx> {
+    fn drop(&mut self) {
+        unsafe { Z3_param_descrs_dec_ref(self.ctx.z3_ctx, self.z3_param_descrs) };
+    }
+}
+
+impl<'ctx> fmt::Display for Params<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let p = unsafe { Z3_params_to_string(self.ctx.z3_ctx, self.z3_params) };
+        if p.is_null() {
+            return Result::Err(fmt::Error);
+        }
+        match unsafe { CStr::from_ptr(p) }.to_str() {
+            Ok(s) => write!(f, "{}", s),
+            Err(_) => Result::Err(fmt::Error),
+        }
+    }
+}
+
+impl<'ctx> Drop for Params<'ctx> {
+    fn drop(&mut self) {
+        unsafe { Z3_params_dec_ref(self.ctx.z3_ctx, self.z3_params) };
+    }
+}
@@ -0,0 +1,78 @@
+use std::ffi::CStr;
+use std::fmt;
+use z3_sys::*;
+use Ast;
+use Context;
+use Pattern;
+
+impl<'ctx> Pattern<'ctx> {
+    /// Create a pattern for quantifier instantiation.
+    ///
+    /// Z3 uses pattern matching to instantiate quantifiers. If a
+    /// pattern is not provided for a quantifier, then Z3 will
+    /// automatically compute a set of patterns for it. However, for
+    /// optimal performance, the user should provide the patterns.
+    ///
+    /// Patterns comprise a list of terms. The list should be
+    /// non-empty. If the list comprises of more than one term, it is
+    /// a called a multi-pattern.
+    ///
+    /// In general, one can pass in a list of (multi-)patterns in the
+    /// quantifier constructor.
+    ///
+    /// # See also:
+    ///
+    /// - [`Ast::forall_const()`](struct.Ast.html#method.forall_const)
+    /// - [`Ast::exists_const()`](struct.Ast.html#method.exists_const)
+    pub fn new(ctx: &'ctx Context, terms: &[&Ast<'ctx>]) -> Pattern<'ctx> {
+        assert!(!terms.is_empty());
+        let terms: Vec<Z3_ast> = terms.iter().map(|t| t.z3_ast).collect();
+        Pattern {
+            ctx,
+            z3_patt: unsafe {
+                let p = Z3_mk_pattern(ctx.z3_ctx, terms.len() as u32, terms.as_ptr());
+                Z3_inc_ref(ctx.z3_ctx, Z3_pattern_to_ast(ctx.z3_ctx, p));
+                p
+            },
+        }
+    }
+}
+
+impl<'ctx> fmt::Display for Pattern<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let p = unsafe { Z3_pattern_to_string(self.ctx.z3_ctx, self.z3_patt) };
+        if p.is_null() {
+            return Result::Err(fmt::Error);
+        }
+        match unsafe { CStr::from_ptr(p) }.to_str() {
+            Ok(s) => write!(f, "{}", s),
+            Err(_) => Result::Err(fmt::Error),
+        }
+    }
+}
+
+impl<'ctx> Clone for Pattern<'ctx> {
+    fn clone(&self) -> Pattern<'ctx> {
+        Pattern {
+            ctx: self.ctx,
+            z3_patt: unsafe {
+                Z3_inc_ref(
+                    self.ctx.z3_ctx,
+                    Z3_pattern_to_ast(self.ctx.z3_ctx, self.z3_patt),
+                );
+                self.z3_patt
+            },
+        }
+    }
+}
+
+impl<'ctx> Drop for Pattern<'ctx> {
+    fn drop(&mut self) {
+        unsafe {
+            Z3_dec_ref(
+                self.ctx.z3_ctx,
+                Z3_pattern_to_ast(self.ctx.z3_ctx, self.z3_patt),
+            )
+        };
+    }
+}
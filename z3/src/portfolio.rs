@@ -0,0 +1,164 @@
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+use Ast;
+use Config;
+use Context;
+use ContextHandle;
+use Logic;
+use Portfolio;
+use PortfolioResult;
+use SatResult;
+use Solver;
+use Tactic;
+
+/// A solver checked by one of the portfolio's worker threads.
+///
+/// Contexts cannot be shared between threads, but each worker gets a
+/// solver in a context of its own, which nothing else touches until the
+/// worker has been joined. Other threads only interrupt it through a
+/// `ContextHandle`.
+struct WorkerSolver<'a, 'c: 'a>(&'a Solver<'c>);
+
+unsafe impl<'a, 'c> Send for WorkerSolver<'a, 'c> {}
+
+impl<'a, 'c> WorkerSolver<'a, 'c> {
+    fn check(&self) -> SatResult {
+        self.0.check()
+    }
+}
+
+impl<'a> Portfolio<'a> {
+    /// Create a portfolio without any strategies.
+    pub fn new() -> Portfolio<'a> {
+        Portfolio { strategies: vec![] }
+    }
+
+    /// Add a strategy that builds the solver to check with in the given
+    /// context.
+    pub fn strategy<F>(mut self, f: F) -> Portfolio<'a>
+    where
+        F: for<'c> Fn(&'c Context) -> Solver<'c> + Sync + 'a,
+    {
+        self.strategies.push(Box::new(f));
+        self
+    }
+
+    /// Add a strategy that checks with a solver built from the tactic
+    /// `name`.
+    ///
+    /// # See also:
+    ///
+    /// - [`Solver::from_tactic()`](struct.Solver.html#method.from_tactic)
+    pub fn tactic(self, name: &str) -> Portfolio<'a> {
+        let name = name.to_owned();
+        self.strategy(move |ctx| Solver::from_tactic(&Tactic::new(ctx, &name)))
+    }
+
+    /// Add a strategy that checks with a solver tuned for `logic`.
+    ///
+    /// # See also:
+    ///
+    /// - [`Solver::new_for_logic()`](struct.Solver.html#method.new_for_logic)
+    pub fn logic(self, logic: Logic) -> Portfolio<'a> {
+        self.strategy(move |ctx| Solver::new_for_logic(ctx, logic))
+    }
+
+    /// Return the number of strategies in the portfolio.
+    pub fn len(&self) -> usize {
+        self.strategies.len()
+    }
+
+    /// Return `true` if the portfolio has no strategies.
+    pub fn is_empty(&self) -> bool {
+        self.strategies.is_empty()
+    }
+
+    /// Check the assertions of `solver` with every strategy in parallel,
+    /// each in a new context created from `cfg`.
+    ///
+    /// Returns as soon as one strategy has found the assertions
+    /// satisfiable or unsatisfiable, after interrupting the others. If
+    /// every strategy gives up, the result is `SatResult::Unknown`.
+    ///
+    /// Only the assertions of `solver` are checked; its parameters,
+    /// scopes and tactic play no part.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the portfolio has no strategies, or if a strategy
+    /// panics.
+    pub fn check<'ctx>(&self, cfg: &Config, solver: &Solver<'ctx>) -> PortfolioResult<'ctx> {
+        assert!(!self.is_empty(), "portfolio has no strategies");
+        let assertions: Vec<Ast<'ctx>> = solver.assertions().collect();
+        let contexts: Vec<Context> = self.strategies.iter().map(|_| Context::new(cfg)).collect();
+        let solvers: Vec<Solver> = self
+            .strategies
+            .iter()
+            .zip(&contexts)
+            .map(|(strategy, ctx)| {
+                let s = strategy(ctx);
+                for a in &assertions {
+                    s.assert(&a.translate(ctx));
+                }
+                s
+            })
+            .collect();
+        let handles: Vec<ContextHandle> = contexts.iter().map(Context::handle).collect();
+
+        let mut finished = vec![false; solvers.len()];
+        let mut winner = None;
+        let mut result = SatResult::Unknown;
+        thread::scope(|scope| {
+            let (tx, rx) = mpsc::channel();
+            for (i, s) in solvers.iter().enumerate() {
+                let tx = tx.clone();
+                let s = WorkerSolver(s);
+                scope.spawn(move || tx.send((i, s.check())));
+            }
+            drop(tx);
+
+            let mut pending = solvers.len();
+            while pending > 0 {
+                match rx.recv_timeout(Duration::from_millis(10)) {
+                    Ok((i, r)) => {
+                        finished[i] = true;
+                        pending -= 1;
+                        if winner.is_none() && r != SatResult::Unknown {
+                            winner = Some(i);
+                            result = r;
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    // A worker panicked; the scope resumes the panic.
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+                // An interrupt only stops a check that is already running,
+                // so keep interrupting until every worker is done.
+                if winner.is_some() {
+                    for (h, _) in handles.iter().zip(&finished).filter(|&(_, &f)| !f) {
+                        h.interrupt();
+                    }
+                }
+            }
+        });
+
+        let model = match winner {
+            Some(i) if result == SatResult::Sat => {
+                solvers[i].get_model().ok().map(|m| m.translate(solver.ctx))
+            }
+            _ => None,
+        };
+        PortfolioResult {
+            result,
+            winner,
+            model,
+        }
+    }
+}
+
+impl<'a> Default for Portfolio<'a> {
+    fn default() -> Portfolio<'a> {
+        Portfolio::new()
+    }
+}
@@ -0,0 +1,136 @@
+use std::ffi::{CStr, CString};
+use z3_sys::*;
+use Context;
+use Goal;
+use Probe;
+
+macro_rules! probe_binop {
+    ( $f:ident, $z3fn:ident, $doc:expr ) => {
+        #[doc = $doc]
+        pub fn $f(&self, other: &Probe<'ctx>) -> Probe<'ctx> {
+            unsafe {
+                let p = $z3fn(self.ctx.z3_ctx, self.z3_probe, other.z3_probe);
+                Probe::from_raw(self.ctx, p)
+            }
+        }
+    };
+}
+
+impl<'ctx> Probe<'ctx> {
+    /// Return the names of all built-in probes.
+    pub fn list_all(ctx: &'ctx Context) -> Vec<String> {
+        unsafe {
+            let n = Z3_get_num_probes(ctx.z3_ctx);
+            (0..n)
+                .map(|i| {
+                    let p = Z3_get_probe_name(ctx.z3_ctx, i);
+                    CStr::from_ptr(p).to_string_lossy().into_owned()
+                })
+                .collect()
+        }
+    }
+
+    /// Create the built-in probe called `name`, e.g. `num-consts` or
+    /// `is-propositional`.
+    ///
+    /// # Panics
+    ///
+    /// If there is no probe called `name`.
+    ///
+    /// # See also:
+    ///
+    /// - [`Probe::list_all()`](#method.list_all)
+    pub fn new(ctx: &'ctx Context, name: &str) -> Probe<'ctx> {
+        assert!(
+            Probe::list_all(ctx).iter().any(|n| n == name),
+            "unknown probe: {}",
+            name
+        );
+        let name = CString::new(name).unwrap();
+        unsafe { Probe::from_raw(ctx, Z3_mk_probe(ctx.z3_ctx, name.as_ptr())) }
+    }
+
+    /// Wrap a probe returned by Z3.
+    unsafe fn from_raw(ctx: &'ctx Context, p: Z3_probe) -> Probe<'ctx> {
+        ctx.check_null(p);
+        Z3_probe_inc_ref(ctx.z3_ctx, p);
+        Probe { ctx, z3_probe: p }
+    }
+
+    /// Return a probe that always evaluates to `val`.
+    pub fn constant(ctx: &'ctx Context, val: f64) -> Probe<'ctx> {
+        unsafe { Probe::from_raw(ctx, Z3_probe_const(ctx.z3_ctx, val)) }
+    }
+
+    /// Evaluate the probe on `goal`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let goal = Goal::new(&ctx, true, false, false);
+    /// goal.assert(&ctx.named_bool_const("p"));
+    ///
+    /// assert_eq!(Probe::new(&ctx, "is-propositional").apply(&goal), 1.0);
+    /// assert_eq!(Probe::new(&ctx, "size").apply(&goal), 1.0);
+    /// ```
+    pub fn apply(&self, goal: &Goal<'ctx>) -> f64 {
+        unsafe { Z3_probe_apply(self.ctx.z3_ctx, self.z3_probe, goal.z3_goal) }
+    }
+
+    probe_binop!(
+        lt,
+        Z3_probe_lt,
+        "Return a probe that is true when `self` is less than `other`."
+    );
+    probe_binop!(
+        gt,
+        Z3_probe_gt,
+        "Return a probe that is true when `self` is greater than `other`."
+    );
+    probe_binop!(
+        le,
+        Z3_probe_le,
+        "Return a probe that is true when `self` is less than or equal to `other`."
+    );
+    probe_binop!(
+        ge,
+        Z3_probe_ge,
+        "Return a probe that is true when `self` is greater than or equal to `other`."
+    );
+    probe_binop!(
+        _eq,
+        Z3_probe_eq,
+        "Return a probe that is true when `self` is equal to `other`."
+    );
+    probe_binop!(
+        and,
+        Z3_probe_and,
+        "Return a probe that is true when both `self` and `other` are."
+    );
+    probe_binop!(
+        or,
+        Z3_probe_or,
+        "Return a probe that is true when `self` or `other` is."
+    );
+
+    /// Return a probe that is true when `self` is false.
+    pub fn not(&self) -> Probe<'ctx> {
+        unsafe { Probe::from_raw(self.ctx, Z3_probe_not(self.ctx.z3_ctx, self.z3_probe)) }
+    }
+}
+
+impl<'ctx> Clone for Probe<'ctx> {
+    fn clone(&self) -> Probe<'ctx> {
+        unsafe { Probe::from_raw(self.ctx, self.z3_probe) }
+    }
+}
+
+impl<'ctx> Drop for Probe<'ctx> {
+    fn drop(&mut self) {
+        unsafe { Z3_probe_dec_ref(self.ctx.z3_ctx, self.z3_probe) };
+    }
+}
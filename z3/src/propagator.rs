@@ -0,0 +1,202 @@
+use std::any::Any;
+use std::os::raw::{c_uint, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::process;
+use z3_sys::*;
+use Ast;
+use Context;
+use PropagatorCallback;
+use Z3Error;
+
+/// A custom theory plugged into a [`Solver`](struct.Solver.html) with
+/// [`Solver::set_user_propagator()`].
+///
+/// The solver reports to the propagator when expressions registered with
+/// [`Solver::propagate_register()`] are assigned values, or become equal
+/// or disequal to each other. Each expression is identified by the id
+/// that registering it returned. The propagator reacts by propagating
+/// consequences or conflicts through the
+/// [`PropagatorCallback`](struct.PropagatorCallback.html) it is passed.
+///
+/// All assignments made after a [`push()`](#tymethod.push) are undone by
+/// the matching [`pop()`](#tymethod.pop), so a propagator that keeps
+/// track of them must backtrack its own state accordingly.
+///
+/// A panic in a callback interrupts the search and is resumed once the
+/// check returns.
+///
+/// [`Solver::set_user_propagator()`]: struct.Solver.html#method.set_user_propagator
+/// [`Solver::propagate_register()`]: struct.Solver.html#method.propagate_register
+pub trait UserPropagator<'ctx> {
+    /// Create a backtracking point.
+    fn push(&mut self);
+
+    /// Backtrack `num_scopes` backtracking points.
+    fn pop(&mut self, num_scopes: u32);
+
+    /// The registered expression `id` has been assigned `value`, which
+    /// is `true` or `false` for Booleans and a numeral for bit-vectors.
+    fn fixed(&mut self, cb: &PropagatorCallback<'ctx>, id: u32, value: &Ast<'ctx>) {}
+
+    /// The registered expressions `x` and `y` have become equal.
+    fn eq(&mut self, cb: &PropagatorCallback<'ctx>, x: u32, y: u32) {}
+
+    /// The registered expressions `x` and `y` have become disequal.
+    fn diseq(&mut self, cb: &PropagatorCallback<'ctx>, x: u32, y: u32) {}
+
+    /// The solver has found a candidate model. Propagating anything here
+    /// makes the search continue; otherwise the model is accepted.
+    fn final_check(&mut self, cb: &PropagatorCallback<'ctx>) {}
+}
+
+impl<'ctx> PropagatorCallback<'ctx> {
+    /// Propagate `consequence`, which follows from the values of the
+    /// registered expressions in `fixed` and the equalities `eqs`
+    /// between registered expressions.
+    pub fn propagate(&self, fixed: &[u32], eqs: &[(u32, u32)], consequence: &Ast<'ctx>) {
+        let lhs: Vec<c_uint> = eqs.iter().map(|&(x, _)| x).collect();
+        let rhs: Vec<c_uint> = eqs.iter().map(|&(_, y)| y).collect();
+        unsafe {
+            Z3_solver_propagate_consequence(
+                self.ctx.z3_ctx,
+                self.z3_cb,
+                fixed.len() as u32,
+                fixed.as_ptr(),
+                eqs.len() as u32,
+                lhs.as_ptr(),
+                rhs.as_ptr(),
+                consequence.z3_ast,
+            )
+        };
+    }
+
+    /// Report that the values of the registered expressions in `fixed`
+    /// and the equalities `eqs` cannot hold together.
+    pub fn conflict(&self, fixed: &[u32], eqs: &[(u32, u32)]) {
+        self.propagate(fixed, eqs, &self.ctx.from_bool(false));
+    }
+}
+
+/// A propagator installed on a solver, together with the context it
+/// uses and a panic raised by one of its callbacks.
+struct PropagatorState<'ctx> {
+    ctx: &'ctx Context,
+    propagator: Box<dyn UserPropagator<'ctx> + 'ctx>,
+    panic: Option<Box<dyn Any + Send>>,
+}
+
+impl<'ctx> PropagatorState<'ctx> {
+    /// Run a callback of the propagator. After a panic the search is
+    /// interrupted and further callbacks are skipped.
+    fn run<F>(&mut self, f: F)
+    where
+        F: FnOnce(&'ctx Context, &mut (dyn UserPropagator<'ctx> + 'ctx)),
+    {
+        if self.panic.is_some() {
+            return;
+        }
+        let ctx = self.ctx;
+        let propagator = &mut *self.propagator;
+        if let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| f(ctx, propagator))) {
+            self.panic = Some(payload);
+            ctx.interrupt();
+        }
+    }
+}
+
+/// Owner of the state of a propagator installed on a solver, which is
+/// the user context Z3 passes to the callbacks.
+///
+/// The state is kept behind an untyped pointer so that `Solver` stays
+/// covariant in its lifetime. This is sound because propagators are
+/// installed through a `&mut Solver<'ctx>`, whose lifetime cannot have
+/// been shortened, and the state is dropped with the solver.
+pub(crate) struct InstalledPropagator {
+    state: *mut c_void,
+}
+
+impl InstalledPropagator {
+    /// Install `propagator` on `slv`, failing if the solver does not
+    /// support user propagators. The caller must drop the result only after
+    /// the solver.
+    pub(crate) unsafe fn install<'ctx, P>(
+        ctx: &'ctx Context,
+        slv: Z3_solver,
+        propagator: P,
+    ) -> Result<InstalledPropagator, Z3Error>
+    where
+        P: UserPropagator<'ctx> + 'ctx,
+    {
+        let installed = InstalledPropagator {
+            state: Box::into_raw(Box::new(PropagatorState {
+                ctx,
+                propagator: Box::new(propagator),
+                panic: None,
+            })) as *mut c_void,
+        };
+        let c = ctx.z3_ctx;
+        let state = installed.state;
+        ctx.try_z3(|| {
+            Z3_solver_propagate_init(c, slv, state, Some(push_eh), Some(pop_eh), Some(fresh_eh))
+        })?;
+        Z3_solver_propagate_fixed(c, slv, Some(fixed_eh));
+        Z3_solver_propagate_eq(c, slv, Some(eq_eh));
+        Z3_solver_propagate_diseq(c, slv, Some(diseq_eh));
+        Z3_solver_propagate_final(c, slv, Some(final_eh));
+        Ok(installed)
+    }
+
+    /// Return the panic raised by a callback during the last search.
+    pub(crate) fn take_panic(&mut self) -> Option<Box<dyn Any + Send>> {
+        unsafe { state(self.state).panic.take() }
+    }
+}
+
+impl Drop for InstalledPropagator {
+    fn drop(&mut self) {
+        unsafe { drop(Box::from_raw(self.state as *mut PropagatorState)) };
+    }
+}
+
+/// Recover the state from the user context passed to a callback. The
+/// caller picks `'ctx`, which must be the lifetime the propagator was
+/// installed with, and must not keep the reference past the callback.
+unsafe fn state<'a, 'ctx>(ctx: *mut c_void) -> &'a mut PropagatorState<'ctx> {
+    &mut *(ctx as *mut PropagatorState<'ctx>)
+}
+
+unsafe extern "C" fn push_eh(ctx: *mut c_void) {
+    state(ctx).run(|_, p| p.push());
+}
+
+unsafe extern "C" fn pop_eh(ctx: *mut c_void, num_scopes: c_uint) {
+    state(ctx).run(|_, p| p.pop(num_scopes));
+}
+
+// Z3 copies the propagator when it copies the solver into another
+// context, which a Rust propagator cannot follow. Z3 expects a new user
+// context back and there is no way to report failure, nor to unwind
+// through Z3, so abort.
+unsafe extern "C" fn fresh_eh(ctx: *mut c_void, new_context: Z3_context) -> *mut c_void {
+    eprintln!("a solver with a user propagator cannot be copied");
+    process::abort();
+}
+
+unsafe extern "C" fn fixed_eh(ctx: *mut c_void, cb: Z3_solver_callback, id: c_uint, value: Z3_ast) {
+    state(ctx).run(|ctx, p| {
+        let cb = PropagatorCallback { ctx, z3_cb: cb };
+        p.fixed(&cb, id, &Ast::new(ctx, value))
+    });
+}
+
+unsafe extern "C" fn eq_eh(ctx: *mut c_void, cb: Z3_solver_callback, x: c_uint, y: c_uint) {
+    state(ctx).run(|ctx, p| p.eq(&PropagatorCallback { ctx, z3_cb: cb }, x, y));
+}
+
+unsafe extern "C" fn diseq_eh(ctx: *mut c_void, cb: Z3_solver_callback, x: c_uint, y: c_uint) {
+    state(ctx).run(|ctx, p| p.diseq(&PropagatorCallback { ctx, z3_cb: cb }, x, y));
+}
+
+unsafe extern "C" fn final_eh(ctx: *mut c_void, cb: Z3_solver_callback) {
+    state(ctx).run(|ctx, p| p.final_check(&PropagatorCallback { ctx, z3_cb: cb }));
+}
@@ -0,0 +1,137 @@
+use std::fmt;
+use std::ops::Deref;
+use z3_sys::*;
+use Ast;
+use Context;
+use Regexp;
+use Seq;
+use Sort;
+
+macro_rules! unop {
+    ( $f:ident, $z3fn:ident ) => {
+        pub fn $f(&self) -> Regexp<'ctx> {
+            Regexp::wrap(self.ctx, unsafe { $z3fn(self.ctx.z3_ctx, self.z3_ast) })
+        }
+    };
+}
+
+macro_rules! varop {
+    ( $f:ident, $z3fn:ident ) => {
+        pub fn $f(&self, other: &[&Regexp<'ctx>]) -> Regexp<'ctx> {
+            let mut tmp = vec![self.z3_ast];
+            for a in other {
+                tmp.push(a.z3_ast)
+            }
+            Regexp::wrap(self.ctx, unsafe {
+                $z3fn(self.ctx.z3_ctx, tmp.len() as u32, tmp.as_ptr())
+            })
+        }
+    };
+}
+
+impl<'ctx> Regexp<'ctx> {
+    fn wrap(ctx: &'ctx Context, ast: Z3_ast) -> Regexp<'ctx> {
+        Regexp {
+            ast: Ast::new(ctx, ast),
+        }
+    }
+
+    /// Wrap an `Ast` as a `Regexp`, returning `None` if it does not have
+    /// regular expression sort.
+    pub fn from_ast(ast: Ast<'ctx>) -> Option<Regexp<'ctx>> {
+        if ast.sort_kind() == SortKind::RE {
+            Some(Regexp { ast })
+        } else {
+            None
+        }
+    }
+
+    /// Create the regular expression that accepts exactly the sequence
+    /// `seq`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    ///
+    /// // (ab)+c?
+    /// let re = Regexp::literal(&Seq::from_string(&ctx, "ab"))
+    ///     .plus()
+    ///     .concat(&[&Regexp::literal(&Seq::from_string(&ctx, "c")).option()]);
+    ///
+    /// let s = Seq::new_string_const(&ctx, "s");
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&s.in_re(&re));
+    /// solver.assert(&s.length()._eq(&ctx.from_i64(5)));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    ///
+    /// let model = solver.get_model().unwrap();
+    /// let s = Seq::from_ast(model.eval(&s, true).unwrap()).unwrap();
+    /// assert_eq!(s.as_string().unwrap(), "ababc");
+    /// ```
+    pub fn literal(seq: &Seq<'ctx>) -> Regexp<'ctx> {
+        Regexp::wrap(seq.ctx, unsafe {
+            Z3_mk_seq_to_re(seq.ctx.z3_ctx, seq.z3_ast)
+        })
+    }
+
+    /// Create the regular expression accepting any single character
+    /// between `lo` and `hi` (inclusive), which must both be sequences
+    /// of length one.
+    pub fn range(lo: &Seq<'ctx>, hi: &Seq<'ctx>) -> Regexp<'ctx> {
+        Regexp::wrap(lo.ctx, unsafe {
+            Z3_mk_re_range(lo.ctx.z3_ctx, lo.z3_ast, hi.z3_ast)
+        })
+    }
+
+    /// Create the regular expression of sort `sort` accepting no
+    /// sequence at all.
+    pub fn empty(ctx: &'ctx Context, sort: &Sort<'ctx>) -> Regexp<'ctx> {
+        Regexp::wrap(ctx, unsafe { Z3_mk_re_empty(ctx.z3_ctx, sort.z3_sort) })
+    }
+
+    /// Create the regular expression of sort `sort` accepting every
+    /// sequence.
+    pub fn full(ctx: &'ctx Context, sort: &Sort<'ctx>) -> Regexp<'ctx> {
+        Regexp::wrap(ctx, unsafe { Z3_mk_re_full(ctx.z3_ctx, sort.z3_sort) })
+    }
+
+    unop!(plus, Z3_mk_re_plus);
+    unop!(star, Z3_mk_re_star);
+    unop!(option, Z3_mk_re_option);
+    unop!(complement, Z3_mk_re_complement);
+    varop!(union, Z3_mk_re_union);
+    varop!(concat, Z3_mk_re_concat);
+    varop!(intersect, Z3_mk_re_intersect);
+
+    /// Repeat `self` between `lo` and `hi` times (`re.loop`). If `hi` is
+    /// 0, the number of repetitions is unbounded above.
+    pub fn repeat(&self, lo: u32, hi: u32) -> Regexp<'ctx> {
+        Regexp::wrap(self.ctx, unsafe {
+            Z3_mk_re_loop(self.ctx.z3_ctx, self.z3_ast, lo, hi)
+        })
+    }
+}
+
+impl<'ctx> Deref for Regexp<'ctx> {
+    type Target = Ast<'ctx>;
+
+    fn deref(&self) -> &Ast<'ctx> {
+        &self.ast
+    }
+}
+
+impl<'ctx> From<Regexp<'ctx>> for Ast<'ctx> {
+    fn from(re: Regexp<'ctx>) -> Ast<'ctx> {
+        re.ast
+    }
+}
+
+impl<'ctx> fmt::Display for Regexp<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt::Display::fmt(&self.ast, f)
+    }
+}
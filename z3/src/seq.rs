@@ -0,0 +1,211 @@
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::ops::Deref;
+use z3_sys::*;
+use Ast;
+use Context;
+use Regexp;
+use Seq;
+use Sort;
+
+impl<'ctx> Seq<'ctx> {
+    fn wrap(ctx: &'ctx Context, ast: Z3_ast) -> Seq<'ctx> {
+        Seq {
+            ast: Ast::new(ctx, ast),
+        }
+    }
+
+    /// Wrap an `Ast` as a `Seq`, returning `None` if it does not have
+    /// sequence sort.
+    pub fn from_ast(ast: Ast<'ctx>) -> Option<Seq<'ctx>> {
+        if ast.sort_kind() == SortKind::Seq {
+            Some(Seq { ast })
+        } else {
+            None
+        }
+    }
+
+    /// Create a sequence constant named `name` whose elements have sort
+    /// `elt`.
+    pub fn new_const(ctx: &'ctx Context, name: &str, elt: &Sort<'ctx>) -> Seq<'ctx> {
+        let sort = Sort::seq(ctx, elt);
+        Seq {
+            ast: Ast::new_const(&ctx.str_sym(name), &sort),
+        }
+    }
+
+    /// Create a string constant named `name`.
+    pub fn new_string_const(ctx: &'ctx Context, name: &str) -> Seq<'ctx> {
+        let sort = Sort::string(ctx);
+        Seq {
+            ast: Ast::new_const(&ctx.str_sym(name), &sort),
+        }
+    }
+
+    /// Create a string literal.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    ///
+    /// let s = Seq::new_string_const(&ctx, "s");
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&s.concat(&[&Seq::from_string(&ctx, "!")])
+    ///     ._eq(&Seq::from_string(&ctx, "hello!")));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    ///
+    /// let model = solver.get_model().unwrap();
+    /// let s = Seq::from_ast(model.eval(&s, true).unwrap()).unwrap();
+    /// assert_eq!(s.as_string().unwrap(), "hello");
+    /// ```
+    pub fn from_string(ctx: &'ctx Context, s: &str) -> Seq<'ctx> {
+        let ss = CString::new(s).unwrap();
+        Seq::wrap(ctx, unsafe { Z3_mk_string(ctx.z3_ctx, ss.as_ptr()) })
+    }
+
+    /// Create the empty sequence of sequence sort `sort`.
+    pub fn empty(ctx: &'ctx Context, sort: &Sort<'ctx>) -> Seq<'ctx> {
+        Seq::wrap(ctx, unsafe { Z3_mk_seq_empty(ctx.z3_ctx, sort.z3_sort) })
+    }
+
+    /// Create the sequence of length one containing `elt`.
+    pub fn unit(elt: &Ast<'ctx>) -> Seq<'ctx> {
+        Seq::wrap(elt.ctx, unsafe {
+            Z3_mk_seq_unit(elt.ctx.z3_ctx, elt.z3_ast)
+        })
+    }
+
+    /// Convert an integer expression to its decimal string representation
+    /// (`int.to.str`). Negative integers are mapped to the empty string.
+    pub fn from_int(i: &Ast<'ctx>) -> Seq<'ctx> {
+        Seq::wrap(i.ctx, unsafe { Z3_mk_int_to_str(i.ctx.z3_ctx, i.z3_ast) })
+    }
+
+    /// Return the value of a string literal, or `None` if `self` is
+    /// not a string literal.
+    ///
+    /// Non-printable characters are escaped as in SMT-LIB2.
+    pub fn as_string(&self) -> Option<String> {
+        unsafe {
+            if !Z3_is_string(self.ctx.z3_ctx, self.z3_ast) {
+                return None;
+            }
+            let p = Z3_get_string(self.ctx.z3_ctx, self.z3_ast);
+            if p.is_null() {
+                return None;
+            }
+            CStr::from_ptr(p).to_str().ok().map(|s| s.to_owned())
+        }
+    }
+
+    /// Concatenate `self` with `others`.
+    pub fn concat(&self, others: &[&Seq<'ctx>]) -> Seq<'ctx> {
+        let mut tmp = vec![self.z3_ast];
+        for s in others {
+            tmp.push(s.z3_ast)
+        }
+        Seq::wrap(self.ctx, unsafe {
+            Z3_mk_seq_concat(self.ctx.z3_ctx, tmp.len() as u32, tmp.as_ptr())
+        })
+    }
+
+    /// Return the integer length of the sequence.
+    pub fn length(&self) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_seq_length(self.ctx.z3_ctx, self.z3_ast)
+        })
+    }
+
+    /// Convert a string of digits to the integer it denotes
+    /// (`str.to.int`). Strings that are not numerals map to -1.
+    pub fn to_int(&self) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_str_to_int(self.ctx.z3_ctx, self.z3_ast)
+        })
+    }
+
+    /// Extract the subsequence of (at most) `length` elements starting
+    /// at `offset`.
+    pub fn substr(&self, offset: &Ast<'ctx>, length: &Ast<'ctx>) -> Seq<'ctx> {
+        Seq::wrap(self.ctx, unsafe {
+            Z3_mk_seq_extract(self.ctx.z3_ctx, self.z3_ast, offset.z3_ast, length.z3_ast)
+        })
+    }
+
+    /// Return the unit sequence found at `index`, or the empty sequence
+    /// if `index` is out of bounds.
+    pub fn at(&self, index: &Ast<'ctx>) -> Seq<'ctx> {
+        Seq::wrap(self.ctx, unsafe {
+            Z3_mk_seq_at(self.ctx.z3_ctx, self.z3_ast, index.z3_ast)
+        })
+    }
+
+    /// Check whether `self` contains `other` as a subsequence.
+    pub fn contains(&self, other: &Seq<'ctx>) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_seq_contains(self.ctx.z3_ctx, self.z3_ast, other.z3_ast)
+        })
+    }
+
+    /// Check whether `prefix` is a prefix of `self`.
+    pub fn starts_with(&self, prefix: &Seq<'ctx>) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_seq_prefix(self.ctx.z3_ctx, prefix.z3_ast, self.z3_ast)
+        })
+    }
+
+    /// Check whether `suffix` is a suffix of `self`.
+    pub fn ends_with(&self, suffix: &Seq<'ctx>) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_seq_suffix(self.ctx.z3_ctx, suffix.z3_ast, self.z3_ast)
+        })
+    }
+
+    /// Return the index of the first occurrence of `substr` in `self`,
+    /// starting the search at `offset`. The result is -1 if there is no
+    /// such occurrence.
+    pub fn index_of(&self, substr: &Seq<'ctx>, offset: &Ast<'ctx>) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_seq_index(self.ctx.z3_ctx, self.z3_ast, substr.z3_ast, offset.z3_ast)
+        })
+    }
+
+    /// Check whether `self` is in the language generated by the regular
+    /// expression `re`.
+    pub fn in_re(&self, re: &Regexp<'ctx>) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_seq_in_re(self.ctx.z3_ctx, self.z3_ast, re.z3_ast)
+        })
+    }
+
+    /// Replace the first occurrence of `src` in `self` with `dst`.
+    pub fn replace(&self, src: &Seq<'ctx>, dst: &Seq<'ctx>) -> Seq<'ctx> {
+        Seq::wrap(self.ctx, unsafe {
+            Z3_mk_seq_replace(self.ctx.z3_ctx, self.z3_ast, src.z3_ast, dst.z3_ast)
+        })
+    }
+}
+
+impl<'ctx> Deref for Seq<'ctx> {
+    type Target = Ast<'ctx>;
+
+    fn deref(&self) -> &Ast<'ctx> {
+        &self.ast
+    }
+}
+
+impl<'ctx> From<Seq<'ctx>> for Ast<'ctx> {
+    fn from(seq: Seq<'ctx>) -> Ast<'ctx> {
+        seq.ast
+    }
+}
+
+impl<'ctx> fmt::Display for Seq<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt::Display::fmt(&self.ast, f)
+    }
+}
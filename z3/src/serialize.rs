@@ -0,0 +1,77 @@
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::ffi::CStr;
+use z3_sys::*;
+use Ast;
+use Model;
+use ModelValue;
+
+impl<'ctx> Ast<'ctx> {
+    /// Return the value of a constant assigned by a model.
+    fn model_value(&self) -> ModelValue {
+        let value = match self.sort_kind() {
+            SortKind::Bool => self.as_bool().map(ModelValue::Bool),
+            SortKind::Int => self.numeral_string().map(ModelValue::Int),
+            SortKind::Real => self.numeral_string().map(ModelValue::Real),
+            SortKind::BV => self.numeral_string().map(|value| {
+                let width = unsafe {
+                    let s = Z3_get_sort(self.ctx.z3_ctx, self.z3_ast);
+                    Z3_get_bv_sort_size(self.ctx.z3_ctx, s)
+                };
+                ModelValue::BitVec { width, value }
+            }),
+            SortKind::Seq => unsafe {
+                if Z3_is_string(self.ctx.z3_ctx, self.z3_ast) {
+                    let p = Z3_get_string(self.ctx.z3_ctx, self.z3_ast);
+                    let s = CStr::from_ptr(p).to_string_lossy().into_owned();
+                    Some(ModelValue::String(s))
+                } else {
+                    None
+                }
+            },
+            _ => None,
+        };
+        value.unwrap_or_else(|| ModelValue::Other(self.to_string()))
+    }
+}
+
+impl<'ctx> Model<'ctx> {
+    /// Return the values of the constants assigned by the model, by
+    /// name.
+    ///
+    /// Requires the `serde` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_int_const("x");
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&x._eq(&ctx.from_i64(-12)));
+    /// solver.check();
+    ///
+    /// let values = solver.get_model().unwrap().values();
+    /// assert_eq!(values["x"], ModelValue::Int("-12".to_owned()));
+    /// ```
+    pub fn values(&self) -> BTreeMap<String, ModelValue> {
+        self.const_decls()
+            .iter()
+            .filter_map(|decl| {
+                let value = self.get_const_interp(decl)?;
+                Some((decl.name(), value.model_value()))
+            })
+            .collect()
+    }
+}
+
+/// A model is serialized as a map from the names of its constants to
+/// their [values](enum.ModelValue.html). Interpretations of functions
+/// are left out.
+impl<'ctx> Serialize for Model<'ctx> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.values().serialize(serializer)
+    }
+}
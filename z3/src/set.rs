@@ -0,0 +1,149 @@
+use std::fmt;
+use std::ops::Deref;
+use z3_sys::*;
+use Ast;
+use Context;
+use Set;
+use Sort;
+
+macro_rules! varop {
+    ( $f:ident, $z3fn:ident ) => {
+        pub fn $f(&self, other: &[&Set<'ctx>]) -> Set<'ctx> {
+            let mut tmp = vec![self.z3_ast];
+            for a in other {
+                tmp.push(a.z3_ast)
+            }
+            Set::wrap(self.ctx, unsafe {
+                $z3fn(self.ctx.z3_ctx, tmp.len() as u32, tmp.as_ptr())
+            })
+        }
+    };
+}
+
+impl<'ctx> Set<'ctx> {
+    fn wrap(ctx: &'ctx Context, ast: Z3_ast) -> Set<'ctx> {
+        Set {
+            ast: Ast::new(ctx, ast),
+        }
+    }
+
+    /// Wrap an `Ast` as a `Set`, returning `None` if it is not an array
+    /// with `Bool` range.
+    pub fn from_ast(ast: Ast<'ctx>) -> Option<Set<'ctx>> {
+        if ast.sort_kind() != SortKind::Array {
+            return None;
+        }
+        let range_kind = unsafe {
+            let s = Z3_get_sort(ast.ctx.z3_ctx, ast.z3_ast);
+            let r = Z3_get_array_sort_range(ast.ctx.z3_ctx, s);
+            Z3_get_sort_kind(ast.ctx.z3_ctx, r)
+        };
+        if range_kind == SortKind::Bool {
+            Some(Set { ast })
+        } else {
+            None
+        }
+    }
+
+    /// Create a set constant named `name` with elements of sort `elt`.
+    pub fn new_const(ctx: &'ctx Context, name: &str, elt: &Sort<'ctx>) -> Set<'ctx> {
+        let sort = Sort::set(ctx, elt);
+        Set {
+            ast: Ast::new_const(&ctx.str_sym(name), &sort),
+        }
+    }
+
+    /// Create the empty set of elements of sort `elt`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    ///
+    /// let one = ctx.from_i64(1);
+    /// let two = ctx.from_i64(2);
+    /// let s = Set::empty(&ctx, &ctx.int_sort()).add(&one).add(&two);
+    /// let t = Set::new_const(&ctx, "t", &ctx.int_sort());
+    ///
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&s.subset(&t));
+    /// solver.assert(&t.difference(&s).member(&two));
+    /// assert_eq!(solver.check(), SatResult::Unsat);
+    /// ```
+    pub fn empty(ctx: &'ctx Context, elt: &Sort<'ctx>) -> Set<'ctx> {
+        Set::wrap(ctx, unsafe { Z3_mk_empty_set(ctx.z3_ctx, elt.z3_sort) })
+    }
+
+    /// Create the set of all elements of sort `elt`.
+    pub fn full(ctx: &'ctx Context, elt: &Sort<'ctx>) -> Set<'ctx> {
+        Set::wrap(ctx, unsafe { Z3_mk_full_set(ctx.z3_ctx, elt.z3_sort) })
+    }
+
+    /// Return the set obtained by adding `elt` to `self`.
+    pub fn add(&self, elt: &Ast<'ctx>) -> Set<'ctx> {
+        Set::wrap(self.ctx, unsafe {
+            Z3_mk_set_add(self.ctx.z3_ctx, self.z3_ast, elt.z3_ast)
+        })
+    }
+
+    /// Return the set obtained by removing `elt` from `self`.
+    pub fn del(&self, elt: &Ast<'ctx>) -> Set<'ctx> {
+        Set::wrap(self.ctx, unsafe {
+            Z3_mk_set_del(self.ctx.z3_ctx, self.z3_ast, elt.z3_ast)
+        })
+    }
+
+    varop!(union, Z3_mk_set_union);
+    varop!(intersect, Z3_mk_set_intersect);
+
+    /// Return the elements of `self` that are not in `other`.
+    pub fn difference(&self, other: &Set<'ctx>) -> Set<'ctx> {
+        Set::wrap(self.ctx, unsafe {
+            Z3_mk_set_difference(self.ctx.z3_ctx, self.z3_ast, other.z3_ast)
+        })
+    }
+
+    /// Return the complement of `self`.
+    pub fn complement(&self) -> Set<'ctx> {
+        Set::wrap(self.ctx, unsafe {
+            Z3_mk_set_complement(self.ctx.z3_ctx, self.z3_ast)
+        })
+    }
+
+    /// Check whether `elt` is a member of `self`.
+    pub fn member(&self, elt: &Ast<'ctx>) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_set_member(self.ctx.z3_ctx, elt.z3_ast, self.z3_ast)
+        })
+    }
+
+    /// Check whether `self` is a subset of `other`.
+    pub fn subset(&self, other: &Set<'ctx>) -> Ast<'ctx> {
+        Ast::new(self.ctx, unsafe {
+            Z3_mk_set_subset(self.ctx.z3_ctx, self.z3_ast, other.z3_ast)
+        })
+    }
+}
+
+impl<'ctx> Deref for Set<'ctx> {
+    type Target = Ast<'ctx>;
+
+    fn deref(&self) -> &Ast<'ctx> {
+        &self.ast
+    }
+}
+
+impl<'ctx> From<Set<'ctx>> for Ast<'ctx> {
+    fn from(set: Set<'ctx>) -> Ast<'ctx> {
+        set.ast
+    }
+}
+
+impl<'ctx> fmt::Display for Set<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        fmt::Display::fmt(&self.ast, f)
+    }
+}
@@ -1,11 +1,26 @@
-use std::ffi::CStr;
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
 use std::fmt;
+use std::panic;
 use z3_sys::*;
 use Ast;
+use AstVector;
+use CheckResult;
 use Context;
+use Cubes;
+#[cfg(feature = "z3_4_13")]
+use InstalledOnClause;
+use InstalledPropagator;
+use Logic;
 use Model;
+use ParamDescrs;
+use Params;
+use SatResult;
 use Solver;
-use Z3_MUTEX;
+use Statistics;
+use Tactic;
+use UserPropagator;
+use Z3Error;
 
 impl<'ctx> Solver<'ctx> {
     /// Create a new solver. This solver is a "combined solver"
@@ -46,15 +61,180 @@ impl<'ctx> Solver<'ctx> {
     /// [`Solver::push()`]: #method.push
     /// [`Solver::reset()`]: #method.reset
     ///
-    pub fn new(ctx: &Context) -> Solver {
+    pub fn new(ctx: &Context) -> Solver<'_> {
         Solver {
             ctx,
             z3_slv: unsafe {
-                let guard = Z3_MUTEX.lock().unwrap();
                 let s = Z3_mk_solver(ctx.z3_ctx);
                 Z3_solver_inc_ref(ctx.z3_ctx, s);
                 s
             },
+            propagator: None,
+            #[cfg(feature = "z3_4_13")]
+            on_clause: None,
+        }
+    }
+
+    /// Create a new incremental SMT solver. Unlike [`Solver::new()`](#method.new)
+    /// this does not preprocess the assertions with Z3's "default"
+    /// tactic before a non-incremental check.
+    pub fn new_simple(ctx: &'ctx Context) -> Solver<'ctx> {
+        Solver {
+            ctx,
+            z3_slv: unsafe {
+                let s = Z3_mk_simple_solver(ctx.z3_ctx);
+                Z3_solver_inc_ref(ctx.z3_ctx, s);
+                s
+            },
+            propagator: None,
+            #[cfg(feature = "z3_4_13")]
+            on_clause: None,
+        }
+    }
+
+    /// Create a solver specialized for `logic`, which is either a
+    /// [`Logic`](enum.Logic.html) or the SMT-LIB name of a logic, such as
+    /// `"QF_BV"`.
+    ///
+    /// The specialized solver is typically much faster than the general
+    /// one, but only accepts assertions in the logic.
+    ///
+    /// # Panics
+    ///
+    /// Panics if Z3 does not recognize the logic.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let solver = Solver::new_for_logic(&ctx, Logic::QF_BV);
+    /// let bv8 = ctx.bitvector_sort(8);
+    /// let x = ctx.named_bitvector_const("x", 8);
+    /// solver.assert(&x.bvmul(&bv8.from_u64(3))._eq(&bv8.from_u64(1)));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    ///
+    /// let by_name = Solver::new_for_logic(&ctx, "QF_LIA");
+    /// assert_eq!(by_name.check(), SatResult::Sat);
+    /// ```
+    pub fn new_for_logic<L: AsRef<str>>(ctx: &'ctx Context, logic: L) -> Solver<'ctx> {
+        let logic = logic.as_ref();
+        let name = CString::new(logic).unwrap();
+        let s = unsafe {
+            let sym = Z3_mk_string_symbol(ctx.z3_ctx, name.as_ptr());
+            let s = ctx.try_z3(|| Z3_mk_solver_for_logic(ctx.z3_ctx, sym));
+            if let Ok(s) = s {
+                Z3_solver_inc_ref(ctx.z3_ctx, s);
+            }
+            s
+        };
+        match s {
+            Ok(z3_slv) => Solver {
+                ctx,
+                z3_slv,
+                propagator: None,
+                #[cfg(feature = "z3_4_13")]
+                on_clause: None,
+            },
+            Err(_) => panic!("unknown logic: {}", logic),
+        }
+    }
+
+    /// Create a solver that uses `tactic` to check satisfiability.
+    ///
+    /// The solver supports [`Solver::push()`](#method.push) and
+    /// [`Solver::pop()`](#method.pop), but it is not incremental: the
+    /// tactic is applied to all assertions on every check.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let mut cfg = Config::new();
+    /// cfg.set_model_generation(true);
+    /// let ctx = Context::new(&cfg);
+    /// let tactic = Tactic::new(&ctx, "simplify")
+    ///     .and_then(&Tactic::new(&ctx, "bit-blast"))
+    ///     .and_then(&Tactic::new(&ctx, "sat"));
+    /// let solver = Solver::from_tactic(&tactic);
+    ///
+    /// let bv8 = ctx.bitvector_sort(8);
+    /// let x = ctx.named_bitvector_const("x", 8);
+    /// solver.assert(&x.bvadd(&x)._eq(&bv8.from_u64(6)));
+    /// solver.assert(&x.bvult(&bv8.from_u64(100)));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    /// let xv = solver.get_model().unwrap().eval(&x, true).unwrap();
+    /// assert_eq!(xv.as_u64(), Some(3));
+    /// ```
+    pub fn from_tactic(tactic: &Tactic<'ctx>) -> Solver<'ctx> {
+        Solver {
+            ctx: tactic.ctx,
+            z3_slv: unsafe {
+                let s = Z3_mk_solver_from_tactic(tactic.ctx.z3_ctx, tactic.z3_tactic);
+                Z3_solver_inc_ref(tactic.ctx.z3_ctx, s);
+                s
+            },
+            propagator: None,
+            #[cfg(feature = "z3_4_13")]
+            on_clause: None,
+        }
+    }
+
+    /// Copy the solver, with its assertions, into the context `dest`.
+    ///
+    /// The copy can be used from another thread that owns `dest`. To copy
+    /// a solver within its own context, use `clone()`.
+    ///
+    /// # Panics
+    ///
+    /// Z3 can only copy a solver at the base level, so this panics if
+    /// there are backtracking points that have not been popped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&ctx.named_int_const("x").gt(&ctx.from_i64(0)));
+    ///
+    /// let dest = Context::new(&cfg);
+    /// let copy = solver.translate(&dest);
+    /// copy.assert(&dest.named_int_const("x").lt(&dest.from_i64(0)));
+    /// assert_eq!(copy.check(), SatResult::Unsat);
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    /// ```
+    pub fn translate<'dest>(&self, dest: &'dest Context) -> Solver<'dest> {
+        assert!(
+            self.propagator.is_none(),
+            "cannot copy a solver with a user propagator"
+        );
+        #[cfg(feature = "z3_4_13")]
+        assert!(
+            self.on_clause.is_none(),
+            "cannot copy a solver with an on-clause callback"
+        );
+        let scopes = self.get_num_scopes();
+        assert!(
+            scopes == 0,
+            "cannot copy a solver at scope depth {}",
+            scopes
+        );
+        Solver {
+            ctx: dest,
+            z3_slv: unsafe {
+                let s = Z3_solver_translate(self.ctx.z3_ctx, self.z3_slv, dest.z3_ctx);
+                Z3_solver_inc_ref(dest.z3_ctx, s);
+                s
+            },
+            propagator: None,
+            #[cfg(feature = "z3_4_13")]
+            on_clause: None,
         }
     }
 
@@ -64,12 +244,18 @@ impl<'ctx> Solver<'ctx> {
     /// [`Solver::check_assumptions()`](#method.check_assumptions) should be
     /// used to check whether the logical context is consistent or not.
     ///
+    /// # Panics
+    ///
+    /// Panics if `ast` is not a Boolean formula.
+    ///
     /// # See also:
     ///
     /// - [`Solver::assert_and_track()`](#method.assert_and_track)
     pub fn assert(&self, ast: &Ast<'ctx>) {
-        let guard = Z3_MUTEX.lock().unwrap();
-        unsafe { Z3_solver_assert(self.ctx.z3_ctx, self.z3_slv, ast.z3_ast) };
+        unsafe {
+            self.ctx
+                .check_z3(|| Z3_solver_assert(self.ctx.z3_ctx, self.z3_slv, ast.z3_ast))
+        };
     }
 
     /// Assert a constraint `a` into the solver, and track it (in the
@@ -91,13 +277,78 @@ impl<'ctx> Solver<'ctx> {
     ///
     /// - [`Solver::assert()`](#method.assert)
     pub fn assert_and_track(&self, ast: &Ast<'ctx>, p: &Ast<'ctx>) {
-        let guard = Z3_MUTEX.lock().unwrap();
-        unsafe { Z3_solver_assert(self.ctx.z3_ctx, self.z3_slv, ast.z3_ast) };
+        unsafe {
+            self.ctx.check_z3(|| {
+                Z3_solver_assert_and_track(self.ctx.z3_ctx, self.z3_slv, ast.z3_ast, p.z3_ast)
+            })
+        };
+    }
+
+    /// Apply the parameters in `params` to the solver, for instance
+    /// `timeout` or `random_seed`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let solver = Solver::new(&ctx);
+    ///
+    /// let mut params = Params::new(&ctx);
+    /// params.set_u32("timeout", 1000);
+    /// params.set_bool("model", true);
+    /// solver.set_params(&params);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the solver does not accept one of the parameters, or its value
+    /// has the wrong kind.
+    ///
+    /// # See also:
+    ///
+    /// - [`Solver::get_param_descrs()`](#method.get_param_descrs)
+    pub fn set_params(&self, params: &Params<'ctx>) {
+        // Z3 only validates the parameters when it first uses the solver,
+        // which would leave the error to a later call.
+        let descrs = self.get_param_descrs();
+        unsafe {
+            self.ctx.check_z3(|| {
+                Z3_params_validate(self.ctx.z3_ctx, params.z3_params, descrs.z3_param_descrs)
+            });
+            Z3_solver_set_params(self.ctx.z3_ctx, self.z3_slv, params.z3_params)
+        };
     }
 
-    /// Remove all assertions from the solver.
+    /// Return descriptions of the parameters accepted by the solver.
+    ///
+    /// # See also:
+    ///
+    /// - [`Solver::set_params()`](#method.set_params)
+    pub fn get_param_descrs(&self) -> ParamDescrs<'ctx> {
+        unsafe {
+            let d = Z3_solver_get_param_descrs(self.ctx.z3_ctx, self.z3_slv);
+            ParamDescrs::from_raw(self.ctx, d)
+        }
+    }
+
+    /// Interrupt a running [`Solver::check()`](#method.check).
+    ///
+    /// This interrupts every operation running in the solver's context;
+    /// see [`Context::interrupt()`](struct.Context.html#method.interrupt).
+    pub fn interrupt(&self) {
+        self.ctx.interrupt();
+    }
+
+    /// Remove all assertions from the solver and pop all backtracking
+    /// points.
+    ///
+    /// Parameters set with [`Solver::set_params()`](#method.set_params)
+    /// are kept, so a solver can be reused in a loop instead of creating
+    /// a fresh one each time.
     pub fn reset(&self) {
-        let guard = Z3_MUTEX.lock().unwrap();
         unsafe { Z3_solver_reset(self.ctx.z3_ctx, self.z3_slv) };
     }
 
@@ -124,14 +375,19 @@ impl<'ctx> Solver<'ctx> {
     ///
     /// [model construction is enabled]: struct.Config.html#method.set_model_generation
     /// [proof generation was enabled]: struct.Config.html#method.set_proof_generation
-    pub fn check(&self) -> bool {
-        let guard = Z3_MUTEX.lock().unwrap();
-        unsafe { Z3_solver_check(self.ctx.z3_ctx, self.z3_slv) == Z3_L_TRUE }
+    pub fn check(&self) -> SatResult {
+        let lbool = { unsafe { Z3_solver_check(self.ctx.z3_ctx, self.z3_slv) } };
+        self.resume_propagator_panic();
+        SatResult::from_lbool(lbool)
     }
 
     /// Check whether the assertions in the given solver and
     /// optional assumptions are consistent or not.
     ///
+    /// Each assumption must be a Boolean constant or the negation of
+    /// one. On a satisfiable result the model is returned as part of the
+    /// [`CheckResult`](enum.CheckResult.html).
+    ///
     /// The function
     /// [`Solver::get_unsat_core()`](#method.get_unsat_core)
     /// retrieves the subset of the assumptions used in the
@@ -140,12 +396,213 @@ impl<'ctx> Solver<'ctx> {
     /// # See also:
     ///
     /// - [`Solver::check()`](#method.check)
-    pub fn check_assumptions(&self, assumptions: &[Ast<'ctx>]) -> bool {
-        let guard = Z3_MUTEX.lock().unwrap();
+    pub fn check_assumptions(&self, assumptions: &[&Ast<'ctx>]) -> CheckResult<'ctx> {
+        match self.check_assumptions_lbool(assumptions) {
+            Z3_L_TRUE => CheckResult::Satisfiable(self.get_model().unwrap()),
+            Z3_L_FALSE => CheckResult::Unsatisfiable,
+            Z3_L_UNDEF => {
+                CheckResult::Unknown(self.get_model().ok(), self.get_reason_unknown())
+            }
+            _ => panic!("Bad check result from z3 api!"),
+        }
+    }
+
+    /// Check the assertions under `assumptions`, without retrieving the
+    /// model.
+    fn check_assumptions_lbool(&self, assumptions: &[&Ast<'ctx>]) -> Z3_lbool {
         let a: Vec<Z3_ast> = assumptions.iter().map(|a| a.z3_ast).collect();
-        unsafe {
+        let lbool = unsafe {
             Z3_solver_check_assumptions(self.ctx.z3_ctx, self.z3_slv, a.len() as u32, a.as_ptr())
-                == Z3_L_TRUE
+        };
+        self.resume_propagator_panic();
+        lbool
+    }
+
+    /// Return a brief justification for an "unknown" result of the last
+    /// check, such as `"timeout"`, `"canceled"` or `"incomplete"`.
+    pub fn get_reason_unknown(&self) -> String {
+        unsafe {
+            let p = Z3_solver_get_reason_unknown(self.ctx.z3_ctx, self.z3_slv);
+            CStr::from_ptr(p).to_string_lossy().into_owned()
+        }
+    }
+
+    /// Retrieve the unsat core for the last
+    /// [`Solver::check_assumptions()`](#method.check_assumptions).
+    /// The unsat core is a subset of the assumptions, together with the
+    /// tracking constants passed to
+    /// [`Solver::assert_and_track()`](#method.assert_and_track), which
+    /// is already unsatisfiable with the assertions.
+    ///
+    /// The result is empty if the last check was not unsatisfiable.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    ///
+    /// let x = ctx.named_int_const("x");
+    /// let a = ctx.named_bool_const("a");
+    /// let b = ctx.named_bool_const("b");
+    /// let c = ctx.named_bool_const("c");
+    ///
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&a.implies(&x.gt(&ctx.from_i64(10))));
+    /// solver.assert(&b.implies(&x.lt(&ctx.from_i64(5))));
+    /// solver.assert(&c.implies(&x._eq(&ctx.from_i64(20))));
+    ///
+    /// match solver.check_assumptions(&[&a, &b, &c]) {
+    ///     CheckResult::Unsatisfiable => {}
+    ///     _ => panic!("expected unsat"),
+    /// }
+    /// let core = solver.get_unsat_core();
+    /// assert!(core.contains(&b));
+    /// assert!(core.contains(&a) || core.contains(&c));
+    /// ```
+    pub fn get_unsat_core(&self) -> Vec<Ast<'ctx>> {
+        let core = unsafe { Z3_solver_get_unsat_core(self.ctx.z3_ctx, self.z3_slv) };
+        Ast::vec_from_ast_vector(self.ctx, core)
+    }
+
+    /// Shrink `core`, a set of assumptions that is unsatisfiable together
+    /// with the assertions, to a minimal one: removing any assumption
+    /// from the result makes it satisfiable.
+    ///
+    /// Each assumption is dropped in turn and kept only if the remaining
+    /// ones become satisfiable, or the check is unknown. Whenever they are
+    /// still unsatisfiable, the core is narrowed further to the
+    /// [`Solver::get_unsat_core()`](#method.get_unsat_core) of that check.
+    /// The assertions of the solver are not changed.
+    ///
+    /// The assumptions must be passed to
+    /// [`Solver::check_assumptions()`](#method.check_assumptions); tracking
+    /// constants from [`Solver::assert_and_track()`](#method.assert_and_track)
+    /// are always assumed and cannot be removed. If `core` is satisfiable
+    /// it is returned unchanged.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_int_const("x");
+    /// let a = ctx.named_bool_const("a");
+    /// let b = ctx.named_bool_const("b");
+    /// let c = ctx.named_bool_const("c");
+    ///
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&a.implies(&x.gt(&ctx.from_i64(10))));
+    /// solver.assert(&b.implies(&x.gt(&ctx.from_i64(20))));
+    /// solver.assert(&c.implies(&x.lt(&ctx.from_i64(5))));
+    ///
+    /// let core = solver.minimize_core(&[&a, &b, &c]);
+    /// assert_eq!(core.len(), 2);
+    /// assert!(core.contains(&c));
+    /// ```
+    pub fn minimize_core(&self, core: &[&Ast<'ctx>]) -> Vec<Ast<'ctx>> {
+        let mut core: Vec<Ast<'ctx>> = core.iter().map(|&a| a.clone()).collect();
+        // Everything before `i` has been found to be necessary. It stays
+        // necessary in every smaller unsatisfiable subset, so it is never
+        // dropped by narrowing to a new unsat core.
+        let mut i = 0;
+        while i < core.len() {
+            let rest: Vec<&Ast<'ctx>> = core
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, a)| a)
+                .collect();
+            if self.check_assumptions_lbool(&rest) == Z3_L_FALSE {
+                let smaller = self.get_unsat_core();
+                let narrowed = rest
+                    .into_iter()
+                    .filter(|a| smaller.contains(a))
+                    .cloned()
+                    .collect();
+                core = narrowed;
+            } else {
+                i += 1;
+            }
+        }
+        core
+    }
+
+    /// Return the formulas currently asserted to the solver, including
+    /// those asserted with [`Solver::assert_and_track()`](#method.assert_and_track).
+    pub fn assertions(&self) -> impl Iterator<Item = Ast<'ctx>> {
+        let v = unsafe { Z3_solver_get_assertions(self.ctx.z3_ctx, self.z3_slv) };
+        Ast::vec_from_ast_vector(self.ctx, v).into_iter()
+    }
+
+    /// Retrieve statistics for the last [`Solver::check()`](#method.check),
+    /// such as the number of conflicts, decisions and restarts.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let solver = Solver::new(&ctx);
+    /// let x = ctx.named_int_const("x");
+    /// solver.assert(&x.gt(&ctx.from_i64(0)));
+    /// solver.check();
+    ///
+    /// let stats = solver.get_statistics();
+    /// for entry in stats.entries() {
+    ///     println!("{}: {:?}", entry.key, entry.value);
+    /// }
+    /// assert!(stats.get("memory").is_some());
+    /// ```
+    pub fn get_statistics(&self) -> Statistics<'ctx> {
+        unsafe {
+            let s = Z3_solver_get_statistics(self.ctx.z3_ctx, self.z3_slv);
+            Statistics::from_raw(self.ctx, s)
+        }
+    }
+
+    /// Return the assertions of the solver as a complete SMT-LIB2
+    /// benchmark, with declarations and a final `(check-sat)`, that can
+    /// be run with the Z3 command line tool.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&ctx.named_int_const("x").gt(&ctx.from_i64(0)));
+    ///
+    /// let smt2 = solver.to_smt2();
+    /// assert!(smt2.contains("(declare-fun x () Int)"));
+    /// assert!(smt2.contains("(check-sat)"));
+    /// ```
+    pub fn to_smt2(&self) -> String {
+        let mut assertions: Vec<Ast<'ctx>> = self.assertions().collect();
+        let formula = assertions.pop().unwrap_or_else(|| self.ctx.from_bool(true));
+        let assumptions: Vec<Z3_ast> = assertions.iter().map(|a| a.z3_ast).collect();
+        let empty = CString::new("").unwrap();
+        let status = CString::new("unknown").unwrap();
+        unsafe {
+            let p = Z3_benchmark_to_smtlib_string(
+                self.ctx.z3_ctx,
+                empty.as_ptr(),
+                empty.as_ptr(),
+                status.as_ptr(),
+                empty.as_ptr(),
+                assumptions.len() as u32,
+                assumptions.as_ptr(),
+                formula.z3_ast,
+            );
+            CStr::from_ptr(p).to_string_lossy().into_owned()
         }
     }
 
@@ -157,68 +614,431 @@ impl<'ctx> Solver<'ctx> {
     ///
     /// - [`Solver::pop()`](#method.pop)
     pub fn push(&self) {
-        let guard = Z3_MUTEX.lock().unwrap();
         unsafe { Z3_solver_push(self.ctx.z3_ctx, self.z3_slv) };
     }
 
     /// Backtrack `n` backtracking points.
     ///
+    /// # Panics
+    ///
+    /// Panics if `n` is greater than
+    /// [`Solver::get_num_scopes()`](#method.get_num_scopes).
+    ///
     /// # See also:
     ///
     /// - [`Solver::push()`](#method.push)
     pub fn pop(&self, n: u32) {
-        let guard = Z3_MUTEX.lock().unwrap();
+        let scopes = self.get_num_scopes();
+        assert!(
+            n <= scopes,
+            "cannot pop {} scopes from a solver with {}",
+            n,
+            scopes
+        );
         unsafe { Z3_solver_pop(self.ctx.z3_ctx, self.z3_slv, n) };
     }
 
+    /// Determine which of `variables` have a fixed value in every model
+    /// of the assertions and `assumptions`, as is needed e.g. to compute
+    /// the backbone of a formula.
+    ///
+    /// On a satisfiable result each consequence is an implication
+    /// `(=> (and a1 .. an) (= v value))`, where `a1 .. an` are the
+    /// assumptions it depends on, or `true` if there are none. For a
+    /// Boolean variable the right-hand side is `v` or `(not v)`.
+    /// Variables that can take more than one value have no consequence.
+    /// Otherwise the result is empty.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let a = ctx.named_bool_const("a");
+    /// let b = ctx.named_bool_const("b");
+    /// let c = ctx.named_bool_const("c");
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&a.implies(&b));
+    /// solver.assert(&b.or(&[&c]));
+    ///
+    /// let (result, consequences) = solver.get_consequences(&[&a], &[&b, &c]);
+    /// assert_eq!(result, SatResult::Sat);
+    /// assert_eq!(consequences.len(), 1);
+    /// assert_eq!(consequences[0].to_string(), "(=> a b)");
+    /// ```
+    pub fn get_consequences(
+        &self,
+        assumptions: &[&Ast<'ctx>],
+        variables: &[&Ast<'ctx>],
+    ) -> (SatResult, Vec<Ast<'ctx>>) {
+        let assumptions = AstVector::from_slice(self.ctx, assumptions);
+        let variables = AstVector::from_slice(self.ctx, variables);
+        let consequences = AstVector::new(self.ctx);
+        let lbool = unsafe {
+            Z3_solver_get_consequences(
+                self.ctx.z3_ctx,
+                self.z3_slv,
+                assumptions.z3_ast_vector,
+                variables.z3_ast_vector,
+                consequences.z3_ast_vector,
+            )
+        };
+        self.resume_propagator_panic();
+        (SatResult::from_lbool(lbool), consequences.to_vec())
+    }
+
+    /// Split the search space of the solver into cubes: conjunctions of
+    /// literals that together cover every satisfying assignment. Each
+    /// cube can be assumed by a separate solver, e.g. a
+    /// [`translate`](#method.translate)d copy on another thread, to work
+    /// on the problem in parallel.
+    ///
+    /// Cubing branches on the variables in `vars`, or on variables chosen
+    /// by Z3 if it is empty. An empty cube stands for `true`: the
+    /// remaining problem could not be split further. The iterator ends
+    /// once the cube `false` is produced, i.e. every cube has been
+    /// returned. For Z3's SAT solver the number of cubes is controlled by
+    /// the `sat.lookahead.cube.*` parameters.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let a = ctx.named_bool_const("a");
+    /// let b = ctx.named_bool_const("b");
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&a.or(&[&b]));
+    ///
+    /// for cube in solver.cube(&[]) {
+    ///     let refs: Vec<&Ast> = cube.iter().collect();
+    ///     let sub = solver.translate(&ctx);
+    ///     sub.assert(&ctx.from_bool(true).and(&refs));
+    ///     assert_eq!(sub.check(), SatResult::Sat);
+    /// }
+    /// ```
+    pub fn cube<'a>(&'a self, vars: &[&Ast<'ctx>]) -> Cubes<'a, 'ctx> {
+        Cubes {
+            solver: self,
+            vars: AstVector::from_slice(self.ctx, vars),
+            done: false,
+        }
+    }
+
+    /// Return the number of backtracking points created with
+    /// [`Solver::push()`](#method.push) that have not been popped.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let solver = Solver::new(&ctx);
+    /// solver.push();
+    /// solver.push();
+    /// solver.push();
+    /// assert_eq!(solver.get_num_scopes(), 3);
+    /// solver.pop(2);
+    /// assert_eq!(solver.get_num_scopes(), 1);
+    /// ```
+    pub fn get_num_scopes(&self) -> u32 {
+        unsafe { Z3_solver_get_num_scopes(self.ctx.z3_ctx, self.z3_slv) }
+    }
+
+    /// Install `propagator` as a custom theory of the solver. Its
+    /// callbacks run during [`Solver::check()`](#method.check) and the
+    /// other methods that search for a model, for the expressions
+    /// registered with
+    /// [`Solver::propagate_register()`](#method.propagate_register).
+    ///
+    /// A solver with a user propagator cannot be copied with
+    /// [`Solver::translate()`](#method.translate) or `clone()`.
+    ///
+    /// User propagators are only supported by the plain SMT solver created
+    /// with [`Solver::new_simple()`](#method.new_simple).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the solver already has a user propagator, or does not
+    /// support one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// // Forbid more than one of the registered Booleans from being true.
+    /// struct AtMostOne {
+    ///     trail: Vec<u32>,
+    ///     scopes: Vec<usize>,
+    /// }
+    ///
+    /// impl<'ctx> UserPropagator<'ctx> for AtMostOne {
+    ///     fn push(&mut self) {
+    ///         self.scopes.push(self.trail.len());
+    ///     }
+    ///
+    ///     fn pop(&mut self, num_scopes: u32) {
+    ///         let n = self.scopes.len() - num_scopes as usize;
+    ///         self.trail.truncate(self.scopes[n]);
+    ///         self.scopes.truncate(n);
+    ///     }
+    ///
+    ///     fn fixed(&mut self, cb: &PropagatorCallback<'ctx>, id: u32, value: &Ast<'ctx>) {
+    ///         if value.as_bool() == Some(true) {
+    ///             if let Some(&other) = self.trail.first() {
+    ///                 cb.conflict(&[other, id], &[]);
+    ///             }
+    ///             self.trail.push(id);
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let a = ctx.named_bool_const("a");
+    /// let b = ctx.named_bool_const("b");
+    /// let mut solver = Solver::new_simple(&ctx);
+    /// solver.set_user_propagator(AtMostOne { trail: vec![], scopes: vec![] });
+    /// solver.propagate_register(&a);
+    /// solver.propagate_register(&b);
+    ///
+    /// solver.assert(&a.or(&[&b]));
+    /// assert_eq!(solver.check(), SatResult::Sat);
+    /// solver.assert(&a._eq(&b));
+    /// assert_eq!(solver.check(), SatResult::Unsat);
+    /// ```
+    pub fn set_user_propagator<P: UserPropagator<'ctx> + 'ctx>(&mut self, propagator: P) {
+        assert!(
+            self.propagator.is_none(),
+            "solver already has a user propagator"
+        );
+        let installed =
+            { unsafe { InstalledPropagator::install(self.ctx, self.z3_slv, propagator) } };
+        match installed {
+            Ok(installed) => self.propagator = Some(RefCell::new(installed)),
+            Err(err) => panic!("{}", err.message),
+        }
+    }
+
+    /// Register `ast`, a Boolean or bit-vector expression, with the user
+    /// propagator, and return the id the propagator's callbacks refer to
+    /// it by.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no propagator was installed with
+    /// [`Solver::set_user_propagator()`](#method.set_user_propagator).
+    pub fn propagate_register(&self, ast: &Ast<'ctx>) -> u32 {
+        assert!(self.propagator.is_some(), "solver has no user propagator");
+        unsafe { Z3_solver_propagate_register(self.ctx.z3_ctx, self.z3_slv, ast.z3_ast) }
+    }
+
+    /// Call `on_clause` for every clause the solver infers during a
+    /// search, with the proof hint that justifies the clause, the ids
+    /// of the assumptions it depends on and its literals. This replaces
+    /// an earlier on-clause callback.
+    ///
+    /// This can stream the inferences to an external proof checker or a
+    /// log.
+    ///
+    /// Like a solver with a user propagator, a solver with an on-clause
+    /// callback cannot be copied, and a panic in the callback interrupts
+    /// the search and is resumed once the check returns.
+    ///
+    /// Requires the `z3_4_13` feature, and Z3 4.13 or later.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let mut cfg = Config::new();
+    /// cfg.set_proof_generation(true);
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_int_const("x");
+    /// let mut solver = Solver::new(&ctx);
+    /// let mut clauses = 0;
+    /// solver.register_on_clause(|_, _, _| clauses += 1);
+    /// solver.assert(&x.gt(&ctx.from_i64(2)));
+    /// solver.assert(&x.lt(&ctx.from_i64(1)));
+    /// assert_eq!(solver.check(), SatResult::Unsat);
+    /// drop(solver);
+    /// assert!(clauses > 0);
+    /// ```
+    #[cfg(feature = "z3_4_13")]
+    pub fn register_on_clause<F>(&mut self, on_clause: F)
+    where
+        F: FnMut(Option<&Ast<'ctx>>, &[u32], &[Ast<'ctx>]) + 'ctx,
+    {
+        let installed = unsafe { InstalledOnClause::install(self.ctx, self.z3_slv, on_clause) };
+        self.on_clause = Some(RefCell::new(installed));
+    }
+
+    /// Resume a panic raised by a callback of the user propagator, or
+    /// the on-clause callback, during the last search.
+    fn resume_propagator_panic(&self) {
+        if let Some(payload) = self
+            .propagator
+            .as_ref()
+            .and_then(|p| p.borrow_mut().take_panic())
+        {
+            panic::resume_unwind(payload);
+        }
+        #[cfg(feature = "z3_4_13")]
+        {
+            if let Some(payload) = self
+                .on_clause
+                .as_ref()
+                .and_then(|c| c.borrow_mut().take_panic())
+            {
+                panic::resume_unwind(payload);
+            }
+        }
+    }
+
     /// Retrieve the model for the last [`Solver::check()`](#method.check)
     /// or [`Solver::check_assumptions()`](#method.check_assumptions)
     ///
-    /// The error handler is invoked if a model is not available because
-    /// the commands above were not invoked for the given solver, or if
-    /// the result was `Z3_L_FALSE`.
-    pub fn get_model(&self) -> Model<'ctx> {
+    /// Returns an error if a model is not available because the commands
+    /// above were not invoked for the given solver, or if the result was
+    /// `Z3_L_FALSE`.
+    pub fn get_model(&self) -> Result<Model<'ctx>, Z3Error> {
         Model::of_solver(self)
     }
 
     /// Retrieve the proof for the last [`Solver::check()`](#method.check)
     /// or [`Solver::check_assumptions()`](#method.check_assumptions)
     ///
-    /// The error handler is invoked if [proof generation is not enabled],
-    /// or if the commands above were not invoked for the given solver,
-    /// or if the result was different from `Z3_L_FALSE`.
+    /// Returns an error if [proof generation is not enabled], or if the
+    /// commands above were not invoked for the given solver, or if the
+    /// result was different from `Z3_L_FALSE`.
     ///
     /// # See also:
     ///
     /// - [`Config::set_proof_generation()`](struct.Config.html#method.set_proof_generation)
     ///
     /// [proof generation is not enabled]: struct.Config.html#method.set_proof_generation
-    pub fn get_proof(&self) -> Ast<'ctx> {
-        let guard = Z3_MUTEX.lock().unwrap();
-        Ast::new(self.ctx, unsafe {
-            Z3_solver_get_proof(self.ctx.z3_ctx, self.z3_slv)
-        })
+    pub fn get_proof(&self) -> Result<Ast<'ctx>, Z3Error> {
+        let p = unsafe {
+            self.ctx
+                .try_z3(|| Z3_solver_get_proof(self.ctx.z3_ctx, self.z3_slv))?
+        };
+        Ok(Ast::new(self.ctx, p))
+    }
+}
+
+impl Logic {
+    /// Return the SMT-LIB name of the logic.
+    pub fn name(self) -> &'static str {
+        match self {
+            Logic::QF_UF => "QF_UF",
+            Logic::QF_BV => "QF_BV",
+            Logic::QF_ABV => "QF_ABV",
+            Logic::QF_AUFBV => "QF_AUFBV",
+            Logic::QF_UFBV => "QF_UFBV",
+            Logic::QF_IDL => "QF_IDL",
+            Logic::QF_RDL => "QF_RDL",
+            Logic::QF_LIA => "QF_LIA",
+            Logic::QF_LRA => "QF_LRA",
+            Logic::QF_NIA => "QF_NIA",
+            Logic::QF_NRA => "QF_NRA",
+            Logic::QF_UFLIA => "QF_UFLIA",
+            Logic::QF_FP => "QF_FP",
+            Logic::QF_FPBV => "QF_FPBV",
+            Logic::QF_S => "QF_S",
+            Logic::LIA => "LIA",
+            Logic::LRA => "LRA",
+            Logic::NIA => "NIA",
+            Logic::NRA => "NRA",
+            Logic::UFLIA => "UFLIA",
+            Logic::AUFLIA => "AUFLIA",
+            Logic::UFBV => "UFBV",
+        }
+    }
+}
+
+impl AsRef<str> for Logic {
+    fn as_ref(&self) -> &str {
+        self.name()
+    }
+}
+
+impl fmt::Display for Logic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Copy the solver within its context, so that the copy can explore a
+/// different branch of the problem. Like
+/// [`Solver::translate()`](struct.Solver.html#method.translate), this
+/// panics if the solver has backtracking points.
+impl<'ctx> Clone for Solver<'ctx> {
+    fn clone(&self) -> Solver<'ctx> {
+        self.translate(self.ctx)
     }
 }
 
 impl<'ctx> fmt::Display for Solver<'ctx> {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        let p = unsafe {
-            CStr::from_ptr(Z3_solver_to_string(self.ctx.z3_ctx, self.z3_slv) as *mut i8)
-        };
-        if p.as_ptr().is_null() {
+        let p = unsafe { Z3_solver_to_string(self.ctx.z3_ctx, self.z3_slv) };
+        if p.is_null() {
             return Result::Err(fmt::Error);
         }
-        match p.to_str() {
+        match unsafe { CStr::from_ptr(p) }.to_str() {
             Ok(s) => write!(f, "{}", s),
             Err(_) => Result::Err(fmt::Error),
         }
     }
 }
 
+impl<'ctx> fmt::Debug for Solver<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        f.debug_struct("Solver")
+            .field("scopes", &self.get_num_scopes())
+            .field("assertions", &self.assertions().count())
+            .field("user_propagator", &self.propagator.is_some())
+            .finish()
+    }
+}
+
+impl<'a, 'ctx> Iterator for Cubes<'a, 'ctx> {
+    type Item = Vec<Ast<'ctx>>;
+
+    fn next(&mut self) -> Option<Vec<Ast<'ctx>>> {
+        if self.done {
+            return None;
+        }
+        let ctx = self.solver.ctx;
+        let cube = unsafe {
+            let v = Z3_solver_cube(
+                ctx.z3_ctx,
+                self.solver.z3_slv,
+                self.vars.z3_ast_vector,
+                u32::MAX,
+            );
+            AstVector::from_raw(ctx, v)
+        }
+        .to_vec();
+        self.solver.resume_propagator_panic();
+        if cube.len() == 1 && cube[0].as_bool() == Some(false) {
+            self.done = true;
+            return None;
+        }
+        self.done = cube.is_empty();
+        Some(cube)
+    }
+}
+
 impl<'ctx> Drop for Solver<'ctx> {
     fn drop(&mut self) {
-        let guard = Z3_MUTEX.lock().unwrap();
         unsafe { Z3_solver_dec_ref(self.ctx.z3_ctx, self.z3_slv) };
     }
 }
@@ -1,28 +1,39 @@
-use std::ffi::CStr;
+use std::ffi::{CStr, CString};
 use std::fmt;
 use z3_sys::*;
+use Ast;
 use Context;
+use FuncDecl;
 use Sort;
 use Symbol;
-use Ast;
-use Z3_MUTEX;
 
 impl<'ctx> Sort<'ctx> {
-    pub fn uninterpretd(ctx: &'ctx Context, sym: &Symbol<'ctx>) -> Sort<'ctx> {
+    /// Create an uninterpreted sort named `sym`.
+    ///
+    /// Two uninterpreted sorts with the same name are the same sort.
+    /// Nothing is known about the values of such a sort except that
+    /// they are equal or distinct, which makes it suitable for abstract
+    /// types such as nodes or addresses.
+    pub fn uninterpreted(ctx: &'ctx Context, sym: &Symbol<'ctx>) -> Sort<'ctx> {
         Sort {
             ctx,
             z3_sort: unsafe {
-                let guard = Z3_MUTEX.lock().unwrap();
-                Z3_mk_uninterpreted_sort(ctx.z3_ctx, sym.z3_sym)
+                let s = Z3_mk_uninterpreted_sort(ctx.z3_ctx, sym.z3_sym);
+                Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
+                s
             },
         }
     }
 
-    pub fn bool(ctx: &Context) -> Sort {
+    #[deprecated(note = "misspelled, use `Sort::uninterpreted` instead")]
+    pub fn uninterpretd(ctx: &'ctx Context, sym: &Symbol<'ctx>) -> Sort<'ctx> {
+        Sort::uninterpreted(ctx, sym)
+    }
+
+    pub fn bool(ctx: &Context) -> Sort<'_> {
         Sort {
             ctx,
             z3_sort: unsafe {
-                let guard = Z3_MUTEX.lock().unwrap();
                 let s = Z3_mk_bool_sort(ctx.z3_ctx);
                 Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
                 s
@@ -30,11 +41,10 @@ impl<'ctx> Sort<'ctx> {
         }
     }
 
-    pub fn int(ctx: &Context) -> Sort {
+    pub fn int(ctx: &Context) -> Sort<'_> {
         Sort {
             ctx,
             z3_sort: unsafe {
-                let guard = Z3_MUTEX.lock().unwrap();
                 let s = Z3_mk_int_sort(ctx.z3_ctx);
                 Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
                 s
@@ -42,11 +52,10 @@ impl<'ctx> Sort<'ctx> {
         }
     }
 
-    pub fn real(ctx: &Context) -> Sort {
+    pub fn real(ctx: &Context) -> Sort<'_> {
         Sort {
             ctx,
             z3_sort: unsafe {
-                let guard = Z3_MUTEX.lock().unwrap();
                 let s = Z3_mk_real_sort(ctx.z3_ctx);
                 Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
                 s
@@ -54,11 +63,10 @@ impl<'ctx> Sort<'ctx> {
         }
     }
 
-    pub fn bitvector(ctx: &Context, sz: u32) -> Sort {
+    pub fn bitvector(ctx: &Context, sz: u32) -> Sort<'_> {
         Sort {
             ctx,
             z3_sort: unsafe {
-                let guard = Z3_MUTEX.lock().unwrap();
                 let s = Z3_mk_bv_sort(ctx.z3_ctx, sz as ::std::os::raw::c_uint);
                 Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
                 s
@@ -70,7 +78,6 @@ impl<'ctx> Sort<'ctx> {
         Sort {
             ctx,
             z3_sort: unsafe {
-                let guard = Z3_MUTEX.lock().unwrap();
                 let s = Z3_mk_array_sort(ctx.z3_ctx, domain.z3_sort, range.z3_sort);
                 Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
                 s
@@ -82,7 +89,6 @@ impl<'ctx> Sort<'ctx> {
         Sort {
             ctx,
             z3_sort: unsafe {
-                let guard = Z3_MUTEX.lock().unwrap();
                 let s = Z3_mk_set_sort(ctx.z3_ctx, elt.z3_sort);
                 Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
                 s
@@ -90,6 +96,236 @@ impl<'ctx> Sort<'ctx> {
         }
     }
 
+    pub fn string(ctx: &'ctx Context) -> Sort<'ctx> {
+        Sort {
+            ctx,
+            z3_sort: unsafe {
+                let s = Z3_mk_string_sort(ctx.z3_ctx);
+                Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
+                s
+            },
+        }
+    }
+
+    pub fn seq(ctx: &'ctx Context, elt: &Sort<'ctx>) -> Sort<'ctx> {
+        Sort {
+            ctx,
+            z3_sort: unsafe {
+                let s = Z3_mk_seq_sort(ctx.z3_ctx, elt.z3_sort);
+                Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
+                s
+            },
+        }
+    }
+
+    pub fn regexp(ctx: &'ctx Context, seq: &Sort<'ctx>) -> Sort<'ctx> {
+        Sort {
+            ctx,
+            z3_sort: unsafe {
+                let s = Z3_mk_re_sort(ctx.z3_ctx, seq.z3_sort);
+                Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
+                s
+            },
+        }
+    }
+
+    /// Create a floating-point sort with `ebits` exponent bits and
+    /// `sbits` significand bits (including the hidden bit).
+    pub fn float(ctx: &'ctx Context, ebits: u32, sbits: u32) -> Sort<'ctx> {
+        Sort {
+            ctx,
+            z3_sort: unsafe {
+                let s = Z3_mk_fpa_sort(ctx.z3_ctx, ebits, sbits);
+                Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
+                s
+            },
+        }
+    }
+
+    /// Create the IEEE 754 single precision (`Float32`) sort.
+    pub fn float32(ctx: &'ctx Context) -> Sort<'ctx> {
+        Sort {
+            ctx,
+            z3_sort: unsafe {
+                let s = Z3_mk_fpa_sort_32(ctx.z3_ctx);
+                Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
+                s
+            },
+        }
+    }
+
+    /// Create the IEEE 754 double precision (`Float64`) sort.
+    pub fn float64(ctx: &'ctx Context) -> Sort<'ctx> {
+        Sort {
+            ctx,
+            z3_sort: unsafe {
+                let s = Z3_mk_fpa_sort_64(ctx.z3_ctx);
+                Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
+                s
+            },
+        }
+    }
+
+    /// Create the sort of IEEE 754 rounding modes.
+    pub fn rounding_mode(ctx: &'ctx Context) -> Sort<'ctx> {
+        Sort {
+            ctx,
+            z3_sort: unsafe {
+                let s = Z3_mk_fpa_rounding_mode_sort(ctx.z3_ctx);
+                Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
+                s
+            },
+        }
+    }
+
+    /// Create a finite-domain sort named `name` with `size` elements.
+    ///
+    /// Finite-domain sorts are mainly used to declare bounded relation
+    /// domains for the Datalog engine. Their elements are the numerals
+    /// `0` to `size - 1`, obtained with
+    /// [`Sort::from_u64()`](#method.from_u64).
+    pub fn finite_domain(ctx: &'ctx Context, name: &Symbol<'ctx>, size: u64) -> Sort<'ctx> {
+        Sort {
+            ctx,
+            z3_sort: unsafe {
+                let s = Z3_mk_finite_domain_sort(ctx.z3_ctx, name.z3_sym, size);
+                Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
+                s
+            },
+        }
+    }
+
+    /// Return the symbol naming this sort, e.g. the name given to
+    /// [`Sort::uninterpreted()`](#method.uninterpreted).
+    pub fn symbol(&self) -> Symbol<'ctx> {
+        Symbol::wrap(self.ctx, unsafe {
+            Z3_get_sort_name(self.ctx.z3_ctx, self.z3_sort)
+        })
+    }
+
+    /// Return the number of elements of a finite-domain sort, or `None`
+    /// if `self` is not a finite-domain sort.
+    pub fn finite_domain_size(&self) -> Option<u64> {
+        unsafe {
+            if Z3_get_sort_kind(self.ctx.z3_ctx, self.z3_sort) != SortKind::FiniteDomain {
+                return None;
+            }
+            let mut size = 0;
+            if Z3_get_finite_domain_sort_size(self.ctx.z3_ctx, self.z3_sort, &mut size) {
+                Some(size)
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Create a tuple sort named `name` with the given named fields.
+    ///
+    /// Returns the sort together with the declaration of its
+    /// constructor (also named `name`) and of one projection function
+    /// per field, in order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    ///
+    /// let int = ctx.int_sort();
+    /// let (pair, mk_pair, proj) = Sort::tuple(
+    ///     &ctx,
+    ///     &ctx.str_sym("Pair"),
+    ///     &[(&ctx.str_sym("fst"), &int), (&ctx.str_sym("snd"), &int)],
+    /// );
+    ///
+    /// let p = Ast::new_const(&ctx.str_sym("p"), &pair);
+    /// let solver = Solver::new(&ctx);
+    /// solver.assert(&p._eq(&mk_pair.apply(&[&ctx.from_i64(1), &ctx.from_i64(2)])));
+    /// solver.assert(&proj[1].apply(&[&p])._eq(&ctx.from_i64(1)));
+    /// assert_eq!(solver.check(), SatResult::Unsat);
+    /// ```
+    ///
+    /// # See also:
+    ///
+    /// - [`DatatypeBuilder`](struct.DatatypeBuilder.html)
+    pub fn tuple(
+        ctx: &'ctx Context,
+        name: &Symbol<'ctx>,
+        fields: &[(&Symbol<'ctx>, &Sort<'ctx>)],
+    ) -> (Sort<'ctx>, FuncDecl<'ctx>, Vec<FuncDecl<'ctx>>) {
+        let field_names: Vec<Z3_symbol> = fields.iter().map(|(n, _)| n.z3_sym).collect();
+        let field_sorts: Vec<Z3_sort> = fields.iter().map(|(_, s)| s.z3_sort).collect();
+        let mut mk_decl = ::std::ptr::null_mut();
+        let mut proj_decls = vec![::std::ptr::null_mut(); fields.len()];
+        unsafe {
+            let s = Z3_mk_tuple_sort(
+                ctx.z3_ctx,
+                name.z3_sym,
+                fields.len() as u32,
+                field_names.as_ptr(),
+                field_sorts.as_ptr(),
+                &mut mk_decl,
+                proj_decls.as_mut_ptr(),
+            );
+            Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
+            (
+                Sort { ctx, z3_sort: s },
+                FuncDecl::from_raw(ctx, mk_decl),
+                proj_decls
+                    .into_iter()
+                    .map(|d| FuncDecl::from_raw(ctx, d))
+                    .collect(),
+            )
+        }
+    }
+
+    /// Create an enumeration sort named `name` whose elements are named
+    /// by `enum_names`.
+    ///
+    /// Returns the sort together with the declarations of its constants
+    /// and of the corresponding testers, in the order of `enum_names`.
+    /// The constants are nullary, so use `apply(&[])` to obtain them as
+    /// terms.
+    pub fn enumeration(
+        ctx: &'ctx Context,
+        name: &Symbol<'ctx>,
+        enum_names: &[&str],
+    ) -> (Sort<'ctx>, Vec<FuncDecl<'ctx>>, Vec<FuncDecl<'ctx>>) {
+        let mut consts = vec![::std::ptr::null_mut(); enum_names.len()];
+        let mut testers = vec![::std::ptr::null_mut(); enum_names.len()];
+        unsafe {
+            let names: Vec<Z3_symbol> = enum_names
+                .iter()
+                .map(|n| {
+                    let cs = CString::new(*n).unwrap();
+                    Z3_mk_string_symbol(ctx.z3_ctx, cs.as_ptr())
+                })
+                .collect();
+            let s = Z3_mk_enumeration_sort(
+                ctx.z3_ctx,
+                name.z3_sym,
+                names.len() as u32,
+                names.as_ptr(),
+                consts.as_mut_ptr(),
+                testers.as_mut_ptr(),
+            );
+            Z3_inc_ref(ctx.z3_ctx, Z3_sort_to_ast(ctx.z3_ctx, s));
+            (
+                Sort { ctx, z3_sort: s },
+                consts
+                    .into_iter()
+                    .map(|d| FuncDecl::from_raw(ctx, d))
+                    .collect(),
+                testers
+                    .into_iter()
+                    .map(|d| FuncDecl::from_raw(ctx, d))
+                    .collect(),
+            )
+        }
+    }
+
     /// Converts an unsigned integer to an `Ast` of the given `Sort`
     ///
     /// `self` must be an integer, bit-vector, or finite-domain sort.
@@ -111,14 +347,13 @@ impl<'ctx> Sort<'ctx> {
     /// let solver = Solver::new(&ctx);
     /// solver.assert(&x.bvadd(&bv_sort.from_u64(1))
     ///     ._eq(&bv_sort.from_u64(0)));
-    /// assert!(solver.check());
+    /// assert_eq!(solver.check(), SatResult::Sat);
     ///
-    /// let model = solver.get_model();
-    /// assert!(model.eval(&x).and_then(|i| i.as_u64()).unwrap_or(0) == 0xFF);
+    /// let model = solver.get_model().unwrap();
+    /// assert!(model.eval(&x, true).and_then(|i| i.as_u64()).unwrap_or(0) == 0xFF);
     /// ```
     pub fn from_u64(&self, u: u64) -> Ast<'ctx> {
         Ast::new(self.ctx, unsafe {
-            let guard = Z3_MUTEX.lock().unwrap();
             Z3_mk_unsigned_int64(self.ctx.z3_ctx, u, self.z3_sort)
         })
     }
@@ -143,11 +378,10 @@ impl<'ctx> Sort<'ctx> {
     /// solver.assert(&bv_sort.from_i64(-5)
     ///     .bvmul(&bv_sort.from_i64(-100))
     ///     ._eq(&bv_sort.from_i64((-5 * -100) % 256)));
-    /// assert!(solver.check());
+    /// assert_eq!(solver.check(), SatResult::Sat);
     /// ```
     pub fn from_i64(&self, i: i64) -> Ast<'ctx> {
         Ast::new(self.ctx, unsafe {
-            let guard = Z3_MUTEX.lock().unwrap();
             Z3_mk_int64(self.ctx.z3_ctx, i, self.z3_sort)
         })
     }
@@ -155,18 +389,43 @@ impl<'ctx> Sort<'ctx> {
 
 impl<'ctx> fmt::Display for Sort<'ctx> {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        let p =
-            unsafe { CStr::from_ptr(Z3_sort_to_string(self.ctx.z3_ctx, self.z3_sort) as *mut i8) };
-        if p.as_ptr().is_null() {
+        let p = unsafe { Z3_sort_to_string(self.ctx.z3_ctx, self.z3_sort) };
+        if p.is_null() {
             return Result::Err(fmt::Error);
         }
-        match p.to_str() {
+        match unsafe { CStr::from_ptr(p) }.to_str() {
             Ok(s) => write!(f, "{}", s),
             Err(_) => Result::Err(fmt::Error),
         }
     }
 }
 
+impl<'ctx> Clone for Sort<'ctx> {
+    fn clone(&self) -> Sort<'ctx> {
+        Sort {
+            ctx: self.ctx,
+            z3_sort: unsafe {
+                Z3_inc_ref(
+                    self.ctx.z3_ctx,
+                    Z3_sort_to_ast(self.ctx.z3_ctx, self.z3_sort),
+                );
+                self.z3_sort
+            },
+        }
+    }
+}
+
+impl<'ctx> Drop for Sort<'ctx> {
+    fn drop(&mut self) {
+        unsafe {
+            Z3_dec_ref(
+                self.ctx.z3_ctx,
+                Z3_sort_to_ast(self.ctx.z3_ctx, self.z3_sort),
+            );
+        }
+    }
+}
+
 impl<'ctx> PartialEq<Sort<'ctx>> for Sort<'ctx> {
     fn eq(&self, other: &Sort<'ctx>) -> bool {
         unsafe { Z3_is_eq_sort(self.ctx.z3_ctx, self.z3_sort, other.z3_sort) }
@@ -0,0 +1,72 @@
+use std::ffi::CStr;
+use std::fmt;
+use z3_sys::*;
+use Context;
+use Statistics;
+use StatisticsEntry;
+use StatisticsValue;
+
+impl<'ctx> Statistics<'ctx> {
+    /// Wrap a statistics object returned by Z3.
+    pub(crate) unsafe fn from_raw(ctx: &'ctx Context, s: Z3_stats) -> Statistics<'ctx> {
+        ctx.check_null(s);
+        Z3_stats_inc_ref(ctx.z3_ctx, s);
+        Statistics { ctx, z3_stats: s }
+    }
+
+    /// Return the number of entries.
+    pub fn len(&self) -> usize {
+        unsafe { Z3_stats_size(self.ctx.z3_ctx, self.z3_stats) as usize }
+    }
+
+    /// Return `true` if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the value of the entry named `key`, if any.
+    pub fn get(&self, key: &str) -> Option<StatisticsValue> {
+        self.entries().find(|e| e.key == key).map(|e| e.value)
+    }
+
+    /// Iterate over all entries.
+    pub fn entries(&self) -> impl Iterator<Item = StatisticsEntry> + '_ {
+        (0..self.len() as u32).map(move |i| self.entry(i))
+    }
+
+    fn entry(&self, idx: u32) -> StatisticsEntry {
+        unsafe {
+            let key = Z3_stats_get_key(self.ctx.z3_ctx, self.z3_stats, idx);
+            let key = CStr::from_ptr(key).to_string_lossy().into_owned();
+            let value = if Z3_stats_is_uint(self.ctx.z3_ctx, self.z3_stats, idx) {
+                StatisticsValue::UInt(Z3_stats_get_uint_value(self.ctx.z3_ctx, self.z3_stats, idx))
+            } else {
+                StatisticsValue::Double(Z3_stats_get_double_value(
+                    self.ctx.z3_ctx,
+                    self.z3_stats,
+                    idx,
+                ))
+            };
+            StatisticsEntry { key, value }
+        }
+    }
+}
+
+impl<'ctx> fmt::Display for Statistics<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        let p = unsafe { Z3_stats_to_string(self.ctx.z3_ctx, self.z3_stats) };
+        if p.is_null() {
+            return Result::Err(fmt::Error);
+        }
+        match unsafe { CStr::from_ptr(p) }.to_str() {
+            Ok(s) => write!(f, "{}", s),
+            Err(_) => Result::Err(fmt::Error),
+        }
+    }
+}
+
+impl<'ctx> Drop for Statistics<'ctx> {
+    fn drop(&mut self) {
+        unsafe { Z3_stats_dec_ref(self.ctx.z3_ctx, self.z3_stats) };
+    }
+}
@@ -1,26 +1,34 @@
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
+use std::fmt;
 use z3_sys::*;
 use Context;
 use Symbol;
-use Z3_MUTEX;
 
 impl<'ctx> Symbol<'ctx> {
+    /// Wrap a symbol returned by Z3.
+    pub(crate) fn wrap(ctx: &'ctx Context, z3_sym: Z3_symbol) -> Symbol<'ctx> {
+        Symbol {
+            ctx,
+            cst: None,
+            z3_sym,
+        }
+    }
+
     /// Create a Z3 symbol using an integer.
     ///
-    /// NB. Not all integers can be passed to this function.
-    /// The legal range of unsigned integers is 0 to 2^30-1.
+    /// # Panics
+    ///
+    /// If `i` is not in the legal range of 0 to 2^30-1.
     ///
     /// # See also:
     ///
     /// - [`Symbol::from_string()`](#method.from_string)
-    pub fn from_int(ctx: &Context, i: u32) -> Symbol {
+    pub fn from_int(ctx: &Context, i: u32) -> Symbol<'_> {
+        assert!(i < 1 << 30, "integer symbols must be below 2^30");
         Symbol {
             ctx,
             cst: None,
-            z3_sym: {
-                let guard = Z3_MUTEX.lock().unwrap();
-                unsafe { Z3_mk_int_symbol(ctx.z3_ctx, i as ::std::os::raw::c_int) }
-            },
+            z3_sym: { unsafe { Z3_mk_int_symbol(ctx.z3_ctx, i as ::std::os::raw::c_int) } },
         }
     }
 
@@ -35,10 +43,52 @@ impl<'ctx> Symbol<'ctx> {
         Symbol {
             ctx,
             cst: Some(ss),
-            z3_sym: unsafe {
-                let guard = Z3_MUTEX.lock().unwrap();
-                Z3_mk_string_symbol(ctx.z3_ctx, p)
-            },
+            z3_sym: unsafe { Z3_mk_string_symbol(ctx.z3_ctx, p) },
+        }
+    }
+
+    /// Return whether this symbol was created from an integer or a
+    /// string.
+    pub fn kind(&self) -> SymbolKind {
+        unsafe { Z3_get_symbol_kind(self.ctx.z3_ctx, self.z3_sym) }
+    }
+
+    /// Return the integer of an integer symbol, or `None` for a string
+    /// symbol.
+    pub fn as_int(&self) -> Option<u32> {
+        if self.kind() != SymbolKind::Int {
+            return None;
         }
+        Some(unsafe { Z3_get_symbol_int(self.ctx.z3_ctx, self.z3_sym) } as u32)
+    }
+
+    /// Return the string of a string symbol, or `None` for an integer
+    /// symbol.
+    pub fn as_string(&self) -> Option<String> {
+        if self.kind() != SymbolKind::String {
+            return None;
+        }
+        unsafe {
+            let p = Z3_get_symbol_string(self.ctx.z3_ctx, self.z3_sym);
+            Some(CStr::from_ptr(p).to_string_lossy().into_owned())
+        }
+    }
+}
+
+impl<'ctx> fmt::Display for Symbol<'ctx> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self.as_string() {
+            Some(s) => write!(f, "{}", s),
+            None => write!(f, "{}", self.as_int().unwrap()),
+        }
+    }
+}
+
+// Z3 interns symbols, so equal symbols are the same pointer.
+impl<'ctx> PartialEq for Symbol<'ctx> {
+    fn eq(&self, other: &Symbol<'ctx>) -> bool {
+        self.z3_sym == other.z3_sym
     }
 }
+
+impl<'ctx> Eq for Symbol<'ctx> {}
@@ -0,0 +1,234 @@
+use std::ffi::{CStr, CString};
+use z3_sys::*;
+use ApplyResult;
+use Context;
+use Goal;
+use ParamDescrs;
+use Params;
+use Probe;
+use Tactic;
+use Z3Error;
+
+impl<'ctx> Tactic<'ctx> {
+    /// Return the names of all built-in tactics.
+    pub fn list_all(ctx: &'ctx Context) -> Vec<String> {
+        unsafe {
+            let n = Z3_get_num_tactics(ctx.z3_ctx);
+            (0..n)
+                .map(|i| {
+                    let p = Z3_get_tactic_name(ctx.z3_ctx, i);
+                    CStr::from_ptr(p).to_string_lossy().into_owned()
+                })
+                .collect()
+        }
+    }
+
+    /// Create the built-in tactic called `name`, e.g. `simplify`, `qe` or
+    /// `smt`.
+    ///
+    /// # Panics
+    ///
+    /// If there is no tactic called `name`.
+    ///
+    /// # See also:
+    ///
+    /// - [`Tactic::list_all()`](#method.list_all)
+    pub fn new(ctx: &'ctx Context, name: &str) -> Tactic<'ctx> {
+        assert!(
+            Tactic::list_all(ctx).iter().any(|n| n == name),
+            "unknown tactic: {}",
+            name
+        );
+        let name = CString::new(name).unwrap();
+        unsafe { Tactic::from_raw(ctx, Z3_mk_tactic(ctx.z3_ctx, name.as_ptr())) }
+    }
+
+    /// Wrap a tactic returned by Z3.
+    unsafe fn from_raw(ctx: &'ctx Context, t: Z3_tactic) -> Tactic<'ctx> {
+        ctx.check_null(t);
+        Z3_tactic_inc_ref(ctx.z3_ctx, t);
+        Tactic { ctx, z3_tactic: t }
+    }
+
+    /// Return a tactic that leaves its input unchanged.
+    pub fn skip(ctx: &'ctx Context) -> Tactic<'ctx> {
+        unsafe { Tactic::from_raw(ctx, Z3_tactic_skip(ctx.z3_ctx)) }
+    }
+
+    /// Return a tactic that always fails.
+    pub fn fail(ctx: &'ctx Context) -> Tactic<'ctx> {
+        unsafe { Tactic::from_raw(ctx, Z3_tactic_fail(ctx.z3_ctx)) }
+    }
+
+    /// Return a tactic that applies `self` and then `then` to each of the
+    /// resulting subgoals.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let t = Tactic::new(&ctx, "simplify")
+    ///     .and_then(&Tactic::new(&ctx, "solve-eqs"))
+    ///     .and_then(&Tactic::new(&ctx, "smt"));
+    /// ```
+    pub fn and_then(&self, then: &Tactic<'ctx>) -> Tactic<'ctx> {
+        unsafe {
+            let t = Z3_tactic_and_then(self.ctx.z3_ctx, self.z3_tactic, then.z3_tactic);
+            Tactic::from_raw(self.ctx, t)
+        }
+    }
+
+    /// Return a tactic that applies `self`, and if it fails, applies
+    /// `other` instead.
+    pub fn or_else(&self, other: &Tactic<'ctx>) -> Tactic<'ctx> {
+        unsafe {
+            let t = Z3_tactic_or_else(self.ctx.z3_ctx, self.z3_tactic, other.z3_tactic);
+            Tactic::from_raw(self.ctx, t)
+        }
+    }
+
+    /// Return a tactic that keeps applying `self` until no subgoal is
+    /// modified anymore, or `max` iterations have been performed.
+    pub fn repeat(&self, max: u32) -> Tactic<'ctx> {
+        unsafe {
+            let t = Z3_tactic_repeat(self.ctx.z3_ctx, self.z3_tactic, max);
+            Tactic::from_raw(self.ctx, t)
+        }
+    }
+
+    /// Return a tactic that applies `self` but fails if it does not
+    /// terminate within `ms` milliseconds.
+    pub fn try_for(&self, ms: u32) -> Tactic<'ctx> {
+        unsafe {
+            let t = Z3_tactic_try_for(self.ctx.z3_ctx, self.z3_tactic, ms);
+            Tactic::from_raw(self.ctx, t)
+        }
+    }
+
+    /// Return a tactic that applies `self` if `probe` evaluates to true
+    /// on the goal, and otherwise leaves the goal unchanged.
+    pub fn when(&self, probe: &Probe<'ctx>) -> Tactic<'ctx> {
+        unsafe {
+            let t = Z3_tactic_when(self.ctx.z3_ctx, probe.z3_probe, self.z3_tactic);
+            Tactic::from_raw(self.ctx, t)
+        }
+    }
+
+    /// Return a tactic that applies `then` if `probe` evaluates to true on
+    /// the goal, and `otherwise` if it does not.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let t = Tactic::cond(
+    ///     &Probe::new(&ctx, "is-propositional"),
+    ///     &Tactic::new(&ctx, "sat"),
+    ///     &Tactic::new(&ctx, "smt"),
+    /// );
+    /// ```
+    pub fn cond(
+        probe: &Probe<'ctx>,
+        then: &Tactic<'ctx>,
+        otherwise: &Tactic<'ctx>,
+    ) -> Tactic<'ctx> {
+        unsafe {
+            let t = Z3_tactic_cond(
+                then.ctx.z3_ctx,
+                probe.z3_probe,
+                then.z3_tactic,
+                otherwise.z3_tactic,
+            );
+            Tactic::from_raw(then.ctx, t)
+        }
+    }
+
+    /// Return a tactic that fails if `probe` evaluates to true on the
+    /// goal.
+    pub fn fail_if(probe: &Probe<'ctx>) -> Tactic<'ctx> {
+        unsafe {
+            let t = Z3_tactic_fail_if(probe.ctx.z3_ctx, probe.z3_probe);
+            Tactic::from_raw(probe.ctx, t)
+        }
+    }
+
+    /// Return a tactic that applies `self` using the parameters in
+    /// `params`.
+    ///
+    /// # See also:
+    ///
+    /// - [`Tactic::get_param_descrs()`](#method.get_param_descrs)
+    pub fn with_params(&self, params: &Params<'ctx>) -> Tactic<'ctx> {
+        unsafe {
+            let t = Z3_tactic_using_params(self.ctx.z3_ctx, self.z3_tactic, params.z3_params);
+            Tactic::from_raw(self.ctx, t)
+        }
+    }
+
+    /// Apply the tactic to `goal`.
+    ///
+    /// Returns an error if the tactic fails, e.g. if `goal` contains
+    /// formulas the tactic does not support.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use z3::*;
+    ///
+    /// let cfg = Config::new();
+    /// let ctx = Context::new(&cfg);
+    /// let x = ctx.named_int_const("x");
+    /// let y = ctx.named_int_const("y");
+    ///
+    /// let goal = Goal::new(&ctx, true, false, false);
+    /// goal.assert(&x._eq(&y.add(&[&ctx.from_i64(1)])));
+    /// goal.assert(&x.gt(&ctx.from_i64(5)));
+    ///
+    /// let result = Tactic::new(&ctx, "solve-eqs").apply(&goal).unwrap();
+    /// assert_eq!(result.len(), 1);
+    /// assert_eq!(result.subgoal(0).len(), 1);
+    /// ```
+    pub fn apply(&self, goal: &Goal<'ctx>) -> Result<ApplyResult<'ctx>, Z3Error> {
+        unsafe {
+            let c = self.ctx.z3_ctx;
+            let r = self
+                .ctx
+                .try_z3(|| Z3_tactic_apply(c, self.z3_tactic, goal.z3_goal))?;
+            Ok(ApplyResult::from_raw(self.ctx, r))
+        }
+    }
+
+    /// Return a description of the parameters accepted by the tactic.
+    pub fn get_help(&self) -> String {
+        unsafe {
+            let p = Z3_tactic_get_help(self.ctx.z3_ctx, self.z3_tactic);
+            CStr::from_ptr(p).to_string_lossy().into_owned()
+        }
+    }
+
+    /// Return descriptions of the parameters accepted by the tactic.
+    pub fn get_param_descrs(&self) -> ParamDescrs<'ctx> {
+        unsafe {
+            let d = Z3_tactic_get_param_descrs(self.ctx.z3_ctx, self.z3_tactic);
+            ParamDescrs::from_raw(self.ctx, d)
+        }
+    }
+}
+
+impl<'ctx> Clone for Tactic<'ctx> {
+    fn clone(&self) -> Tactic<'ctx> {
+        unsafe { Tactic::from_raw(self.ctx, self.z3_tactic) }
+    }
+}
+
+impl<'ctx> Drop for Tactic<'ctx> {
+    fn drop(&mut self) {
+        unsafe { Z3_tactic_dec_ref(self.ctx.z3_ctx, self.z3_tactic) };
+    }
+}
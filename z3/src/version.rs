@@ -0,0 +1,57 @@
+use std::ffi::CStr;
+use std::fmt;
+use z3_sys::*;
+use Version;
+
+/// Return the version of the linked Z3 library.
+///
+/// # Example
+///
+/// ```
+/// let v = z3::version();
+/// assert!(v.major >= 4);
+/// println!("using Z3 {}", v);
+/// ```
+///
+/// # See also:
+///
+/// - [`full_version()`](fn.full_version.html)
+pub fn version() -> Version {
+    let (mut major, mut minor, mut build_number, mut revision_number) = (0, 0, 0, 0);
+    unsafe {
+        Z3_get_version(
+            &mut major,
+            &mut minor,
+            &mut build_number,
+            &mut revision_number,
+        )
+    };
+    Version {
+        major,
+        minor,
+        build_number,
+        revision_number,
+    }
+}
+
+/// Return a string that fully describes the linked Z3 library, e.g.
+/// `"Z3 4.8.12.0"`, possibly followed by build information.
+///
+/// # See also:
+///
+/// - [`version()`](fn.version.html)
+pub fn full_version() -> String {
+    unsafe { CStr::from_ptr(Z3_get_full_version()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}.{}",
+            self.major, self.minor, self.build_number, self.revision_number
+        )
+    }
+}
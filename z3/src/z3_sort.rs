@@ -0,0 +1,272 @@
+use std::any::TypeId;
+use Ast;
+use Context;
+use DatatypeAccessor;
+use DatatypeBuilder;
+use DatatypeSort;
+use Seq;
+use Sort;
+
+/// A Rust type whose values correspond to the values of a Z3 sort.
+///
+/// This is implemented for `bool`, the integer types, which map to
+/// unbounded integers, `String` and `&str`. For structs and enums it can
+/// be derived with `#[derive(Z3Sort)]`, which requires the `derive`
+/// feature and declares a datatype with the same name as the type:
+///
+/// - a struct becomes a datatype with a single constructor named after
+///   the type, and an enum a datatype with one constructor per variant;
+/// - each field becomes an accessor, named after the field, or
+///   `<constructor>_<index>` for tuple fields;
+/// - fields of the type itself, `Self`, or of other derived types that
+///   refer back to it, possibly through a `Box`, make the datatype
+///   recursive. Mutually recursive types are declared together.
+///
+/// A derived type also implements [`Z3Datatype`](trait.Z3Datatype.html),
+/// which gives access to the constructors, testers and accessors. Its
+/// datatype is declared once per context, the first time it is needed.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "derive")]
+/// # {
+/// use z3::*;
+///
+/// #[derive(Z3Sort)]
+/// enum Shape {
+///     Circle { radius: i64 },
+///     Rect(i64, i64),
+/// }
+///
+/// let cfg = Config::new();
+/// let ctx = Context::new(&cfg);
+/// let datatype = Shape::z3_datatype(&ctx);
+/// let shape = ctx.named_const("shape", &datatype.sort);
+/// let rect = &datatype.variants[1];
+///
+/// let solver = Solver::new(&ctx);
+/// solver.assert(&shape._eq(&Shape::Rect(2, 3).to_z3(&ctx)));
+/// solver.assert(&rect.accessors[1].apply(&[&shape])._eq(&ctx.from_i64(3)));
+/// assert_eq!(solver.check(), SatResult::Sat);
+/// # }
+/// ```
+pub trait Z3Sort {
+    /// Return the sort of the values of this type.
+    fn z3_sort(ctx: &Context) -> Sort<'_>;
+
+    /// Return `self` as a Z3 value.
+    fn to_z3<'ctx>(&self, ctx: &'ctx Context) -> Ast<'ctx>;
+
+    /// Return the sort of a datatype field of this type, where `group`
+    /// holds the derived datatypes that are being declared.
+    #[doc(hidden)]
+    fn z3_field<'ctx>(
+        ctx: &'ctx Context,
+        group: &mut DatatypeGroup<'ctx>,
+    ) -> DatatypeAccessor<'ctx> {
+        DatatypeAccessor::Sort(Self::z3_sort(ctx))
+    }
+}
+
+/// A Rust type that corresponds to a Z3 datatype, usually through
+/// `#[derive(Z3Sort)]`.
+pub trait Z3Datatype: Z3Sort {
+    /// Return the datatype, declaring it if `ctx` does not have it yet.
+    fn z3_datatype(ctx: &Context) -> DatatypeSort<'_>;
+}
+
+/// Declarations of derived datatypes in progress, used by
+/// `#[derive(Z3Sort)]`.
+///
+/// Declaring a type declares the types of its fields first. Types that
+/// refer to each other, found like the strongly connected components of
+/// Tarjan's algorithm, are declared together with
+/// [`DatatypeBuilder::create_datatypes()`](struct.DatatypeBuilder.html#method.create_datatypes).
+pub struct DatatypeGroup<'ctx> {
+    ctx: &'ctx Context,
+    /// Types not declared yet, in the order they were reached.
+    pending: Vec<PendingDatatype<'ctx>>,
+    /// Indices into `pending` of the types whose fields are being
+    /// collected, innermost last.
+    active: Vec<usize>,
+}
+
+struct PendingDatatype<'ctx> {
+    id: TypeId,
+    name: String,
+    /// Index of the earliest pending type this one refers to, directly
+    /// or through its fields.
+    low: usize,
+    builder: Option<DatatypeBuilder<'ctx>>,
+}
+
+impl<'ctx> DatatypeGroup<'ctx> {
+    /// Return the datatype of `T`, declaring it if `ctx` does not have it
+    /// yet.
+    pub fn datatype<T: Z3Sort + 'static>(ctx: &'ctx Context) -> DatatypeSort<'ctx> {
+        DatatypeSort::from_sort(DatatypeGroup::sort::<T>(ctx))
+    }
+
+    /// Return the sort of the datatype of `T`, declaring it if `ctx` does
+    /// not have it yet.
+    pub fn sort<T: Z3Sort + 'static>(ctx: &'ctx Context) -> Sort<'ctx> {
+        if let Some(sort) = ctx.cached_datatype(TypeId::of::<T>()) {
+            return sort;
+        }
+        let mut group = DatatypeGroup {
+            ctx,
+            pending: Vec::new(),
+            active: Vec::new(),
+        };
+        T::z3_field(ctx, &mut group);
+        ctx.cached_datatype(TypeId::of::<T>()).unwrap()
+    }
+
+    /// Return the sort of a field of type `T`, a derived type whose
+    /// datatype is named `name` and returned by `declare`.
+    pub fn field<T, F>(&mut self, name: &str, declare: F) -> DatatypeAccessor<'ctx>
+    where
+        T: 'static,
+        F: FnOnce(&mut DatatypeGroup<'ctx>) -> DatatypeBuilder<'ctx>,
+    {
+        let id = TypeId::of::<T>();
+        if let Some(sort) = self.ctx.cached_datatype(id) {
+            return DatatypeAccessor::Sort(sort);
+        }
+        let index = match self.pending.iter().position(|p| p.id == id) {
+            Some(index) => index,
+            None => self.declare(id, name, declare),
+        };
+        if index < self.pending.len() {
+            // Still pending, so part of a cycle through the active types.
+            if let Some(&outer) = self.active.last() {
+                let low = &mut self.pending[outer].low;
+                *low = (*low).min(index);
+            }
+            return DatatypeAccessor::Datatype(name.to_owned());
+        }
+        DatatypeAccessor::Sort(self.ctx.cached_datatype(id).unwrap())
+    }
+
+    /// Collect the fields of `id` and declare it, together with the
+    /// types that refer back to it, unless it refers to a type that is
+    /// still being collected. Return its index into `pending`.
+    fn declare<F>(&mut self, id: TypeId, name: &str, declare: F) -> usize
+    where
+        F: FnOnce(&mut DatatypeGroup<'ctx>) -> DatatypeBuilder<'ctx>,
+    {
+        let index = self.pending.len();
+        self.pending.push(PendingDatatype {
+            id,
+            name: name.to_owned(),
+            low: index,
+            builder: None,
+        });
+        self.active.push(index);
+        let builder = declare(self);
+        self.active.pop();
+        self.pending[index].builder = Some(builder);
+
+        let low = self.pending[index].low;
+        if low < index {
+            let outer = self.active.last().unwrap();
+            let outer_low = &mut self.pending[*outer].low;
+            *outer_low = (*outer_low).min(low);
+            return index;
+        }
+        let group: Vec<PendingDatatype> = self.pending.drain(index..).collect();
+        for (i, p) in group.iter().enumerate() {
+            assert!(
+                group[..i].iter().all(|q| q.name != p.name),
+                "mutually recursive datatypes with the same name `{}`",
+                p.name
+            );
+        }
+        let (ids, builders): (Vec<TypeId>, _) = group
+            .into_iter()
+            .map(|p| (p.id, p.builder.unwrap()))
+            .unzip();
+        let datatypes = DatatypeBuilder::create_datatypes(builders);
+        for (id, datatype) in ids.into_iter().zip(datatypes) {
+            self.ctx.cache_datatype(id, &datatype.sort);
+        }
+        index
+    }
+}
+
+impl Z3Sort for bool {
+    fn z3_sort(ctx: &Context) -> Sort<'_> {
+        Sort::bool(ctx)
+    }
+
+    fn to_z3<'ctx>(&self, ctx: &'ctx Context) -> Ast<'ctx> {
+        ctx.from_bool(*self)
+    }
+}
+
+macro_rules! impl_z3_sort_int {
+    ( $( $ty:ident => $from:ident as $as:ident ),* ) => {
+        $(
+            impl Z3Sort for $ty {
+                fn z3_sort(ctx: &Context) -> Sort<'_> {
+                    Sort::int(ctx)
+                }
+
+                fn to_z3<'ctx>(&self, ctx: &'ctx Context) -> Ast<'ctx> {
+                    ctx.$from(*self as $as)
+                }
+            }
+        )*
+    };
+}
+
+impl_z3_sort_int!(
+    i8 => from_i64 as i64,
+    i16 => from_i64 as i64,
+    i32 => from_i64 as i64,
+    i64 => from_i64 as i64,
+    isize => from_i64 as i64,
+    u8 => from_u64 as u64,
+    u16 => from_u64 as u64,
+    u32 => from_u64 as u64,
+    u64 => from_u64 as u64,
+    usize => from_u64 as u64
+);
+
+impl Z3Sort for String {
+    fn z3_sort(ctx: &Context) -> Sort<'_> {
+        Sort::string(ctx)
+    }
+
+    fn to_z3<'ctx>(&self, ctx: &'ctx Context) -> Ast<'ctx> {
+        Seq::from_string(ctx, self).into()
+    }
+}
+
+impl Z3Sort for &str {
+    fn z3_sort(ctx: &Context) -> Sort<'_> {
+        Sort::string(ctx)
+    }
+
+    fn to_z3<'ctx>(&self, ctx: &'ctx Context) -> Ast<'ctx> {
+        Seq::from_string(ctx, self).into()
+    }
+}
+
+impl<T: Z3Sort> Z3Sort for Box<T> {
+    fn z3_sort(ctx: &Context) -> Sort<'_> {
+        T::z3_sort(ctx)
+    }
+
+    fn to_z3<'ctx>(&self, ctx: &'ctx Context) -> Ast<'ctx> {
+        (**self).to_z3(ctx)
+    }
+
+    fn z3_field<'ctx>(
+        ctx: &'ctx Context,
+        group: &mut DatatypeGroup<'ctx>,
+    ) -> DatatypeAccessor<'ctx> {
+        T::z3_field(ctx, group)
+    }
+}
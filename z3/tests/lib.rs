@@ -5,6 +5,15 @@ extern crate log;
 extern crate z3;
 use z3::*;
 
+#[cfg(feature = "num")]
+extern crate num_bigint;
+#[cfg(feature = "num")]
+extern crate num_rational;
+#[cfg(feature = "rug")]
+extern crate rug;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+
 #[test]
 fn test_config() {
     let _ = env_logger::try_init();
@@ -39,7 +48,7 @@ fn test_solving() {
 
     let solver = Solver::new(&ctx);
     solver.assert(&x.gt(&y));
-    assert!(solver.check());
+    assert_eq!(solver.check(), SatResult::Sat);
 }
 
 #[test]
@@ -58,11 +67,11 @@ fn test_solving_for_model() {
     solver.assert(&y.gt(&zero));
     solver.assert(&y.rem(&seven)._eq(&two));
     solver.assert(&x.add(&[&two]).gt(&seven));
-    assert!(solver.check());
+    assert_eq!(solver.check(), SatResult::Sat);
 
-    let model = solver.get_model();
-    let xv = model.eval(&x).unwrap().as_i64().unwrap();
-    let yv = model.eval(&y).unwrap().as_i64().unwrap();
+    let model = solver.get_model().unwrap();
+    let xv = model.eval(&x, true).unwrap().as_i64().unwrap();
+    let yv = model.eval(&y, true).unwrap().as_i64().unwrap();
     info!("x: {}", xv);
     info!("y: {}", yv);
     assert!(xv > yv);
@@ -81,11 +90,11 @@ fn test_cloning_ast() {
 
     let solver = Solver::new(&ctx);
     solver.assert(&x._eq(&zero));
-    assert!(solver.check());
+    assert_eq!(solver.check(), SatResult::Sat);
 
-    let model = solver.get_model();
-    let xv = model.eval(&x).unwrap().as_i64().unwrap();
-    let yv = model.eval(&y).unwrap().as_i64().unwrap();
+    let model = solver.get_model().unwrap();
+    let xv = model.eval(&x, true).unwrap().as_i64().unwrap();
+    let yv = model.eval(&y, true).unwrap().as_i64().unwrap();
     assert_eq!(xv, 0);
     assert_eq!(yv, 0);
 }
@@ -109,14 +118,3701 @@ fn test_pb_ops_model() {
     let y = ctx.named_bool_const("y");
 
     let coeffs = vec![1, 1];
-    let other_args = vec![&y];
+    let other_args = [&y];
     let solver = Solver::new(&ctx);
     solver.assert(&x.pb_eq(&other_args[..], coeffs, 1));
-    assert!(solver.check());
-    let model = solver.get_model();
-    let xv = model.eval(&x).unwrap().as_bool().unwrap();
-    let yv = model.eval(&y).unwrap().as_bool().unwrap();
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    let xv = model.eval(&x, true).unwrap().as_bool().unwrap();
+    let yv = model.eval(&y, true).unwrap().as_bool().unwrap();
     info!("x: {}", xv);
     info!("y: {}", yv);
     assert!((xv && !yv) || (!xv && yv));
 }
+
+#[test]
+fn test_ast_operators() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+    let z = ctx.named_int_const("z");
+    let a = ctx.named_bool_const("a");
+    let b = ctx.named_bool_const("b");
+    let bv = ctx.named_bitvector_const("bv", 8);
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&(&x + &y)._eq(&ctx.from_i64(3)));
+    solver.assert(&(-&z)._eq(&ctx.from_i64(-2)));
+    solver.assert(&((&x + &y) * &z - &x)._eq(&ctx.from_i64(5)));
+    solver.assert(&!(&a & &b));
+    solver.assert(&(&a | &b));
+    solver.assert(&(&a ^ &b)._eq(&ctx.from_bool(true)));
+    solver.assert(
+        &(&bv << ctx.bitvector_sort(8).from_u64(4))._eq(&ctx.bitvector_sort(8).from_u64(0x50)),
+    );
+    solver.assert(
+        &(&bv >> ctx.bitvector_sort(8).from_u64(3))._eq(&ctx.bitvector_sort(8).from_u64(0)),
+    );
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    let model = solver.get_model().unwrap();
+    assert_eq!(model.eval(&x, true).unwrap().as_i64().unwrap(), 1);
+    assert_eq!(model.eval(&z, true).unwrap().as_i64().unwrap(), 2);
+    assert_eq!(model.eval(&bv, true).unwrap().as_u64().unwrap(), 0x05);
+}
+
+#[test]
+fn test_quantifier_with_pattern() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let int = ctx.int_sort();
+    let array_sort = ctx.array_sort(&int, &int);
+    let a = ctx.named_const("a", &array_sort);
+    let i = ctx.named_int_const("i");
+    let zero = ctx.from_i64(0);
+
+    let pattern = Pattern::new(&ctx, &[&a.select(&i)]);
+    let axiom = Ast::quantifier_const(
+        &ctx,
+        true,
+        1,
+        Some(&ctx.str_sym("zeroed")),
+        None,
+        &[&i],
+        &[&pattern],
+        &[],
+        &a.select(&i)._eq(&zero),
+    );
+    assert!(format!("{}", axiom).contains(":pattern"));
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&axiom);
+    solver.push();
+    solver.assert(&a.select(&ctx.from_i64(5))._eq(&ctx.from_i64(1)));
+    assert_eq!(solver.check(), SatResult::Unsat);
+    solver.pop(1);
+
+    let j = ctx.named_int_const("j");
+    solver.assert(&Ast::exists_const(
+        &ctx,
+        &[&j],
+        &[],
+        &a.select(&j)._eq(&zero),
+    ));
+    assert_eq!(solver.check(), SatResult::Sat);
+}
+
+#[test]
+fn test_bitvector_type() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = BV::new_const(&ctx, "x", 8);
+    assert_eq!(x.get_size(), 8);
+
+    // Swapping the nibbles of a byte is a rotation by four.
+    let swapped = x.extract(3, 0).concat(&x.extract(7, 4));
+    assert_eq!(swapped.get_size(), 8);
+    let solver = Solver::new(&ctx);
+    solver.assert(&swapped._eq(&x.rotate_left(4)).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let solver = Solver::new(&ctx);
+    let minus_one = BV::from_i64(&ctx, -1, 8);
+    solver.assert(&minus_one.sign_ext(8)._eq(&BV::from_u64(&ctx, 0xffff, 16)));
+    solver.assert(&minus_one.zero_ext(8)._eq(&BV::from_u64(&ctx, 0xff, 16)));
+    solver.assert(&x.bvslt(&BV::from_i64(&ctx, 0, 8)));
+    solver.assert(&(&x + BV::from_u64(&ctx, 1, 8)).bvugt(&x));
+    solver.assert(&(&x * BV::from_u64(&ctx, 2, 8))._eq(&BV::from_u64(&ctx, 0xfc, 8)));
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    let model = solver.get_model().unwrap();
+    assert_eq!(model.eval(&x, true).unwrap().as_u64().unwrap(), 0xfe);
+    assert!(BV::from_ast(ctx.named_int_const("i")).is_none());
+}
+
+#[test]
+fn test_array_type() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let addr_sort = ctx.bitvector_sort(32);
+    let byte_sort = ctx.bitvector_sort(8);
+    let mem = Array::new_const(&ctx, "mem", &addr_sort, &byte_sort);
+    let p = ctx.named_bitvector_const("p", 32);
+    let q = ctx.named_bitvector_const("q", 32);
+
+    let mem2 = mem.store(&p, &byte_sort.from_u64(0xaa));
+    let solver = Solver::new(&ctx);
+    solver.assert(&p._eq(&q).not());
+    solver.assert(&mem2.select(&q)._eq(&mem.select(&q)).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let matrix = Array::const_array(
+        &ctx,
+        &ctx.int_sort(),
+        &Array::const_array(&ctx, &ctx.int_sort(), &ctx.from_i64(7)),
+    );
+    let row = Array::from_ast(matrix.select(&ctx.from_i64(1))).unwrap();
+    let solver = Solver::new(&ctx);
+    solver.assert(&row.select(&ctx.from_i64(2))._eq(&ctx.from_i64(7)).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    assert!(Array::from_ast(mem.clone().into()).is_some());
+    assert!(Array::from_ast(p).is_none());
+}
+
+#[test]
+fn test_string_constraints() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let s = Seq::new_string_const(&ctx, "s");
+    let t = Seq::new_string_const(&ctx, "t");
+    let three = ctx.from_i64(3);
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&s.length()._eq(&ctx.from_i64(6)));
+    solver.assert(&s.starts_with(&Seq::from_string(&ctx, "ab")));
+    solver.assert(&s.ends_with(&Seq::from_string(&ctx, "42")));
+    solver.assert(
+        &s.substr(&ctx.from_i64(2), &ctx.from_i64(2))
+            .to_int()
+            ._eq(&Ast::from_i64(&ctx, 17)),
+    );
+    solver.assert(
+        &s.index_of(&Seq::from_string(&ctx, "7"), &ctx.from_i64(0))
+            ._eq(&three),
+    );
+    solver.assert(&t._eq(&s.replace(&Seq::from_string(&ctx, "ab"), &Seq::from_int(&three))));
+    solver.assert(&t.contains(&Seq::from_string(&ctx, "31")));
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    let model = solver.get_model().unwrap();
+    let sv = Seq::from_ast(model.eval(&s, true).unwrap()).unwrap();
+    let tv = Seq::from_ast(model.eval(&t, true).unwrap()).unwrap();
+    assert_eq!(sv.as_string().unwrap(), "ab1742");
+    assert_eq!(tv.as_string().unwrap(), "31742");
+    assert!(s.at(&three).as_string().is_none());
+}
+
+#[test]
+fn test_regexp_membership() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let chr = |c: &str| Seq::from_string(&ctx, c);
+    let lower = Regexp::range(&chr("a"), &chr("z"));
+    let digit = Regexp::range(&chr("0"), &chr("9"));
+    let ident = lower.concat(&[&lower.union(&[&digit]).star()]);
+    let keyword = Regexp::literal(&chr("if"));
+    let s = Seq::new_string_const(&ctx, "s");
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&s.in_re(&ident.intersect(&[&keyword.complement()])));
+    solver.assert(&s.length()._eq(&ctx.from_i64(2)));
+    solver.assert(&s.starts_with(&chr("i")));
+    solver.assert(&s.ends_with(&chr("f")));
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let re_sort = ctx.regexp_sort(&ctx.string_sort());
+    let solver = Solver::new(&ctx);
+    solver.assert(&s.in_re(&Regexp::literal(&chr("x")).repeat(2, 3)));
+    solver.assert(&s.in_re(&Regexp::full(&ctx, &re_sort)));
+    solver.assert(&s.length()._eq(&ctx.from_i64(4)));
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&s.in_re(&Regexp::empty(&ctx, &re_sort)));
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_float_arithmetic() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let rne = RoundingMode::NearestTiesToEven.to_ast(&ctx);
+    let rtz = RoundingMode::TowardZero.to_ast(&ctx);
+
+    let a = Float::from_f64(&ctx, 0.1);
+    let b = Float::from_f64(&ctx, 0.2);
+    let c = Float::from_f64(&ctx, 0.3);
+    assert_eq!(a.get_ebits(), 11);
+    assert_eq!(a.get_sbits(), 53);
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&a.add(&rne, &b).fp_eq(&c));
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let f32_sort = ctx.float32_sort();
+    let nan = Float::nan(&ctx, &f32_sort);
+    let x = Float::new_const(&ctx, "x", &f32_sort);
+    let solver = Solver::new(&ctx);
+    solver.assert(&nan.fp_eq(&nan));
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&x.is_nan().not());
+    solver.assert(&x.lt(&Float::zero(&ctx, &f32_sort, false)));
+    solver.assert(&x.abs().gt(&Float::from_f32(&ctx, 10.0)));
+    solver.assert(&x.to_sbv(&rtz, 32)._eq(&BV::from_i64(&ctx, -12, 32)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    let xv = Float::from_ast(model.eval(&x, true).unwrap()).unwrap();
+    assert_eq!(
+        model.eval(&xv.is_negative(), true).unwrap().as_bool(),
+        Some(true)
+    );
+
+    let one = Float::from_ieee_bv(&BV::from_u64(&ctx, 0x3f80_0000, 32), &f32_sort);
+    let solver = Solver::new(&ctx);
+    solver.assert(&one.to_real()._eq(&ctx.from_real(1, 1)).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_datatype_builder_invalid() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let unknown_field = || {
+        DatatypeBuilder::new(&ctx, "Tree")
+            .variant("leaf", vec![])
+            .variant(
+                "node",
+                vec![("child", DatatypeAccessor::Datatype("Forest".to_owned()))],
+            )
+            .finish()
+    };
+    let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(unknown_field)).err();
+    let msg = err.unwrap().downcast::<String>().unwrap();
+    assert_eq!(*msg, "unknown datatype `Forest` in field sort");
+    let no_variants = || DatatypeBuilder::new(&ctx, "Empty").finish();
+    let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(no_variants)).err();
+    let msg = err.unwrap().downcast::<String>().unwrap();
+    assert_eq!(*msg, "datatype `Empty` has no variants");
+
+    // The context is still usable after the panics.
+    let unit = DatatypeBuilder::new(&ctx, "Unit")
+        .variant("unit", vec![])
+        .finish();
+    let u = ctx.named_const("u", &unit.sort);
+    let solver = Solver::new(&ctx);
+    solver.assert(&unit.variants[0].tester.apply(&[&u]));
+    assert_eq!(solver.check(), SatResult::Sat);
+}
+
+#[test]
+fn test_datatype_builder() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+
+    // An enumeration and an option-like datatype.
+    let color = DatatypeBuilder::new(&ctx, "Color")
+        .variant("Red", vec![])
+        .variant("Green", vec![])
+        .finish();
+    let c = Ast::new_const(&ctx.str_sym("c"), &color.sort);
+    let red = color.variants[0].constructor.apply(&[]);
+    let green = color.variants[1].constructor.apply(&[]);
+    let solver = Solver::new(&ctx);
+    solver.assert(&c._eq(&red).not());
+    solver.assert(&c._eq(&green).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    // Mutually recursive trees and forests.
+    let mut sorts = DatatypeBuilder::create_datatypes(vec![
+        DatatypeBuilder::new(&ctx, "Tree").variant(
+            "node",
+            vec![
+                ("value", DatatypeAccessor::Sort(ctx.int_sort())),
+                ("children", DatatypeAccessor::Datatype("Forest".to_owned())),
+            ],
+        ),
+        DatatypeBuilder::new(&ctx, "Forest")
+            .variant("empty", vec![])
+            .variant(
+                "grow",
+                vec![
+                    ("first", DatatypeAccessor::Datatype("Tree".to_owned())),
+                    ("rest", DatatypeAccessor::Datatype("Forest".to_owned())),
+                ],
+            ),
+    ]);
+    let forest = sorts.pop().unwrap();
+    let tree = sorts.pop().unwrap();
+    assert_eq!(format!("{}", tree.sort), "Tree");
+    assert_eq!(tree.variants[0].accessors.len(), 2);
+    assert_eq!(forest.variants[1].constructor.arity(), 2);
+
+    let t = Ast::new_const(&ctx.str_sym("t"), &tree.sort);
+    let children = tree.variants[0].accessors[1].apply(&[&t]);
+    let is_grow = forest.variants[1].tester.apply(&[&children]);
+    let first = forest.variants[1].accessors[0].apply(&[&children]);
+    let solver = Solver::new(&ctx);
+    solver.assert(&is_grow);
+    solver.assert(&first._eq(&t));
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_tuple_sort() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let (point, mk_point, proj) = Sort::tuple(
+        &ctx,
+        &ctx.str_sym("Point"),
+        &[
+            (&ctx.str_sym("x"), &ctx.int_sort()),
+            (&ctx.str_sym("y"), &ctx.bool_sort()),
+        ],
+    );
+    assert_eq!(format!("{}", point), "Point");
+    assert_eq!(mk_point.arity(), 2);
+    assert_eq!(proj.len(), 2);
+
+    let p = Ast::new_const(&ctx.str_sym("p"), &point);
+    let q = Ast::new_const(&ctx.str_sym("q"), &point);
+    let solver = Solver::new(&ctx);
+    solver.assert(&proj[0].apply(&[&p])._eq(&proj[0].apply(&[&q])));
+    solver.assert(&proj[1].apply(&[&p])._eq(&proj[1].apply(&[&q])));
+    solver.assert(&p._eq(&q).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_enumeration_sort() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let (color, consts, testers) =
+        Sort::enumeration(&ctx, &ctx.str_sym("Color"), &["Red", "Green", "Blue"]);
+    assert_eq!(consts.len(), 3);
+    assert_eq!(testers.len(), 3);
+
+    let red = consts[0].apply(&[]);
+    let blue = consts[2].apply(&[]);
+    let c = Ast::new_const(&ctx.str_sym("c"), &color);
+    let solver = Solver::new(&ctx);
+    solver.assert(&c._eq(&red).not());
+    solver.assert(&testers[1].apply(&[&c]).not());
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    assert!(model.eval(&c, true).unwrap() == blue);
+    assert_eq!(format!("{}", blue), "Blue");
+
+    solver.assert(&c._eq(&blue).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_set_operations() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let int = ctx.int_sort();
+    let a = Set::new_const(&ctx, "a", &int);
+    let b = Set::new_const(&ctx, "b", &int);
+    let x = ctx.named_int_const("x");
+
+    // De Morgan: ~(a | b) == ~a & ~b
+    let solver = Solver::new(&ctx);
+    let lhs = a.union(&[&b]).complement();
+    let rhs = a.complement().intersect(&[&b.complement()]);
+    solver.assert(&lhs._eq(&rhs).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&a.add(&x).del(&x).member(&x));
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&Set::full(&ctx, &int).subset(&a));
+    solver.assert(&a.member(&x).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    assert!(Set::from_ast(x).is_none());
+    let arr = Array::new_const(&ctx, "arr", &int, &int);
+    assert!(Set::from_ast(arr.into()).is_none());
+    assert!(Set::from_ast(a.into()).is_some());
+}
+
+#[test]
+fn test_finite_domain_sort() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let fd = ctx.finite_domain_sort(&ctx.str_sym("Node"), 16);
+    assert_eq!(fd.finite_domain_size(), Some(16));
+    assert_eq!(ctx.int_sort().finite_domain_size(), None);
+
+    let n = fd.from_u64(3);
+    assert_eq!(n.as_u64(), Some(3));
+}
+
+#[test]
+fn test_uninterpreted_sort() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let node = ctx.uninterpreted_sort(&ctx.str_sym("Node"));
+    assert_eq!(format!("{}", node), "Node");
+    assert!(node == Sort::uninterpreted(&ctx, &ctx.str_sym("Node")));
+    assert!(node != Sort::uninterpreted(&ctx, &ctx.str_sym("Addr")));
+
+    let a = Ast::new_const(&ctx.str_sym("a"), &node);
+    let b = Ast::new_const(&ctx.str_sym("b"), &node);
+    let c = Ast::new_const(&ctx.str_sym("c"), &node);
+    let solver = Solver::new(&ctx);
+    solver.assert(&a._eq(&b));
+    solver.assert(&b._eq(&c));
+    assert_eq!(solver.check(), SatResult::Sat);
+    solver.assert(&a._eq(&c).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_sort_drop() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+
+    // Sorts released by their creator stay alive through the terms and
+    // declarations that use them.
+    let f = {
+        let node = ctx.uninterpreted_sort(&ctx.str_sym("Node"));
+        let set = Sort::array(&ctx, &node, &ctx.bool_sort());
+        FuncDecl::new(&ctx, &ctx.str_sym("f"), &[&node, &set], &node)
+    };
+    let (node, set) = (f.domain(0), f.domain(1));
+    assert!(f.range() == node);
+    drop(f);
+    let x = Ast::new_const(&ctx.str_sym("x"), &node);
+    assert!(x.get_sort() == node);
+    assert_eq!(format!("{}", set), "(Array Node Bool)");
+
+    // The sort of a datatype outlives the `DatatypeSort` it came from,
+    // and the constructors outlive the sort.
+    let DatatypeSort { sort, variants } = DatatypeBuilder::new(&ctx, "Pair")
+        .variant(
+            "pair",
+            vec![
+                ("first", DatatypeAccessor::Sort(node.clone())),
+                ("second", DatatypeAccessor::Sort(ctx.int_sort())),
+            ],
+        )
+        .finish();
+    let pair_sort = sort.clone();
+    drop(sort);
+    drop(node);
+    let p = variants[0].constructor.apply(&[&x, &ctx.from_i64(1)]);
+    assert!(p.get_sort() == pair_sort);
+    drop(pair_sort);
+    assert!(variants[0].accessors[0].range() == x.get_sort());
+
+    // Creating and dropping many sorts neither crashes nor confuses
+    // sorts that are still in use.
+    for i in 1..1000 {
+        let bv = Sort::bitvector(&ctx, i);
+        assert!(bv.clone() == Sort::bitvector(&ctx, i));
+    }
+    let solver = Solver::new(&ctx);
+    solver.assert(&variants[0].accessors[1].apply(&[&p])._eq(&ctx.from_i64(1)));
+    assert_eq!(solver.check(), SatResult::Sat);
+}
+
+#[test]
+fn test_uninterpreted_functions() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let node = ctx.uninterpreted_sort(&ctx.str_sym("Node"));
+    let edge = ctx.func_decl(&ctx.str_sym("edge"), &[&node, &node], &ctx.bool_sort());
+    let next = FuncDecl::new(&ctx, &ctx.str_sym("next"), &[&node], &node);
+    assert_eq!(edge.name(), "edge");
+    assert_eq!(edge.arity(), 2);
+    assert!(edge.domain(1) == node);
+    assert!(edge.range() == ctx.bool_sort());
+
+    let a = Ast::new_const(&ctx.str_sym("a"), &node);
+    let b = next.apply(&[&a]);
+    let solver = Solver::new(&ctx);
+    solver.assert(&edge.apply(&[&a, &b]));
+    solver.assert(&next.apply(&[&a])._eq(&a));
+    assert_eq!(solver.check(), SatResult::Sat);
+    solver.assert(&edge.apply(&[&a, &a]).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_recursive_function() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let int = ctx.int_sort();
+    let list = DatatypeBuilder::new(&ctx, "IntList")
+        .variant("nil", vec![])
+        .variant(
+            "cons",
+            vec![
+                ("head", DatatypeAccessor::Sort(ctx.int_sort())),
+                ("tail", DatatypeAccessor::Datatype("IntList".to_owned())),
+            ],
+        )
+        .finish();
+    let nil = &list.variants[0];
+    let cons = &list.variants[1];
+
+    let len = FuncDecl::new_recursive(&ctx, &ctx.str_sym("len"), &[&list.sort], &int);
+    let l = Ast::new_const(&ctx.str_sym("l"), &list.sort);
+    let tail = cons.accessors[1].apply(&[&l]);
+    let body = nil.tester.apply(&[&l]).ite(
+        &ctx.from_i64(0),
+        &ctx.from_i64(1).add(&[&len.apply(&[&tail])]),
+    );
+    len.add_rec_def(&[&l], &body);
+
+    let xs = Ast::new_const(&ctx.str_sym("xs"), &list.sort);
+    let solver = Solver::new(&ctx);
+    solver.assert(&len.apply(&[&xs])._eq(&ctx.from_i64(2)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    let xs_val = model.eval(&xs, true).unwrap();
+    let tail_val = model
+        .eval(&cons.accessors[1].apply(&[&xs_val]), true)
+        .unwrap();
+    let rest = model
+        .eval(&cons.accessors[1].apply(&[&tail_val]), true)
+        .unwrap();
+    assert_eq!(format!("{}", rest), "nil");
+}
+
+#[test]
+fn test_solver_unsat_core() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let p = ctx.named_bool_const("p");
+    let q = ctx.named_bool_const("q");
+    let r = ctx.named_bool_const("r");
+
+    let solver = Solver::new(&ctx);
+    solver.assert_and_track(&x.gt(&ctx.from_i64(0)), &p);
+    solver.assert_and_track(&x.lt(&ctx.from_i64(0)), &q);
+    solver.assert(&r.implies(&x._eq(&ctx.from_i64(3))));
+
+    match solver.check_assumptions(&[&r]) {
+        CheckResult::Unsatisfiable => {}
+        _ => panic!("expected unsat"),
+    }
+    let core = solver.get_unsat_core();
+    assert_eq!(core.len(), 2);
+    assert!(core.contains(&p) && core.contains(&q));
+
+    let solver = Solver::new(&ctx);
+    solver.assert_and_track(&x.gt(&ctx.from_i64(0)), &p);
+    solver.assert(&r.implies(&x._eq(&ctx.from_i64(3))));
+    match solver.check_assumptions(&[&r]) {
+        CheckResult::Satisfiable(model) => {
+            assert_eq!(model.eval(&x, true).unwrap().as_i64(), Some(3));
+        }
+        _ => panic!("expected sat"),
+    }
+    assert!(solver.get_unsat_core().is_empty());
+}
+
+#[test]
+fn test_solver_get_proof() {
+    let mut cfg = Config::new();
+    cfg.set_proof_generation(true);
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let solver = Solver::new(&ctx);
+    solver.assert(&x.gt(&ctx.from_i64(2)));
+    solver.assert(&x.lt(&ctx.from_i64(1)));
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let proof = solver.get_proof().unwrap();
+    // The proof concludes `false` from the assertions.
+    assert!(format!("{}", proof).contains("false"));
+
+    // Without proof generation there is no proof to return.
+    let ctx = Context::new(&Config::new());
+    let solver = Solver::new(&ctx);
+    solver.assert(&ctx.from_bool(false));
+    assert_eq!(solver.check(), SatResult::Unsat);
+    assert!(solver.get_proof().is_err());
+}
+
+#[test]
+fn test_z3_error_panics() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let solver = Solver::new(&ctx);
+
+    // Asserting a term that is not Boolean is a bug, reported by a panic
+    // with Z3's message rather than by exiting the process.
+    let err =
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| solver.assert(&x))).unwrap_err();
+    assert!(err
+        .downcast_ref::<String>()
+        .unwrap()
+        .starts_with("Z3 error: "));
+
+    // The context is still usable afterwards.
+    solver.assert(&x.gt(&ctx.from_i64(0)));
+    assert_eq!(solver.check(), SatResult::Sat);
+}
+
+#[test]
+fn test_z3_error_not_carried_over() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+
+    // An unknown parameter is reported by the call that sets it, rather
+    // than by the first call that uses the solver.
+    let x = ctx.named_int_const("x");
+    let solver = Solver::new(&ctx);
+    let mut params = Params::new(&ctx);
+    params.set_u32("no_such_parameter", 1);
+    let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| solver.set_params(&params)))
+        .unwrap_err();
+    assert!(err
+        .downcast_ref::<String>()
+        .unwrap()
+        .contains("unknown parameter 'no_such_parameter'"));
+    solver.assert(&x.gt(&ctx.from_i64(0)));
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    // Nor is it blamed on calls in the rest of the context.
+    let opt = Optimize::new(&ctx);
+    opt.from_string("(declare-const y Int) (assert (> y 1))")
+        .unwrap();
+    assert_eq!(opt.check(), SatResult::Sat);
+}
+
+#[test]
+fn test_optimize_unsat_core() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let big = ctx.named_bool_const("big");
+    let small = ctx.named_bool_const("small");
+    let even = ctx.named_bool_const("even");
+
+    let opt = Optimize::new(&ctx);
+    opt.assert(&big.implies(&x.gt(&ctx.from_i64(100))));
+    opt.assert(&small.implies(&x.lt(&ctx.from_i64(10))));
+    opt.assert(&even.implies(&x.modulo(&ctx.from_i64(2))._eq(&ctx.from_i64(0))));
+    opt.minimize(&x);
+
+    match opt.check_assumptions(&[&big, &small, &even]) {
+        CheckResult::Unsatisfiable => {}
+        _ => panic!("expected unsat"),
+    }
+    let core = opt.get_unsat_core();
+    assert!(core.contains(&big) && core.contains(&small));
+
+    match opt.check_assumptions(&[&big, &even]) {
+        CheckResult::Satisfiable(model) => {
+            assert_eq!(model.eval(&x, true).unwrap().as_i64(), Some(102));
+        }
+        _ => panic!("expected sat"),
+    };
+}
+
+#[test]
+fn test_optimize_objective_bounds() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_real_const("y");
+
+    let opt = Optimize::new(&ctx);
+    opt.assert(&x.le(&ctx.from_i64(7)));
+    opt.maximize(&x);
+    opt.maximize(&y);
+    assert_eq!(opt.check(), SatResult::Sat);
+    assert_eq!(opt.get_objectives().len(), 2);
+    assert_eq!(opt.get_upper(0).as_i64(), Some(7));
+    assert_eq!(opt.get_lower(0).as_i64(), Some(7));
+    // y is unbounded above.
+    assert_eq!(opt.get_upper_as_vector(1)[0].as_i64(), Some(1));
+
+    let opt = Optimize::new(&ctx);
+    opt.assert(&y.le(&ctx.from_real(1, 2)));
+    opt.maximize(&y);
+    assert_eq!(opt.check(), SatResult::Sat);
+    let upper = opt.get_upper_as_vector(0);
+    assert_eq!(upper.len(), 3);
+    assert_eq!(upper[0].as_i64(), Some(0));
+    assert_eq!(upper[1].as_real(), Some((1, 2)));
+    assert_eq!(upper[2].as_i64(), Some(0));
+}
+
+#[test]
+fn test_optimize_from_file() {
+    let path = std::env::temp_dir().join("z3rs_test_optimize_from_file.smt2");
+    std::fs::write(
+        &path,
+        "(declare-const a Bool)
+         (declare-const b Bool)
+         (assert (not (and a b)))
+         (assert-soft a :weight 3)
+         (assert-soft b :weight 5)",
+    )
+    .unwrap();
+
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let opt = Optimize::new(&ctx);
+    opt.from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(opt.from_file(&path).is_err());
+    assert_eq!(opt.check(), SatResult::Sat);
+
+    let model = opt.get_model().unwrap();
+    assert_eq!(
+        model
+            .eval(&ctx.named_bool_const("a"), true)
+            .unwrap()
+            .as_bool(),
+        Some(false)
+    );
+    assert_eq!(
+        model
+            .eval(&ctx.named_bool_const("b"), true)
+            .unwrap()
+            .as_bool(),
+        Some(true)
+    );
+    assert_eq!(opt.get_upper(0).as_i64(), Some(3));
+}
+
+#[test]
+fn test_optimize_from_string_error() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let opt = Optimize::new(&ctx);
+    // `x` is not declared.
+    let err = opt.from_string("(assert (> x 1))").unwrap_err();
+    assert!(err.message.contains("unknown constant x"), "{}", err);
+
+    // The optimizer is still usable afterwards.
+    opt.from_string("(declare-const x Int) (assert (> x 1))")
+        .unwrap();
+    assert_eq!(opt.check(), SatResult::Sat);
+}
+
+#[test]
+fn test_optimize_set_params() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+
+    let mut params = Params::new(&ctx);
+    params.set_symbol("priority", "box");
+    params.set_bool("maxres.wmax", true);
+    params.set_u32("maxres.max_core_size", 3);
+    assert!(format!("{}", params).contains("priority box"));
+
+    let opt = Optimize::new(&ctx);
+    opt.set_params(&params);
+    opt.assert(&x.add(&[&y]).le(&ctx.from_i64(10)));
+    opt.assert(&x.ge(&ctx.from_i64(0)));
+    opt.assert(&y.ge(&ctx.from_i64(0)));
+    let max_x = opt.maximize(&x);
+    let max_y = opt.maximize(&y);
+    assert_eq!(opt.check(), SatResult::Sat);
+    // With box priority, each objective is optimized independently.
+    assert_eq!(max_x.upper().as_i64(), Some(10));
+    assert_eq!(max_y.upper().as_i64(), Some(10));
+
+    // A bad parameter is reported by `set_params()` itself, and leaves
+    // the context usable.
+    let mut params = Params::new(&ctx);
+    params.set_u32("no_such_parameter", 1);
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| opt.set_params(&params)));
+    assert!(result.is_err());
+    assert_eq!(opt.check(), SatResult::Sat);
+}
+
+#[test]
+fn test_optimize_soft_groups() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+
+    let opt = Optimize::new(&ctx);
+    let g1 = ctx.str_sym("g1");
+    let g2 = ctx.str_sym("g2");
+    let low = opt.add_soft(&x.lt(&ctx.from_i64(0)), 2, Some(&g1));
+    let high = opt.add_soft(&x.gt(&ctx.from_i64(10)), 3, Some(&g1));
+    let mid = opt.add_soft(&x._eq(&ctx.from_i64(5)), 7, Some(&g2));
+    let default = opt.add_soft(&x._eq(&ctx.from_i64(11)), 1, None);
+    assert_eq!(low.group_index(), high.group_index());
+    assert!(mid.group_index() != low.group_index());
+    assert!(default.group_index() != mid.group_index());
+    assert_eq!(opt.check(), SatResult::Sat);
+
+    // g1 takes priority, so x > 10 is chosen over x < 0.
+    let model = opt.get_model().unwrap();
+    assert_eq!(high.is_satisfied(&model), Some(true));
+    assert_eq!(low.is_satisfied(&model), Some(false));
+    assert_eq!(mid.is_satisfied(&model), Some(false));
+    assert_eq!(low.penalty().as_i64(), Some(2));
+    assert_eq!(mid.penalty().as_i64(), Some(7));
+    assert_eq!(default.penalty().as_i64(), Some(0));
+}
+
+#[test]
+fn test_optimize_statistics() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+
+    let opt = Optimize::new(&ctx);
+    opt.assert(&x.le(&ctx.from_i64(10)));
+    opt.add_soft(&x.gt(&ctx.from_i64(20)), 1, None);
+    opt.maximize(&x);
+    assert_eq!(opt.check(), SatResult::Sat);
+
+    let stats = opt.get_statistics();
+    assert!(!stats.is_empty());
+    assert_eq!(stats.entries().count(), stats.len());
+    let first = stats.entries().next().unwrap();
+    assert_eq!(stats.get(&first.key), Some(first.value));
+    assert_eq!(stats.get("no such statistic"), None);
+    assert!(stats.to_string().contains(&first.key));
+}
+
+#[test]
+fn test_optimize_reason_unknown() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let int = ctx.int_sort();
+    let f = FuncDecl::new(&ctx, &ctx.str_sym("f"), &[&int], &int);
+    let x = ctx.named_int_const("x");
+
+    // Model-based quantifier instantiation does not terminate on
+    // forall x. f(f(x)) = x + 1, so the check times out.
+    let opt = Optimize::new(&ctx);
+    opt.set_timeout(100);
+    let body = f.apply(&[&f.apply(&[&x])])._eq(&x.add(&[&ctx.from_i64(1)]));
+    opt.assert(&Ast::forall_const(&ctx, &[&x], &[], &body));
+    match opt.check_get_model() {
+        CheckResult::Unknown(_, reason) => {
+            assert!(!reason.is_empty());
+            assert_eq!(reason, opt.get_reason_unknown());
+        }
+        _ => panic!("expected an unknown result"),
+    };
+}
+
+#[test]
+fn test_optimize_assert_and_track() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let p = ctx.named_bool_const("p");
+
+    // A satisfiable tracked constraint behaves like a hard constraint.
+    let opt = Optimize::new(&ctx);
+    opt.assert_and_track(&x.le(&ctx.from_i64(7)), &p);
+    opt.maximize(&x);
+    assert_eq!(opt.check(), SatResult::Sat);
+    let model = opt.get_model().unwrap();
+    assert_eq!(model.eval(&x, true).unwrap().as_i64(), Some(7));
+    assert!(opt.get_unsat_core().is_empty());
+}
+
+#[test]
+fn test_optimize_pareto_iter() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+
+    let opt = Optimize::new(&ctx);
+    opt.assert(&x.ge(&ctx.from_i64(0)));
+    opt.assert(&y.ge(&ctx.from_i64(0)));
+    opt.assert(&x.add(&[&y]).le(&ctx.from_i64(3)));
+    opt.maximize(&x);
+    opt.maximize(&y);
+
+    let mut front: Vec<(i64, i64)> = opt
+        .pareto_iter()
+        .map(|m| {
+            (
+                m.eval(&x, true).unwrap().as_i64().unwrap(),
+                m.eval(&y, true).unwrap().as_i64().unwrap(),
+            )
+        })
+        .collect();
+    front.sort();
+    assert_eq!(front, vec![(0, 3), (1, 2), (2, 1), (3, 0)]);
+}
+
+#[test]
+fn test_check_result_helpers() {
+    let mut cfg = Config::new();
+    cfg.set_timeout_msec(100);
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&x.gt(&ctx.from_i64(0)));
+    let sat = solver.check_assumptions(&[]);
+    assert!(sat.is_sat());
+    assert!(!sat.is_unsat());
+    assert!(sat.model().is_some());
+    let model = sat.unwrap_sat();
+    assert!(model.eval(&x, true).unwrap().as_i64().unwrap() > 0);
+
+    solver.assert(&x.lt(&ctx.from_i64(0)));
+    let unsat = solver.check_assumptions(&[]);
+    assert!(unsat.is_unsat());
+    assert!(unsat.model().is_none());
+
+    // Retrieving the result of a timed out check must not abort, whether
+    // or not a partial model is available.
+    let int = ctx.int_sort();
+    let f = FuncDecl::new(&ctx, &ctx.str_sym("f"), &[&int], &int);
+    let body = f.apply(&[&f.apply(&[&x])])._eq(&x.add(&[&ctx.from_i64(1)]));
+    let solver = Solver::new(&ctx);
+    solver.assert(&Ast::forall_const(&ctx, &[&x], &[], &body));
+    match solver.check_assumptions(&[]) {
+        CheckResult::Unknown(_, reason) => assert!(!reason.is_empty()),
+        _ => panic!("expected an unknown result"),
+    };
+}
+
+#[test]
+fn test_assertions() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let a = x.gt(&ctx.from_i64(0));
+    let b = x.lt(&ctx.from_i64(5));
+
+    let solver = Solver::new(&ctx);
+    assert_eq!(solver.assertions().count(), 0);
+    solver.assert(&a);
+    solver.assert(&b);
+    let asserted: Vec<Ast> = solver.assertions().collect();
+    assert_eq!(asserted.len(), 2);
+    assert!(asserted.contains(&a) && asserted.contains(&b));
+
+    let opt = Optimize::new(&ctx);
+    opt.assert(&a);
+    opt.maximize(&x);
+    let asserted: Vec<Ast> = opt.assertions().collect();
+    assert_eq!(asserted.len(), 1);
+    assert!(asserted[0] == a);
+}
+
+#[test]
+fn test_solver_translate() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let solver = Solver::new(&ctx);
+    solver.assert(&x.gt(&ctx.from_i64(41)));
+    solver.assert(&x.lt(&ctx.from_i64(43)));
+
+    let dest = Context::new(&cfg);
+    let copy = solver.translate(&dest);
+    drop(solver);
+    assert_eq!(copy.assertions().count(), 2);
+    assert_eq!(copy.check(), SatResult::Sat);
+    let model = copy.get_model().unwrap();
+    let x = dest.named_int_const("x");
+    assert_eq!(model.eval(&x, true).unwrap().as_i64(), Some(42));
+}
+
+#[test]
+fn test_model_translate() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let solver = Solver::new(&ctx);
+    solver.assert(&x._eq(&ctx.from_i64(7)));
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    let dest = Context::new(&cfg);
+    let model = solver.get_model().unwrap().translate(&dest);
+    drop(solver);
+    let x = dest.named_int_const("x");
+    let doubled = model.eval(&x.add(&[&x]), true).unwrap();
+    assert_eq!(doubled.as_i64(), Some(14));
+}
+
+#[test]
+fn test_ast_translate() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let constraint = x.mul(&[&ctx.from_i64(3)])._eq(&ctx.from_i64(12));
+
+    let dest = Context::new(&cfg);
+    let translated = constraint.translate(&dest);
+    drop(constraint);
+    assert_eq!(translated.to_string(), "(= (* x 3) 12)");
+
+    let solver = Solver::new(&dest);
+    solver.assert(&translated);
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    let x = x.translate(&dest);
+    assert!(x == dest.named_int_const("x"));
+    assert_eq!(model.eval(&x, true).unwrap().as_i64(), Some(4));
+}
+
+#[test]
+fn test_model_introspection() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let int = ctx.int_sort();
+    let x = ctx.named_int_const("x");
+    let f = ctx.func_decl(&ctx.str_sym("f"), &[&int], &int);
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&x._eq(&ctx.from_i64(5)));
+    solver.assert(&f.apply(&[&ctx.from_i64(1)])._eq(&ctx.from_i64(10)));
+    solver.assert(&f.apply(&[&ctx.from_i64(2)])._eq(&ctx.from_i64(20)));
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    let model = solver.get_model().unwrap();
+    assert_eq!(model.num_consts(), 1);
+    assert_eq!(model.num_funcs(), 1);
+    let names: Vec<String> = model.iter().map(|d| d.name()).collect();
+    assert_eq!(names, vec!["x", "f"]);
+
+    let x_decl = &model.const_decls()[0];
+    assert_eq!(model.get_const_interp(x_decl).unwrap().as_i64(), Some(5));
+    assert!(model.get_func_interp(x_decl).is_none());
+
+    let f_decl = &model.func_decls()[0];
+    assert!(model.get_const_interp(f_decl).is_none());
+    let interp = model.get_func_interp(f_decl).unwrap();
+    let lookup = |arg: i64| -> i64 {
+        for e in interp.entries() {
+            let args = e.args();
+            assert_eq!(args.len(), 1);
+            if args[0].as_i64() == Some(arg) {
+                return e.value().as_i64().unwrap();
+            }
+        }
+        interp.else_value().as_i64().unwrap()
+    };
+    assert_eq!(lookup(1), 10);
+    assert_eq!(lookup(2), 20);
+    assert!(!interp.entries().is_empty());
+}
+
+#[test]
+fn test_model_eval_completion() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&x.gt(&ctx.from_i64(3)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+
+    assert!(model.eval(&y, false).unwrap() == y);
+    assert_eq!(model.num_consts(), 1);
+    // Completion assigns y a default value and records it in the model.
+    let yv = model.eval(&y, true).unwrap();
+    assert!(yv.as_i64().is_some());
+    assert_eq!(model.num_consts(), 2);
+    assert!(model.eval(&y, false).unwrap() == yv);
+}
+
+#[test]
+fn test_numeral_extraction() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let r = ctx.named_real_const("r");
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&x._eq(&ctx.from_i64(-3)));
+    solver.assert(&r.mul(&[&ctx.from_i64(4)])._eq(&ctx.from_i64(6)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+
+    let xv = model.eval(&x, true).unwrap();
+    assert_eq!(xv.as_i64(), Some(-3));
+    assert_eq!(xv.as_u64(), None);
+    assert_eq!(xv.as_real(), Some((-3, 1)));
+    assert_eq!(xv.as_bool(), None);
+
+    let rv = model.eval(&r, true).unwrap();
+    assert_eq!(rv.as_real(), Some((3, 2)));
+    assert_eq!(rv.as_i64(), None);
+
+    // Non-numerals yield None rather than invoking the error handler.
+    assert_eq!(x.as_real(), None);
+    assert_eq!(x.as_u64(), None);
+    assert_eq!(ctx.from_u64(u64::MAX).as_u64(), Some(u64::MAX));
+    assert_eq!(ctx.from_bool(false).as_bool(), Some(false));
+}
+
+#[cfg(feature = "num")]
+#[test]
+fn test_bignum_conversions() {
+    use num_bigint::BigInt;
+    use num_rational::BigRational;
+
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let big: BigInt = "-98765432109876543210987654321".parse().unwrap();
+    let third = BigRational::new(BigInt::from(1), BigInt::from(3));
+
+    let x = ctx.named_int_const("x");
+    let r = ctx.named_real_const("r");
+    let solver = Solver::new(&ctx);
+    solver.assert(&x._eq(&Ast::from_bigint(&ctx, &big)));
+    solver.assert(&r._eq(&Ast::from_big_rational(&ctx, &third).add(&[&x])));
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    let model = solver.get_model().unwrap();
+    let xv = model.eval(&x, true).unwrap();
+    assert_eq!(xv.as_bigint(), Some(big.clone()));
+    assert_eq!(xv.as_i64(), None);
+    let rv = model.eval(&r, true).unwrap();
+    assert_eq!(
+        rv.as_big_rational(),
+        Some(third + BigRational::from_integer(big))
+    );
+    assert_eq!(rv.as_bigint(), None);
+    assert_eq!(x.as_bigint(), None);
+    assert_eq!(x.as_big_rational(), None);
+    assert_eq!(BV::from_u64(&ctx, 5, 8).as_bigint(), Some(BigInt::from(5)));
+    assert_eq!(BV::from_u64(&ctx, 5, 8).as_big_rational(), None);
+}
+
+#[cfg(feature = "rug")]
+#[test]
+fn test_rug_conversions() {
+    use rug::{Integer, Rational};
+
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let big: Integer = "-123456789012345678901234567890".parse().unwrap();
+    let third = Rational::from((1, 3));
+
+    let x = ctx.named_int_const("x");
+    let r = ctx.named_real_const("r");
+    let solver = Solver::new(&ctx);
+    solver.assert(&x._eq(&Ast::from((&ctx, &big))));
+    solver.assert(&r._eq(&Ast::from_rug_rational(&ctx, &third).add(&[&x])));
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    let model = solver.get_model().unwrap();
+    let xv = model.eval(&x, true).unwrap();
+    assert_eq!(xv.as_rug_integer(), Some(big.clone()));
+    let rv = model.eval(&r, true).unwrap();
+    assert_eq!(rv.as_rug_rational(), Some(third + big));
+    assert_eq!(rv.as_rug_integer(), None);
+    assert_eq!(x.as_rug_rational(), None);
+}
+
+#[cfg(feature = "rug")]
+#[test]
+fn test_rug_round_trip() {
+    use rug::{Integer, Rational};
+
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let huge: Integer = Integer::from(Integer::u_pow_u(2, 200)) + 12345;
+    for i in &[
+        Integer::from(0),
+        Integer::from(-7),
+        Integer::from(i64::MIN),
+        Integer::from(u64::MAX),
+        huge.clone(),
+        -huge.clone(),
+    ] {
+        let ast = Ast::from_rug_integer(&ctx, i);
+        assert_eq!(ast.as_rug_integer().as_ref(), Some(i));
+        assert_eq!(ast.as_rug_rational(), Some(Rational::from(i)));
+    }
+
+    for r in &[
+        Rational::from((-1, 3)),
+        Rational::from((huge.clone(), 7)),
+        Rational::from((-5, huge.clone())),
+        Rational::from(4),
+    ] {
+        let ast = Ast::from_rug_rational(&ctx, r);
+        assert_eq!(ast.as_rug_rational().as_ref(), Some(r));
+    }
+    assert_eq!(
+        Ast::from_rug_rational(&ctx, &Rational::from(4)).as_rug_integer(),
+        Some(Integer::from(4))
+    );
+
+    assert_eq!(
+        BV::from_u64(&ctx, u64::MAX, 64).as_rug_integer(),
+        Some(Integer::from(u64::MAX))
+    );
+    assert_eq!(ctx.named_int_const("x").as_rug_integer(), None);
+}
+
+#[test]
+fn test_simplify() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let a = ctx.named_bool_const("a");
+    let taut = a.or(&[&a.not()]);
+    assert_eq!(taut.simplify().as_bool(), Some(true));
+
+    let bv = BV::from_u64(&ctx, 0x0f, 8).bvadd(&BV::from_u64(&ctx, 0xf1, 8));
+    assert_eq!(bv.simplify().as_u64(), Some(0));
+
+    let x = ctx.named_int_const("x");
+    let e = x.mul(&[&ctx.from_i64(2)]).sub(&[&x, &x]);
+    assert_eq!(e.simplify().as_i64(), Some(0));
+
+    let mut params = Params::new(&ctx);
+    params.set_bool("elim_and", true);
+    let b = ctx.named_bool_const("b");
+    assert_eq!(
+        a.and(&[&b]).simplify_with(&params).to_string(),
+        "(not (or (not a) (not b)))"
+    );
+}
+
+#[test]
+fn test_substitute() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let int = ctx.int_sort();
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+
+    // Instantiate the template x * x + y at x = 3, y = 4.
+    let template = x.mul(&[&x]).add(&[&y]);
+    let three = ctx.from_i64(3);
+    let four = ctx.from_i64(4);
+    let e = template.substitute(&[(&x, &three), (&y, &four)]);
+    assert_eq!(e.simplify().as_i64(), Some(13));
+    assert!(template.substitute(&[]) == template);
+
+    // Bound variable 0 is the innermost binder, here the second operand.
+    let body = Ast::bound(&ctx, 1, &int).sub(&[&Ast::bound(&ctx, 0, &int)]);
+    let inst = body.substitute_vars(&[&four, &three]);
+    assert_eq!(inst.simplify().as_i64(), Some(-1));
+}
+
+#[test]
+fn test_ast_structure() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let int = ctx.int_sort();
+    let f = ctx.func_decl(&ctx.str_sym("f"), &[&int, &int], &int);
+    let x = ctx.named_int_const("x");
+    let e = f.apply(&[&x, &ctx.from_i64(7)]);
+
+    assert!(e.is_app());
+    assert_eq!(e.decl().name(), f.name());
+    assert_eq!(e.decl().arity(), 2);
+    let args = e.args();
+    assert_eq!(args.len(), 2);
+    assert!(args[0] == x);
+    assert_eq!(args[1].kind(), AstKind::Numeral);
+    assert_eq!(x.num_args(), 0);
+
+    let q = Ast::forall_const(&ctx, &[&x], &[], &e._eq(&x));
+    assert_eq!(q.kind(), AstKind::Quantifier);
+    assert!(!q.is_app());
+    assert!(q.args().is_empty());
+    assert_eq!(Ast::bound(&ctx, 0, &int).kind(), AstKind::Var);
+}
+
+#[test]
+fn test_ast_hash_and_eq() {
+    use std::collections::{HashMap, HashSet};
+
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+
+    // Terms built separately but structurally equal are the same term.
+    let a = x.add(&[&y]);
+    let b = ctx.named_int_const("x").add(&[&ctx.named_int_const("y")]);
+    assert!(a == b);
+    assert_eq!(a.id(), b.id());
+    assert!(a != y.add(&[&x]));
+
+    let set: HashSet<Ast> = vec![a.clone(), b, x.clone(), x.clone()]
+        .into_iter()
+        .collect();
+    assert_eq!(set.len(), 2);
+
+    let mut names = HashMap::new();
+    names.insert(x.clone(), "x");
+    assert_eq!(names.get(&ctx.named_int_const("x")), Some(&"x"));
+    assert_eq!(names.get(&y), None);
+}
+
+#[test]
+fn test_ast_vector() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+
+    let mut v = AstVector::new(&ctx);
+    assert!(v.is_empty());
+    assert!(v.get(0).is_none());
+
+    v.push(&x);
+    v.push(&y);
+    v.push(&ctx.from_i64(3));
+    assert_eq!(v.len(), 3);
+    assert!(v.get(1).unwrap() == y);
+    assert!(v.get(3).is_none());
+
+    v.set(1, &x.add(&[&y]));
+    assert!(v.get(1).unwrap() == x.add(&[&y]));
+
+    v.truncate(2);
+    let items: Vec<Ast> = v.iter().collect();
+    assert_eq!(items.len(), 2);
+    assert!(items[0] == x);
+
+    let converted: Vec<Ast> = v.into();
+    assert!(converted == items);
+}
+
+#[test]
+fn test_param_descrs() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let solver = Solver::new(&ctx);
+
+    let descrs = solver.get_param_descrs();
+    assert!(!descrs.is_empty());
+    assert!(descrs.names().iter().any(|n| n == "timeout"));
+    assert_eq!(descrs.kind("timeout"), Some(ParamKind::UInt));
+    assert_eq!(descrs.kind("no_such_param"), None);
+    assert!(descrs.documentation("timeout").is_some());
+    assert_eq!(descrs.documentation("no_such_param"), None);
+
+    let mut params = Params::new(&ctx);
+    params.set_u32("timeout", 1000);
+    solver.set_params(&params);
+    let x = ctx.named_int_const("x");
+    solver.assert(&x.gt(&ctx.from_i64(0)));
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    let opt = Optimize::new(&ctx);
+    assert_eq!(
+        opt.get_param_descrs().kind("priority"),
+        Some(ParamKind::Symbol)
+    );
+}
+
+#[test]
+fn test_global_params() {
+    // Global parameters are shared with the other tests running in this
+    // process, so only touch one that does not affect their results.
+    set_global_param("warning", "false");
+    assert_eq!(get_global_param("warning"), Some("false".to_string()));
+    assert_eq!(get_global_param("no_such_param"), None);
+
+    reset_all_global_params();
+    assert_eq!(get_global_param("warning"), Some("true".to_string()));
+}
+
+#[test]
+fn test_tactic_combinators() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+
+    let names = Tactic::list_all(&ctx);
+    assert!(names.iter().any(|n| n == "qe"));
+    assert!(names.iter().any(|n| n == "simplify"));
+
+    let simplify = Tactic::new(&ctx, "simplify");
+    assert!(simplify.get_help().contains("elim_and"));
+    assert_eq!(
+        simplify.get_param_descrs().kind("elim_and"),
+        Some(ParamKind::Bool)
+    );
+
+    let mut params = Params::new(&ctx);
+    params.set_bool("elim_and", true);
+    let _pipeline = simplify
+        .with_params(&params)
+        .and_then(&Tactic::new(&ctx, "solve-eqs"))
+        .repeat(3)
+        .try_for(1000)
+        .or_else(&Tactic::skip(&ctx))
+        .and_then(&Tactic::new(&ctx, "smt").or_else(&Tactic::fail(&ctx)));
+}
+
+#[test]
+#[should_panic(expected = "unknown tactic")]
+fn test_tactic_unknown_name() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    Tactic::new(&ctx, "no-such-tactic");
+}
+
+#[test]
+fn test_goal() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+
+    let goal = Goal::new(&ctx, true, false, false);
+    assert!(goal.is_empty());
+    assert!(goal.is_decided_sat());
+    assert_eq!(goal.precision(), GoalPrec::Precise);
+    assert_eq!(goal.depth(), 0);
+
+    let a = x.gt(&y);
+    let b = y.gt(&ctx.from_i64(2));
+    goal.assert(&a);
+    goal.assert(&b);
+    assert_eq!(goal.len(), 2);
+    assert!(goal.num_exprs() > 2);
+    let formulas: Vec<Ast> = goal.formulas().collect();
+    assert!(formulas == vec![a.clone(), b]);
+    assert!(!goal.is_decided_sat());
+    assert!(!goal.is_decided_unsat());
+
+    goal.assert(&ctx.from_bool(false));
+    assert!(goal.is_inconsistent());
+    assert!(goal.is_decided_unsat());
+
+    goal.reset();
+    assert!(goal.is_empty());
+}
+
+#[test]
+fn test_apply_result_convert_model() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+
+    let goal = Goal::new(&ctx, true, false, false);
+    goal.assert(&x._eq(&y.add(&[&ctx.from_i64(1)])));
+    goal.assert(&y.gt(&ctx.from_i64(3)));
+
+    let result = Tactic::new(&ctx, "solve-eqs").apply(&goal).unwrap();
+    assert_eq!(result.len(), 1);
+    let subgoal = result.subgoal(0);
+    assert_eq!(subgoal.depth(), 1);
+
+    // `x` has been eliminated from the subgoal.
+    let solver = Solver::new(&ctx);
+    for f in subgoal.formulas() {
+        solver.assert(&f);
+    }
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = result.convert_model(0, &solver.get_model().unwrap());
+    let xv = model.eval(&x, false).unwrap().as_i64().unwrap();
+    let yv = model.eval(&y, false).unwrap().as_i64().unwrap();
+    assert_eq!(xv, yv + 1);
+    assert!(yv > 3);
+
+    assert_eq!(result.subgoals().count(), 1);
+    assert!(result.to_string().contains("goals"));
+}
+
+#[test]
+fn test_probes() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+
+    assert!(Probe::list_all(&ctx).iter().any(|n| n == "num-consts"));
+
+    let goal = Goal::new(&ctx, true, false, false);
+    goal.assert(&x.gt(&y));
+    goal.assert(&ctx.named_bool_const("p"));
+
+    let consts = Probe::new(&ctx, "num-consts");
+    let propositional = Probe::new(&ctx, "is-propositional");
+    // Boolean constants are not counted.
+    assert_eq!(consts.apply(&goal), 2.0);
+    assert_eq!(propositional.apply(&goal), 0.0);
+    assert_eq!(propositional.not().apply(&goal), 1.0);
+
+    let one = Probe::constant(&ctx, 1.0);
+    assert_eq!(consts.gt(&one).apply(&goal), 1.0);
+    assert_eq!(consts.le(&one).apply(&goal), 0.0);
+    assert_eq!(consts._eq(&Probe::constant(&ctx, 2.0)).apply(&goal), 1.0);
+    assert_eq!(consts.lt(&one).or(&propositional).apply(&goal), 0.0);
+    assert_eq!(consts.ge(&one).and(&propositional.not()).apply(&goal), 1.0);
+
+    // Only fail on goals with more than ten constants.
+    let small_only = Tactic::fail_if(&consts.gt(&Probe::constant(&ctx, 10.0)));
+    assert_eq!(small_only.apply(&goal).unwrap().len(), 1);
+
+    let t = Tactic::cond(
+        &propositional,
+        &Tactic::new(&ctx, "sat"),
+        &Tactic::new(&ctx, "smt"),
+    )
+    .and_then(&Tactic::new(&ctx, "simplify").when(&propositional));
+    let result = t.apply(&goal).unwrap();
+    assert!(result.subgoals().all(|g| g.is_decided_sat()));
+}
+
+#[test]
+fn test_solver_from_tactic() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let tactic = Tactic::new(&ctx, "simplify")
+        .and_then(&Tactic::new(&ctx, "bit-blast"))
+        .and_then(&Tactic::new(&ctx, "sat"));
+    let solver = Solver::from_tactic(&tactic);
+
+    let bv8 = ctx.bitvector_sort(8);
+    let x = ctx.named_bitvector_const("x", 8);
+    solver.assert(&x.bvmul(&bv8.from_u64(3))._eq(&bv8.from_u64(21)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let xv = solver.get_model().unwrap().eval(&x, true).unwrap();
+    assert_eq!(xv.as_u64(), Some(7));
+
+    solver.push();
+    solver.assert(&x._eq(&bv8.from_u64(1)));
+    assert_eq!(solver.check(), SatResult::Unsat);
+    solver.pop(1);
+    assert_eq!(solver.check(), SatResult::Sat);
+}
+
+#[test]
+fn test_fixedpoint() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let int = ctx.int_sort();
+    let inv = FuncDecl::new(&ctx, &ctx.str_sym("inv"), &[&int], &ctx.bool_sort());
+    let x = ctx.named_int_const("x");
+
+    let fp = Fixedpoint::new(&ctx);
+    let mut params = Params::new(&ctx);
+    params.set_symbol("engine", "spacer");
+    fp.set_params(&params);
+    assert_eq!(
+        fp.get_param_descrs().kind("engine"),
+        Some(ParamKind::Symbol)
+    );
+
+    // inv(0), and inv(x) && x < 10 => inv(x + 2)
+    fp.register_relation(&inv);
+    fp.add_rule(&inv.apply(&[&ctx.from_i64(0)]));
+    let step = inv
+        .apply(&[&x])
+        .and(&[&x.lt(&ctx.from_i64(10))])
+        .implies(&inv.apply(&[&x.add(&[&ctx.from_i64(2)])]));
+    fp.add_rule(&Ast::forall_const(&ctx, &[&x], &[], &step));
+    assert_eq!(fp.get_rules().len(), 2);
+
+    let reach = |n| {
+        let q = inv.apply(&[&x]).and(&[&x._eq(&ctx.from_i64(n))]);
+        Ast::exists_const(&ctx, &[&x], &[], &q)
+    };
+    assert_eq!(fp.query(&reach(6)).unwrap(), SatResult::Sat);
+    assert_eq!(fp.query(&reach(7)).unwrap(), SatResult::Unsat);
+    // The answer is an invariant that excludes `inv(7)`.
+    let answer = fp.get_answer();
+    let solver = Solver::new(&ctx);
+    solver.assert(&answer);
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    fp.add_rule(&inv.apply(&[&ctx.from_i64(1)]));
+    assert_eq!(fp.query(&reach(7)).unwrap(), SatResult::Sat);
+    assert!(!fp.get_statistics().is_empty());
+}
+
+#[test]
+fn test_fixedpoint_from_string() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let fp = Fixedpoint::new(&ctx);
+    let queries = fp
+        .from_string(
+            "(declare-rel edge (Int Int))
+             (declare-rel path (Int Int))
+             (declare-rel goal ())
+             (declare-var a Int)
+             (declare-var b Int)
+             (declare-var c Int)
+             (rule (edge 1 2))
+             (rule (edge 2 3))
+             (rule (=> (edge a b) (path a b)))
+             (rule (=> (and (path a b) (edge b c)) (path a c)))
+             (rule (=> (path 1 3) goal))
+             (query goal)",
+        )
+        .unwrap();
+    assert_eq!(queries.len(), 1);
+    assert_eq!(fp.query(&queries[0]).unwrap(), SatResult::Sat);
+
+    // `other` is not declared.
+    let result = fp.from_string("(query other)");
+    match result {
+        Err(e) => assert_eq!(e.code, ErrorCode::ParserError),
+        Ok(_) => panic!("undeclared relation was accepted"),
+    }
+}
+
+#[test]
+fn test_fixedpoint_facts_and_named_rules() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let node = ctx.bitvector_sort(4);
+    let bool_sort = ctx.bool_sort();
+    let edge = FuncDecl::new(&ctx, &ctx.str_sym("edge"), &[&node, &node], &bool_sort);
+    let path = FuncDecl::new(&ctx, &ctx.str_sym("path"), &[&node, &node], &bool_sort);
+    let a = ctx.named_bitvector_const("a", 4);
+    let b = ctx.named_bitvector_const("b", 4);
+    let c = ctx.named_bitvector_const("c", 4);
+
+    let fp = Fixedpoint::new(&ctx);
+    let mut params = Params::new(&ctx);
+    params.set_symbol("engine", "datalog");
+    fp.set_params(&params);
+    fp.register_relation(&edge);
+    fp.register_relation(&path);
+    fp.add_fact(&edge, &[1, 2]);
+    fp.add_fact(&edge, &[2, 3]);
+    let base = edge.apply(&[&a, &b]).implies(&path.apply(&[&a, &b]));
+    fp.add_named_rule(&Ast::forall_const(&ctx, &[&a, &b], &[], &base), "base");
+    let step = path
+        .apply(&[&a, &c])
+        .and(&[&path.apply(&[&c, &b])])
+        .implies(&path.apply(&[&a, &b]));
+    fp.add_named_rule(&Ast::forall_const(&ctx, &[&a, &b, &c], &[], &step), "step");
+    let printed = fp.to_string();
+    assert!(printed.contains("base") && printed.contains("step"));
+
+    let reach = |from, to| {
+        let q = path.apply(&[&node.from_u64(from), &node.from_u64(to)]);
+        fp.query(&q).unwrap()
+    };
+    assert_eq!(reach(1, 3), SatResult::Sat);
+    assert_eq!(reach(1, 4), SatResult::Unsat);
+    fp.add_fact(&edge, &[3, 4]);
+    assert_eq!(reach(1, 4), SatResult::Sat);
+}
+
+#[test]
+#[should_panic]
+fn test_fixedpoint_fact_arity() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let node = ctx.bitvector_sort(4);
+    let edge = FuncDecl::new(
+        &ctx,
+        &ctx.str_sym("edge"),
+        &[&node, &node],
+        &ctx.bool_sort(),
+    );
+    let fp = Fixedpoint::new(&ctx);
+    fp.register_relation(&edge);
+    fp.add_fact(&edge, &[1]);
+}
+
+#[test]
+fn test_fixedpoint_errors() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let int = ctx.int_sort();
+    let inv = FuncDecl::new(&ctx, &ctx.str_sym("inv"), &[&int], &ctx.bool_sort());
+    let fp = Fixedpoint::new(&ctx);
+
+    let mut params = Params::new(&ctx);
+    params.set_symbol("no_such_parameter", "datalog");
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| fp.set_params(&params)));
+    assert!(result.is_err());
+
+    // The datalog engine only supports finite sorts.
+    let mut params = Params::new(&ctx);
+    params.set_symbol("engine", "datalog");
+    fp.set_params(&params);
+    fp.register_relation(&inv);
+    fp.add_rule(&inv.apply(&[&ctx.from_i64(0)]));
+    let err = fp.query(&inv.apply(&[&ctx.from_i64(0)])).unwrap_err();
+    assert!(err.message.contains("infinite sorts"));
+}
+
+#[test]
+fn test_parse_smtlib2() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let pair = ctx.uninterpreted_sort(&ctx.str_sym("Pair"));
+    let first = ctx.func_decl(&ctx.str_sym("first"), &[&pair], &ctx.int_sort());
+
+    let asts = ctx
+        .parse_smtlib2(
+            "(declare-const p Pair)
+             (assert (> (first p) x))
+             (assert (< x 3))",
+            &[&pair],
+            &[&first, &x.decl()],
+        )
+        .unwrap();
+    assert_eq!(asts.len(), 2);
+    assert!(asts[1] == x.lt(&ctx.from_i64(3)));
+
+    let solver = Solver::new(&ctx);
+    for a in &asts {
+        solver.assert(a);
+    }
+    solver.assert(&x._eq(&ctx.from_i64(2)));
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    let err = ctx
+        .parse_smtlib2("(assert true)\n(assert (> y 0))", &[], &[])
+        .err()
+        .unwrap();
+    assert_eq!(err.code, ErrorCode::ParserError);
+    assert!(err.message.contains("line 2"));
+    assert!(err.message.contains("unknown constant y"));
+}
+
+#[test]
+fn test_parse_smtlib2_file() {
+    use std::fs;
+
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let dir = std::env::temp_dir();
+
+    let good = dir.join(format!("z3-rs-parse-{}.smt2", std::process::id()));
+    fs::write(
+        &good,
+        "(declare-const a Int)\n(assert (> a 1))\n(assert (< a 3))\n",
+    )
+    .unwrap();
+    let asts = ctx.parse_smtlib2_file(&good, &[], &[]).unwrap();
+    assert_eq!(asts.len(), 2);
+    let solver = Solver::new(&ctx);
+    for a in &asts {
+        solver.assert(a);
+    }
+    assert_eq!(solver.check(), SatResult::Sat);
+    let a = ctx.named_int_const("a");
+    assert_eq!(
+        solver.get_model().unwrap().eval(&a, true).unwrap().as_i64(),
+        Some(2)
+    );
+
+    let bad = dir.join(format!("z3-rs-parse-bad-{}.smt2", std::process::id()));
+    fs::write(&bad, "(assert true)\n(assert true)\n(assert (and b))\n").unwrap();
+    let err = ctx.parse_smtlib2_file(&bad, &[], &[]).err().unwrap();
+    assert!(err.message.contains("line 3"));
+
+    assert!(ctx
+        .parse_smtlib2_file(dir.join("z3-rs-no-such-file.smt2"), &[], &[])
+        .is_err());
+
+    fs::remove_file(good).unwrap();
+    fs::remove_file(bad).unwrap();
+}
+
+#[test]
+fn test_to_smt2() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+
+    let solver = Solver::new(&ctx);
+    assert!(solver.to_smt2().contains("(check-sat)"));
+    solver.assert(&x.gt(&y));
+    solver.assert(&y.gt(&ctx.from_i64(5)));
+    solver.assert(&x.lt(&ctx.from_i64(7)));
+
+    // The benchmark round-trips through the parser.
+    let smt2 = solver.to_smt2();
+    let reparsed = ctx.parse_smtlib2(&smt2, &[], &[]).unwrap();
+    assert_eq!(reparsed.len(), 3);
+    let copy = Solver::new(&ctx);
+    for a in &reparsed {
+        copy.assert(a);
+    }
+    assert_eq!(copy.check(), SatResult::Unsat);
+
+    let opt = Optimize::new(&ctx);
+    opt.assert(&x.gt(&ctx.from_i64(0)));
+    opt.minimize(&x);
+    let smt2 = opt.to_smt2();
+    assert!(smt2.contains("(minimize x)"));
+    assert!(smt2.contains("(check-sat)"));
+}
+
+#[test]
+fn test_goal_to_dimacs() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let p = ctx.named_bool_const("p");
+    let q = ctx.named_bool_const("q");
+
+    let goal = Goal::new(&ctx, true, false, false);
+    goal.assert(&p.or(&[&q]));
+    goal.assert(&p.not());
+    let dimacs = goal.to_dimacs_string(false).unwrap();
+    assert!(dimacs.starts_with("p cnf 2 2"));
+    assert!(goal.to_dimacs_string(true).unwrap().contains('q'));
+
+    // Formulas that are not in CNF are rejected until converted.
+    let r = ctx.named_bool_const("r");
+    let nested = Goal::new(&ctx, true, false, false);
+    nested.assert(&p.or(&[&q.and(&[&r])]));
+    assert!(nested.to_dimacs_string(false).is_err());
+    let cnf = Tactic::new(&ctx, "tseitin-cnf")
+        .apply(&nested)
+        .unwrap()
+        .subgoal(0);
+    assert!(cnf
+        .to_dimacs_string(false)
+        .unwrap()
+        .starts_with("p cnf 3 2"));
+
+    // Bitvector atoms are only broken into bits by bit-blasting.
+    let x = ctx.named_bitvector_const("x", 4);
+    let bv = Goal::new(&ctx, true, false, false);
+    bv.assert(&x.bvult(&ctx.bitvector_sort(4).from_u64(3)));
+    assert!(bv.to_dimacs_string(false).unwrap().starts_with("p cnf 1 1"));
+    let blasted = Tactic::new(&ctx, "simplify")
+        .and_then(&Tactic::new(&ctx, "bit-blast"))
+        .and_then(&Tactic::new(&ctx, "tseitin-cnf"))
+        .apply(&bv)
+        .unwrap()
+        .subgoal(0);
+    assert!(!blasted
+        .to_dimacs_string(false)
+        .unwrap()
+        .starts_with("p cnf 1 "));
+}
+
+#[test]
+fn test_solver_statistics() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let solver = Solver::new(&ctx);
+
+    // A small pigeonhole problem forces some conflicts.
+    let holes = 3;
+    let p: Vec<Vec<Ast>> = (0..=holes)
+        .map(|i| {
+            (0..holes)
+                .map(|j| ctx.named_bool_const(&format!("p_{}_{}", i, j)))
+                .collect()
+        })
+        .collect();
+    for row in &p {
+        let row: Vec<&Ast> = row.iter().collect();
+        solver.assert(&row[0].or(&row[1..]));
+    }
+    for j in 0..holes {
+        let column: Vec<&Ast> = p.iter().map(|row| &row[j]).collect();
+        for (i, a) in column.iter().enumerate() {
+            for b in &column[i + 1..] {
+                solver.assert(&a.and(&[b]).not());
+            }
+        }
+    }
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let stats = solver.get_statistics();
+    assert!(!stats.is_empty());
+    // Depending on the engine Z3 picks, the key is e.g. `conflicts` or
+    // `sat conflicts`.
+    let conflicts = stats
+        .entries()
+        .find(|e| e.key.ends_with("conflicts"))
+        .unwrap();
+    match conflicts.value {
+        StatisticsValue::UInt(n) => assert!(n > 0),
+        other => panic!("unexpected conflicts entry: {:?}", other),
+    };
+    match stats.get("memory") {
+        Some(StatisticsValue::Double(_)) => {}
+        other => panic!("unexpected memory entry: {:?}", other),
+    };
+    assert!(stats.to_string().contains("conflicts"));
+}
+
+#[test]
+fn test_solver_reset() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let solver = Solver::new(&ctx);
+    let mut params = Params::new(&ctx);
+    params.set_u32("random_seed", 7);
+    solver.set_params(&params);
+
+    for i in 0..3 {
+        solver.push();
+        solver.assert(&x._eq(&ctx.from_i64(i)));
+        solver.assert(&x.gt(&ctx.from_i64(i)));
+        assert_eq!(solver.check(), SatResult::Unsat);
+
+        solver.reset();
+        assert_eq!(solver.assertions().count(), 0);
+        solver.assert(&x._eq(&ctx.from_i64(i)));
+        assert_eq!(solver.check(), SatResult::Sat);
+        solver.reset();
+    }
+    assert_eq!(solver.check(), SatResult::Sat);
+}
+
+#[test]
+fn test_solver_interrupt() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let int = ctx.int_sort();
+    let f = FuncDecl::new(&ctx, &ctx.str_sym("f"), &[&int], &int);
+    let x = ctx.named_int_const("x");
+
+    // Interrupting an idle context has no effect.
+    ctx.interrupt();
+    let solver = Solver::new(&ctx);
+    solver.assert(&x.gt(&ctx.from_i64(0)));
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    // Without a timeout this check does not terminate on its own.
+    solver.reset();
+    let body = f.apply(&[&f.apply(&[&x])])._eq(&x.add(&[&ctx.from_i64(1)]));
+    solver.assert(&Ast::forall_const(&ctx, &[&x], &[], &body));
+
+    let done = AtomicBool::new(false);
+    let handle = ctx.handle();
+    let result = thread::scope(|s| {
+        s.spawn(|| {
+            while !done.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(50));
+                handle.interrupt();
+            }
+        });
+        let result = solver.check_assumptions(&[]);
+        done.store(true, Ordering::SeqCst);
+        result
+    });
+    match result {
+        CheckResult::Unknown(_, reason) => assert_eq!(reason, "interrupted"),
+        _ => panic!("expected an unknown result"),
+    };
+}
+
+#[test]
+fn test_sat_result() {
+    let mut cfg = Config::new();
+    cfg.set_timeout_msec(100);
+    let ctx = Context::new(&cfg);
+    let int = ctx.int_sort();
+    let f = FuncDecl::new(&ctx, &ctx.str_sym("f"), &[&int], &int);
+    let x = ctx.named_int_const("x");
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&x.gt(&ctx.from_i64(0)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    assert_eq!(solver.check_assumptions(&[]).sat_result(), SatResult::Sat);
+    solver.assert(&x.lt(&ctx.from_i64(0)));
+    assert_eq!(solver.check(), SatResult::Unsat);
+    assert_eq!(solver.check_assumptions(&[]).sat_result(), SatResult::Unsat);
+
+    // Unknown is no longer conflated with unsat.
+    solver.reset();
+    let body = f.apply(&[&f.apply(&[&x])])._eq(&x.add(&[&ctx.from_i64(1)]));
+    solver.assert(&Ast::forall_const(&ctx, &[&x], &[], &body));
+    assert_eq!(solver.check(), SatResult::Unknown);
+
+    let opt = Optimize::new(&ctx);
+    opt.set_timeout(100);
+    opt.assert(&Ast::forall_const(&ctx, &[&x], &[], &body));
+    assert_eq!(opt.check(), SatResult::Unknown);
+    assert_eq!(opt.check_get_model().sat_result(), SatResult::Unknown);
+}
+
+#[test]
+fn test_z3_error() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let solver = Solver::new(&ctx);
+    solver.assert(&ctx.named_bool_const("p"));
+
+    // Asking for a model before checking is a usage error, not a crash.
+    let err = solver.get_model().err().unwrap();
+    assert_eq!(err.code, ErrorCode::InvalidUsage);
+    assert!(!err.to_string().is_empty());
+
+    assert_eq!(solver.check(), SatResult::Sat);
+    assert!(solver.get_model().is_ok());
+
+    // The context stays usable after an error.
+    let err = ctx
+        .parse_smtlib2("(assert (> x 0))", &[], &[])
+        .err()
+        .unwrap();
+    assert_eq!(err.code, ErrorCode::ParserError);
+    assert_eq!(solver.check(), SatResult::Sat);
+}
+
+#[test]
+fn test_contexts_in_parallel_threads() {
+    use std::thread;
+
+    // Each thread owns its own context; they no longer share a lock.
+    let handles: Vec<_> = (0..4)
+        .map(|i| {
+            thread::spawn(move || {
+                let cfg = Config::new();
+                let ctx = Context::new(&cfg);
+                let x = ctx.named_int_const("x");
+                let solver = Solver::new(&ctx);
+                solver.assert(&x.mul(&[&x])._eq(&ctx.from_i64(i * i)));
+                solver.assert(&x.ge(&ctx.from_i64(0)));
+                assert_eq!(solver.check(), SatResult::Sat);
+                let model = solver.get_model().unwrap();
+                let value = model.eval(&x, true).unwrap().as_i64().unwrap();
+                value
+            })
+        })
+        .collect();
+    for (i, h) in handles.into_iter().enumerate() {
+        assert_eq!(h.join().unwrap(), i as i64);
+    }
+}
+
+struct OwnedProblem {
+    solver: OwnedSolver,
+    x: OwnedAst,
+}
+
+fn make_owned_problem() -> OwnedProblem {
+    let ctx = Context::new_shared(&Config::new());
+    let x = ctx.named_int_const("x");
+    let solver = OwnedSolver::new(&ctx);
+    solver.get().assert(&x.gt(&ctx.from_i64(41)));
+    OwnedProblem {
+        solver,
+        x: OwnedAst::new(&ctx, &x),
+    }
+}
+
+#[test]
+fn test_owned_handles() {
+    // The context outlives the function that created it through the handles.
+    let problem = make_owned_problem();
+    let ctx = problem.x.context().clone();
+    let solver = problem.solver.get();
+    solver.assert(&problem.x.get().lt(&ctx.from_i64(43)));
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    let model = problem.solver.get_model().unwrap();
+    let x = problem.x.clone();
+    drop(problem);
+    let value = model.get().eval(x.get(), true).unwrap().as_i64();
+    assert_eq!(value, Some(42));
+    assert_eq!(x.to_string(), "x");
+
+    let m = OwnedModel::new(model.context(), model.get());
+    assert!(std::sync::Arc::ptr_eq(m.context(), &ctx));
+}
+
+#[test]
+fn test_detached_solver() {
+    fn assert_send<T: Send>(_: &T) {}
+
+    let problem = make_owned_problem();
+    let x = problem.x.get();
+    let detached = DetachedSolver::new(problem.solver.get(), &[x, &x.add(&[x])], &Config::new());
+    assert_send(&detached);
+    // The original is independent of the copy.
+    problem.solver.get().assert(&x.lt(x));
+    assert_eq!(problem.solver.get().check(), SatResult::Unsat);
+
+    let worker = std::thread::spawn(move || {
+        let (solver, terms) = detached.attach();
+        let ctx = solver.context();
+        solver.get().assert(&terms[1].get()._eq(&ctx.from_i64(84)));
+        assert_eq!(solver.get().check(), SatResult::Sat);
+        let model = solver.get_model().unwrap();
+        let value = model.get().eval(terms[0].get(), true).unwrap().as_i64();
+        value
+    });
+    assert_eq!(worker.join().unwrap(), Some(42));
+}
+
+#[test]
+#[should_panic(expected = "different context")]
+fn test_owned_ast_wrong_context() {
+    let ctx = Context::new_shared(&Config::new());
+    let other = Context::new(&Config::new());
+    let _ = OwnedAst::new(&ctx, &other.named_int_const("x"));
+}
+
+#[test]
+fn test_cardinality_constraints() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let bs: Vec<Ast> = (0..4)
+        .map(|i| ctx.named_bool_const(&format!("b{}", i)))
+        .collect();
+    let rest: Vec<&Ast> = bs[1..].iter().collect();
+    let count = |model: &Model| {
+        bs.iter()
+            .filter(|b| model.eval(b, true).unwrap().as_bool().unwrap())
+            .count()
+    };
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&bs[0].at_least(&rest, 3));
+    solver.assert(&bs[0].at_most(&rest, 3));
+    assert_eq!(solver.check(), SatResult::Sat);
+    assert_eq!(count(&solver.get_model().unwrap()), 3);
+
+    // 2*b0 + b1 + b2 + b3 = 4 with b0 false has no solution.
+    solver.reset();
+    solver.assert(&bs[0].pb_eq(&rest, vec![2, 1, 1, 1], 4));
+    solver.assert(&bs[0].not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    solver.reset();
+    solver.assert(&bs[0].pb_ge(&rest, vec![3, 1, 1, 1], 4));
+    solver.assert(&bs[0].pb_le(&rest, vec![3, 1, 1, 1], 4));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    assert!(model.eval(&bs[0], true).unwrap().as_bool().unwrap());
+    assert_eq!(count(&model), 2);
+}
+
+#[test]
+#[should_panic(expected = "one coefficient per term")]
+fn test_pb_coefficient_count() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_bool_const("x");
+    let y = ctx.named_bool_const("y");
+    let _ = x.pb_le(&[&y], vec![1], 1);
+}
+
+#[test]
+fn test_distinct() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let xs: Vec<Ast> = (0..5)
+        .map(|i| ctx.named_int_const(&format!("x{}", i)))
+        .collect();
+    let refs: Vec<&Ast> = xs.iter().collect();
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&Ast::distinct(&ctx, &refs));
+    for x in &xs {
+        solver.assert(&x.ge(&ctx.from_i64(0)));
+        solver.assert(&x.lt(&ctx.from_i64(5)));
+    }
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    let mut values: Vec<i64> = xs
+        .iter()
+        .map(|x| model.eval(x, true).unwrap().as_i64().unwrap())
+        .collect();
+    values.sort();
+    assert_eq!(values, vec![0, 1, 2, 3, 4]);
+
+    assert_eq!(
+        Ast::distinct(&ctx, &[&xs[0]]).simplify().as_bool(),
+        Some(true)
+    );
+    assert_eq!(Ast::distinct(&ctx, &[]).as_bool(), Some(true));
+}
+
+#[test]
+fn test_ite() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let p = ctx.named_bool_const("p");
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+    assert!(x.get_sort() == ctx.int_sort());
+
+    let max = x.ge(&y).ite(&x, &y);
+    assert!(max.get_sort() == ctx.int_sort());
+    let solver = Solver::new(&ctx);
+    solver.assert(&max.lt(&x));
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    // Branches may be of any sort, including Boolean.
+    let t = ctx.from_bool(true);
+    let f = ctx.from_bool(false);
+    assert!(p.ite(&t, &f).get_sort() == ctx.bool_sort());
+}
+
+#[test]
+#[should_panic(expected = "ite branches have different sorts: Int and Bool")]
+fn test_ite_sort_mismatch() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let p = ctx.named_bool_const("p");
+    let _ = p.ite(&ctx.from_i64(1), &p);
+}
+
+#[test]
+fn test_bool_connectives() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    for &(a, b) in &[(false, false), (false, true), (true, false), (true, true)] {
+        let (x, y) = (ctx.from_bool(a), ctx.from_bool(b));
+        assert_eq!(x.iff(&y).simplify().as_bool(), Some(a == b));
+        assert_eq!(x.implies(&y).simplify().as_bool(), Some(!a || b));
+        assert_eq!(x.xor(&y).simplify().as_bool(), Some(a != b));
+    }
+
+    // p -> q is equivalent to !q -> !p, and p xor q to !(p <-> q).
+    let p = ctx.named_bool_const("p");
+    let q = ctx.named_bool_const("q");
+    let solver = Solver::new(&ctx);
+    solver.assert(&p.implies(&q).iff(&q.not().implies(&p.not())).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+    solver.reset();
+    solver.assert(&p.xor(&q).iff(&p.iff(&q).not()).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_int_real_conversions() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let n = ctx.named_int_const("n");
+    let r = ctx.named_real_const("r");
+
+    assert!(n.int2real().get_sort() == ctx.real_sort());
+    assert!(r.real2int().get_sort() == ctx.int_sort());
+
+    // A task of 5/2 hours, scheduled in whole-hour slots.
+    let solver = Solver::new(&ctx);
+    solver.assert(&r._eq(&ctx.from_real(5, 2)));
+    solver.assert(&n.int2real().ge(&r));
+    solver.assert(&n.lt(&ctx.from_i64(4)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    assert_eq!(model.eval(&n, true).unwrap().as_i64(), Some(3));
+    // real2int rounds down, and 5/2 is not integral.
+    assert_eq!(model.eval(&r.real2int(), true).unwrap().as_i64(), Some(2));
+    assert_eq!(
+        model.eval(&r.is_int(), true).unwrap().as_bool(),
+        Some(false)
+    );
+    assert_eq!(
+        model.eval(&n.int2real().is_int(), true).unwrap().as_bool(),
+        Some(true)
+    );
+}
+
+#[test]
+fn test_int_div_mod_rem_power() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let int = |i| ctx.from_i64(i);
+
+    // Z3 uses Euclidean division: the remainder of `div` is never negative.
+    assert_eq!(int(7).div(&int(2)).simplify().as_i64(), Some(3));
+    assert_eq!(int(-7).div(&int(2)).simplify().as_i64(), Some(-4));
+    assert_eq!(int(-7).modulo(&int(2)).simplify().as_i64(), Some(1));
+    assert_eq!(int(7).modulo(&int(-2)).simplify().as_i64(), Some(1));
+    // `rem` takes the sign of the divisor.
+    assert_eq!(int(7).rem(&int(-2)).simplify().as_i64(), Some(-1));
+    assert_eq!(int(2).power(&int(10)).simplify().as_i64(), Some(1024));
+
+    let x = ctx.named_int_const("x");
+    let solver = Solver::new(&ctx);
+    solver.assert(&x.div(&int(3))._eq(&int(4)));
+    solver.assert(&x.modulo(&int(3))._eq(&int(2)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    assert_eq!(model.eval(&x, true).unwrap().as_i64(), Some(14));
+
+    let r = ctx.named_real_const("r");
+    solver.reset();
+    solver.assert(&r.div(&ctx.from_real(1, 2))._eq(&int(3).int2real()));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    assert_eq!(model.eval(&r, true).unwrap().as_real(), Some((3, 2)));
+}
+
+#[test]
+fn test_numerals_from_strings() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+
+    // 2^64 does not fit in a u64.
+    let big = Ast::int_from_str_radix(&ctx, "18446744073709551616", 10).unwrap();
+    let max = ctx.from_u64(u64::MAX);
+    assert_eq!(big.sub(&[&max]).simplify().as_i64(), Some(1),);
+    let bin = Ast::int_from_str_radix(&ctx, &format!("1{}", "0".repeat(64)), 2).unwrap();
+    assert!(bin.simplify() == big);
+    let neg = Ast::int_from_str_radix(&ctx, "-Zz", 36).unwrap();
+    assert_eq!(neg.as_i64(), Some(-1295));
+    assert_eq!(
+        Ast::int_from_str_radix(&ctx, "+007", 8).unwrap().as_i64(),
+        Some(7)
+    );
+    assert_eq!(
+        Ast::int_from_str_radix(&ctx, "-0", 10).unwrap().as_i64(),
+        Some(0)
+    );
+    assert!(Ast::int_from_str_radix(&ctx, "", 10).is_none());
+    assert!(Ast::int_from_str_radix(&ctx, "-", 10).is_none());
+    assert!(Ast::int_from_str_radix(&ctx, "102", 2).is_none());
+
+    let third = Ast::real_from_decimal_str(&ctx, "1/3").unwrap();
+    assert_eq!(third.as_real(), Some((1, 3)));
+    assert_eq!(
+        Ast::real_from_decimal_str(&ctx, "-2.75").unwrap().as_real(),
+        Some((-11, 4))
+    );
+    let tiny = Ast::real_from_decimal_str(&ctx, "0.000000000000000000001").unwrap();
+    let solver = Solver::new(&ctx);
+    solver.assert(&tiny.gt(&ctx.from_real(0, 1)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    for bad in &["1..5", "1.", ".5", "1/0", "--1", "1/-2", "1e3", ""] {
+        assert!(Ast::real_from_decimal_str(&ctx, bad).is_none(), "{}", bad);
+    }
+}
+
+#[test]
+#[should_panic(expected = "radix must be in 2..=36")]
+fn test_int_from_str_bad_radix() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let _ = Ast::int_from_str_radix(&ctx, "1", 37);
+}
+
+#[test]
+fn test_bv_int_conversions() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let addr = BV::new_const(&ctx, "addr", 16);
+
+    // Memory indexed by integers, addressed through a bitvector.
+    let mem = Array::new_const(&ctx, "mem", &ctx.int_sort(), &ctx.int_sort());
+    let solver = Solver::new(&ctx);
+    solver.assert(&mem.select(&addr.to_int(false))._eq(&ctx.from_i64(7)));
+    solver.assert(&addr.bvugt(&BV::from_u64(&ctx, 0xfff0, 16)));
+    solver.assert(&addr.to_int(true).gt(&ctx.from_i64(-3)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    let a = model
+        .eval(&addr.to_int(false), true)
+        .unwrap()
+        .as_i64()
+        .unwrap();
+    assert!(a == 0xfffe || a == 0xffff);
+
+    // int2bv keeps the value modulo 2^sz.
+    let wrapped = BV::from_int(&ctx.from_i64(300), 8);
+    assert_eq!(wrapped.get_size(), 8);
+    assert_eq!(wrapped.to_int(false).simplify().as_i64(), Some(44));
+    let n = ctx.named_int_const("n");
+    solver.reset();
+    solver.assert(&BV::from_int(&n, 8).to_int(false)._eq(&n).not());
+    solver.assert(&n.ge(&ctx.from_i64(0)));
+    solver.assert(&n.lt(&ctx.from_i64(256)));
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_bv_overflow_predicates() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let bv = |i| BV::from_i64(&ctx, i, 8);
+    let holds = |a: Ast| a.simplify().as_bool().unwrap();
+
+    assert!(holds(bv(100).bvadd_no_overflow(&bv(27), true)));
+    assert!(!holds(bv(100).bvadd_no_overflow(&bv(28), true)));
+    assert!(holds(bv(100).bvadd_no_overflow(&bv(28), false)));
+    assert!(!holds(bv(200).bvadd_no_overflow(&bv(56), false)));
+    assert!(!holds(bv(-100).bvadd_no_underflow(&bv(-29))));
+    assert!(!holds(bv(100).bvsub_no_overflow(&bv(-28))));
+    assert!(!holds(bv(1).bvsub_no_underflow(&bv(2), false)));
+    assert!(holds(bv(1).bvsub_no_underflow(&bv(2), true)));
+    assert!(!holds(bv(16).bvmul_no_overflow(&bv(8), true)));
+    assert!(holds(bv(16).bvmul_no_overflow(&bv(8), false)));
+    assert!(!holds(bv(-16).bvmul_no_underflow(&bv(9))));
+    assert!(!holds(bv(-128).bvsdiv_no_overflow(&bv(-1))));
+    assert!(!holds(bv(-128).bvneg_no_overflow()));
+    assert!(holds(bv(-127).bvneg_no_overflow()));
+
+    // Unsigned addition wraps exactly when the sum is below an operand.
+    let x = BV::new_const(&ctx, "x", 8);
+    let y = BV::new_const(&ctx, "y", 8);
+    let solver = Solver::new(&ctx);
+    solver.assert(
+        &x.bvadd_no_overflow(&y, false)
+            .iff(&(&x + &y).bvuge(&x))
+            .not(),
+    );
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_bitvector_repeat_and_rotation() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = BV::new_const(&ctx, "x", 8);
+    let n = BV::new_const(&ctx, "n", 8);
+
+    let pattern = BV::from_u64(&ctx, 0b10, 2).repeat(4);
+    assert_eq!(pattern.get_size(), 8);
+    assert_eq!(pattern.simplify().as_u64(), Some(0b1010_1010));
+
+    // A symbolic rotation by a constant agrees with the indexed one, and
+    // rotating left then right by the same amount is the identity.
+    let solver = Solver::new(&ctx);
+    solver.assert(
+        &x.ext_rotate_left(&BV::from_u64(&ctx, 3, 8))
+            ._eq(&x.rotate_left(3))
+            .not(),
+    );
+    assert_eq!(solver.check(), SatResult::Unsat);
+    solver.reset();
+    solver.assert(&x.ext_rotate_left(&n).ext_rotate_right(&n)._eq(&x).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_array_lambda() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let i = ctx.named_int_const("i");
+    let j = ctx.named_int_const("j");
+    let k = ctx.named_int_const("k");
+
+    // An array initialised to its own indices, then updated.
+    let identity = Array::lambda(&ctx, &[&i], &i);
+    let mem = identity.store(&ctx.from_i64(5), &ctx.from_i64(0));
+    let solver = Solver::new(&ctx);
+    solver.assert(&mem.select(&k)._eq(&k).not());
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    assert_eq!(model.eval(&k, true).unwrap().as_i64(), Some(5));
+
+    // Two bound constants make a two-dimensional array.
+    let sum = Array::lambda(&ctx, &[&i, &j], &i.add(&[&j]));
+    assert_eq!(sum.get_sort().to_string(), "(Array Int Int Int)");
+
+    // The same array over de-Bruijn indices, where `i` is the outer
+    // variable and so has index 1.
+    let int = ctx.int_sort();
+    let (vi, vj) = (Ast::bound(&ctx, 1, &int), Ast::bound(&ctx, 0, &int));
+    let bounds = [(&ctx.str_sym("i"), &int), (&ctx.str_sym("j"), &int)];
+    let sum2 = Array::lambda_bound(&ctx, &bounds, &vi.add(&[&vj]));
+    assert!(*sum2 == *sum);
+}
+
+#[test]
+fn test_array_map_default_ext() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let int = ctx.int_sort();
+    let zeroes = Array::const_array(&ctx, &int, &ctx.from_i64(0));
+    let sevens = Array::const_array(&ctx, &int, &ctx.from_i64(7));
+    // Map a user-defined function over an array.
+    let double = FuncDecl::new(&ctx, &ctx.str_sym("double"), &[&int], &int);
+    let x = ctx.named_int_const("x");
+    let i = ctx.named_int_const("i");
+    let solver = Solver::new(&ctx);
+    solver.assert(&Ast::forall_const(
+        &ctx,
+        &[&x],
+        &[],
+        &double.apply(&[&x])._eq(&x.mul(&[&ctx.from_i64(2)])),
+    ));
+    let doubled = Array::map(
+        &double,
+        &[&sevens.store(&ctx.from_i64(1), &ctx.from_i64(1))],
+    );
+    solver.assert(&doubled.select(&i)._eq(&ctx.from_i64(14)).not());
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    assert_eq!(model.eval(&i, true).unwrap().as_i64(), Some(1));
+
+    solver.reset();
+    solver.assert(&sevens.store(&i, &x).default()._eq(&ctx.from_i64(7)).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    // The extensionality index is an index of the arrays' domain.
+    let a = Array::new_const(&ctx, "a", &int, &int);
+    assert!(a.ext(&zeroes).get_sort() == int);
+}
+
+#[test]
+fn test_special_relations() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let node = ctx.uninterpreted_sort(&ctx.str_sym("Node"));
+    let a = Ast::new_const(&ctx.str_sym("a"), &node);
+    let b = Ast::new_const(&ctx.str_sym("b"), &node);
+    let c = Ast::new_const(&ctx.str_sym("c"), &node);
+    let solver = Solver::new(&ctx);
+
+    // Any two elements of a linear order are comparable.
+    let lt = FuncDecl::linear_order(&node, 0);
+    solver.assert(&lt.apply(&[&a, &b]).not());
+    solver.assert(&lt.apply(&[&b, &a]).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    // But not necessarily in a partial, tree or piecewise linear order.
+    for order in &[
+        FuncDecl::partial_order(&node, 0),
+        FuncDecl::tree_order(&node, 0),
+        FuncDecl::piecewise_linear_order(&node, 0),
+    ] {
+        solver.reset();
+        solver.assert(&order.apply(&[&a, &b]).not());
+        solver.assert(&order.apply(&[&b, &a]).not());
+        assert_eq!(solver.check(), SatResult::Sat);
+        solver.assert(&order.apply(&[&a, &b]));
+        assert_eq!(solver.check(), SatResult::Unsat);
+    }
+
+    // The closure of an edge relation is reachability.
+    let edge = FuncDecl::new(
+        &ctx,
+        &ctx.str_sym("edge"),
+        &[&node, &node],
+        &ctx.bool_sort(),
+    );
+    let path = edge.transitive_closure();
+    assert_eq!(path.arity(), 2);
+    solver.reset();
+    solver.assert(&edge.apply(&[&a, &b]));
+    solver.assert(&edge.apply(&[&b, &c]));
+    solver.assert(&path.apply(&[&a, &c]).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_symbols() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let named = ctx.str_sym("node");
+    let numbered = ctx.int_sym(42);
+    assert_eq!(named.kind(), SymbolKind::String);
+    assert_eq!(named.as_string(), Some("node".to_owned()));
+    assert_eq!(named.as_int(), None);
+    assert_eq!(numbered.kind(), SymbolKind::Int);
+    assert_eq!(numbered.as_int(), Some(42));
+    assert_eq!(numbered.as_string(), None);
+    assert_eq!(numbered.to_string(), "42");
+    assert!(named == ctx.str_sym("node"));
+    assert!(named != ctx.str_sym("edge"));
+    assert!(numbered != ctx.str_sym("42"));
+
+    // Symbols coming back from Z3 compare equal to the ones passed in.
+    let sort = ctx.uninterpreted_sort(&named);
+    assert!(sort.symbol() == named);
+    let f = FuncDecl::new(&ctx, &numbered, &[&sort], &sort);
+    assert!(f.symbol() == numbered);
+    assert_eq!(f.name(), "42");
+    let c = Ast::new_const(&ctx.int_sym(7), &sort);
+    assert_eq!(c.decl().symbol().as_int(), Some(7));
+}
+
+#[test]
+#[should_panic(expected = "below 2^30")]
+fn test_int_symbol_range() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let _ = ctx.int_sym(1 << 30);
+}
+
+#[test]
+fn test_fresh_declarations() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let int = ctx.int_sort();
+
+    let user = FuncDecl::new(&ctx, &ctx.str_sym("f"), &[&int], &int);
+    let f1 = FuncDecl::fresh(&ctx, "f", &[&int], &int);
+    let f2 = FuncDecl::fresh(&ctx, "f", &[&int], &int);
+    assert!(f1.name().starts_with('f'));
+    assert!(f1.name() != f2.name());
+    assert!(f1.name() != user.name());
+    assert_eq!(f1.arity(), 1);
+
+    // Fresh declarations are independent of each other.
+    let x = ctx.named_int_const("x");
+    let c1 = Ast::fresh_const(&ctx, "c", &int);
+    let c2 = Ast::fresh_const(&ctx, "c", &int);
+    let solver = Solver::new(&ctx);
+    solver.assert(&f1.apply(&[&x])._eq(&f2.apply(&[&x])).not());
+    solver.assert(&c1._eq(&c2).not());
+    assert_eq!(solver.check(), SatResult::Sat);
+}
+
+#[test]
+fn test_model_lookup_by_decl() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let int = ctx.int_sort();
+    let x = ctx.named_int_const("x");
+    let unused = ctx.named_int_const("unused");
+    let f = ctx.func_decl(&ctx.str_sym("f"), &[&int], &int);
+    let g = ctx.func_decl(&ctx.str_sym("g"), &[&int], &int);
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&x._eq(&ctx.from_i64(3)));
+    solver.assert(&f.apply(&[&x])._eq(&ctx.from_i64(9)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+
+    // Declarations held by the caller work as keys, without re-evaluating.
+    assert!(model.has_interp(&x.decl()));
+    assert!(model.has_interp(&f));
+    assert!(!model.has_interp(&unused.decl()));
+    assert!(!model.has_interp(&g));
+    assert_eq!(model.get_const_interp(&x.decl()).unwrap().as_i64(), Some(3));
+    assert!(model.get_const_interp(&unused.decl()).is_none());
+    assert!(model.get_func_interp(&g).is_none());
+    let interp = model.get_func_interp(&f).unwrap();
+    let value = interp
+        .entries()
+        .iter()
+        .find(|e| e.args()[0].as_i64() == Some(3))
+        .map(|e| e.value())
+        .unwrap_or_else(|| interp.else_value());
+    assert_eq!(value.as_i64(), Some(9));
+}
+
+#[test]
+fn test_func_interp_to_map() {
+    use std::collections::HashMap;
+
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let int = ctx.int_sort();
+    let mem = ctx.func_decl(&ctx.str_sym("mem"), &[&int, &int], &int);
+
+    let solver = Solver::new(&ctx);
+    for &(bank, addr, val) in &[(0, 1, 10), (0, 2, 20), (1, 1, 30)] {
+        let cell = mem.apply(&[&ctx.from_i64(bank), &ctx.from_i64(addr)]);
+        solver.assert(&cell._eq(&ctx.from_i64(val)));
+    }
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    let interp = model.get_func_interp(&mem).unwrap();
+
+    assert_eq!(interp.arity(), 2);
+    assert_eq!(interp.num_entries() as usize, interp.entries().len());
+    assert!(interp.entry(interp.num_entries()).is_none());
+
+    let mut contents = HashMap::new();
+    for i in 0..interp.num_entries() {
+        let entry = interp.entry(i).unwrap().clone();
+        assert_eq!(entry.num_args(), 2);
+        let args: Vec<i64> = entry.args().iter().map(|a| a.as_i64().unwrap()).collect();
+        contents.insert((args[0], args[1]), entry.value().as_i64().unwrap());
+    }
+    let default = interp.else_value().as_i64().unwrap();
+    let lookup = |k| *contents.get(&k).unwrap_or(&default);
+    assert_eq!(lookup((0, 1)), 10);
+    assert_eq!(lookup((0, 2)), 20);
+    assert_eq!(lookup((1, 1)), 30);
+}
+
+#[test]
+fn test_model_construction() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let int = ctx.int_sort();
+    let x = ctx.named_int_const("x");
+    let p = ctx.named_bool_const("p");
+    let f = ctx.func_decl(&ctx.str_sym("f"), &[&int, &int], &int);
+
+    let mut model = Model::new(&ctx);
+    assert_eq!(model.num_consts(), 0);
+    model.add_const_interp(&x.decl(), &ctx.from_i64(4));
+    model.add_const_interp(&p.decl(), &ctx.from_bool(true));
+    let mut interp = model.add_func_interp(&f, &ctx.from_i64(-1));
+    interp.add_entry(&[&ctx.from_i64(4), &ctx.from_i64(0)], &ctx.from_i64(1));
+    interp.add_entry(&[&ctx.from_i64(4), &ctx.from_i64(0)], &ctx.from_i64(2));
+    interp.set_else(&ctx.from_i64(0));
+
+    assert_eq!(model.num_consts(), 2);
+    assert!(model.has_interp(&f));
+    assert_eq!(model.get_func_interp(&f).unwrap().num_entries(), 1);
+
+    // Replay a candidate counterexample against a property.
+    let fx0 = f.apply(&[&x, &ctx.from_i64(0)]);
+    let fx1 = f.apply(&[&x, &ctx.from_i64(1)]);
+    let property = p.implies(&fx0.gt(&fx1));
+    assert_eq!(model.eval(&fx0, false).unwrap().as_i64(), Some(2));
+    assert_eq!(model.eval(&fx1, false).unwrap().as_i64(), Some(0));
+    assert_eq!(model.eval(&property, false).unwrap().as_bool(), Some(true));
+}
+
+#[test]
+#[should_panic(expected = "value of sort Bool assigned to constant of sort Int")]
+fn test_model_construction_sort_mismatch() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let mut model = Model::new(&ctx);
+    model.add_const_interp(&x.decl(), &ctx.from_bool(false));
+}
+
+#[test]
+fn test_solver_cube() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let vars: Vec<Ast> = (0..4)
+        .map(|i| ctx.named_bool_const(&format!("v{}", i)))
+        .collect();
+    let solver = Solver::new(&ctx);
+    solver.assert(&vars[0].or(&[&vars[1]]));
+    solver.assert(&vars[2].xor(&vars[3]));
+
+    let var_refs: Vec<&Ast> = vars.iter().collect();
+    let cubes: Vec<Vec<Ast>> = solver.cube(&var_refs).collect();
+    assert!(!cubes.is_empty());
+    for cube in &cubes {
+        let sub = solver.translate(&ctx);
+        for lit in cube {
+            sub.assert(lit);
+        }
+        assert_eq!(sub.check(), SatResult::Sat);
+    }
+
+    // The cubes cover every model of the assertions.
+    let tt = ctx.from_bool(true);
+    let conjunctions: Vec<Ast> = cubes
+        .iter()
+        .map(|c| tt.and(&c.iter().collect::<Vec<_>>()))
+        .collect();
+    let check = solver.translate(&ctx);
+    check.assert(
+        &ctx.from_bool(false)
+            .or(&conjunctions.iter().collect::<Vec<_>>())
+            .not(),
+    );
+    assert_eq!(check.check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_solver_consequences() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+    let p = ctx.named_bool_const("p");
+    let q = ctx.named_bool_const("q");
+    let solver = Solver::new(&ctx);
+    solver.assert(&x._eq(&ctx.from_i64(2)));
+    solver.assert(&y.gt(&x));
+    solver.assert(&p.or(&[&q]));
+    solver.assert(&p.not().or(&[&q]));
+
+    // Backbone: q and x are fixed, p and y are not.
+    let (result, consequences) = solver.get_consequences(&[], &[&x, &y, &p, &q]);
+    assert_eq!(result, SatResult::Sat);
+    let mut strs: Vec<String> = consequences.iter().map(|c| c.to_string()).collect();
+    strs.sort();
+    assert_eq!(strs, vec!["(=> true (= x 2))", "(=> true q)"]);
+
+    let (result, consequences) = solver.get_consequences(&[&q.not()], &[&x]);
+    assert_eq!(result, SatResult::Unsat);
+    assert!(consequences.is_empty());
+}
+
+#[test]
+fn test_solver_for_logic() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    assert_eq!(Logic::QF_BV.to_string(), "QF_BV");
+    assert_eq!(Logic::AUFLIA.name(), "AUFLIA");
+
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+    let solver = Solver::new_for_logic(&ctx, Logic::QF_LIA);
+    solver.assert(&x.add(&[&y])._eq(&ctx.from_i64(10)));
+    solver.assert(&x.sub(&[&y])._eq(&ctx.from_i64(4)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    assert_eq!(model.eval(&x, true).unwrap().as_i64(), Some(7));
+
+    let by_name = Solver::new_for_logic(&ctx, String::from("QF_NIA"));
+    by_name.assert(&x.mul(&[&x])._eq(&ctx.from_i64(9)));
+    assert_eq!(by_name.check(), SatResult::Sat);
+
+    for &logic in &[
+        Logic::QF_UF,
+        Logic::QF_BV,
+        Logic::QF_ABV,
+        Logic::QF_AUFBV,
+        Logic::QF_UFBV,
+        Logic::QF_IDL,
+        Logic::QF_RDL,
+        Logic::QF_LIA,
+        Logic::QF_LRA,
+        Logic::QF_NIA,
+        Logic::QF_NRA,
+        Logic::QF_UFLIA,
+        Logic::QF_FP,
+        Logic::QF_FPBV,
+        Logic::QF_S,
+        Logic::LIA,
+        Logic::LRA,
+        Logic::NIA,
+        Logic::NRA,
+        Logic::UFLIA,
+        Logic::AUFLIA,
+        Logic::UFBV,
+    ] {
+        assert_eq!(Solver::new_for_logic(&ctx, logic).check(), SatResult::Sat);
+    }
+}
+
+#[test]
+#[should_panic(expected = "unknown logic: NOT_A_LOGIC")]
+fn test_solver_for_unknown_logic() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    Solver::new_for_logic(&ctx, "NOT_A_LOGIC");
+}
+
+#[test]
+fn test_solver_scopes() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let solver = Solver::new(&ctx);
+    assert_eq!(solver.get_num_scopes(), 0);
+
+    solver.assert(&x.gt(&ctx.from_i64(0)));
+    for i in 1..4 {
+        solver.push();
+        solver.assert(&x.lt(&ctx.from_i64(4 - i)));
+        assert_eq!(solver.get_num_scopes(), i as u32);
+    }
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    solver.pop(2);
+    assert_eq!(solver.get_num_scopes(), 1);
+    assert_eq!(solver.check(), SatResult::Sat);
+    solver.pop(0);
+    assert_eq!(solver.get_num_scopes(), 1);
+    solver.pop(1);
+    assert_eq!(solver.assertions().count(), 1);
+}
+
+#[test]
+#[should_panic(expected = "cannot pop 2 scopes from a solver with 1")]
+fn test_solver_pop_too_far() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let solver = Solver::new(&ctx);
+    solver.push();
+    solver.pop(2);
+}
+
+#[test]
+fn test_solver_clone() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let solver = Solver::new(&ctx);
+    solver.assert(&x.gt(&ctx.from_i64(0)));
+    solver.assert(&x.lt(&ctx.from_i64(10)));
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    let left = solver.clone();
+    let right = solver.clone();
+    left.assert(&x._eq(&ctx.from_i64(5)));
+    right.assert(&x._eq(&ctx.from_i64(50)));
+    assert_eq!(left.check(), SatResult::Sat);
+    assert_eq!(right.check(), SatResult::Unsat);
+    assert_eq!(solver.check(), SatResult::Sat);
+    assert_eq!(solver.assertions().count(), 2);
+    assert_eq!(left.assertions().count(), 3);
+}
+
+#[test]
+#[should_panic(expected = "cannot copy a solver at scope depth 1")]
+fn test_solver_clone_with_scopes() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let solver = Solver::new(&ctx);
+    solver.push();
+    let _ = solver.clone();
+}
+
+#[derive(Default)]
+struct PropagatorStats {
+    fixed: u32,
+    finals: u32,
+}
+
+struct AtMostOne {
+    trail: Vec<u32>,
+    scopes: Vec<usize>,
+    stats: std::rc::Rc<std::cell::RefCell<PropagatorStats>>,
+}
+
+impl<'ctx> UserPropagator<'ctx> for AtMostOne {
+    fn push(&mut self) {
+        self.scopes.push(self.trail.len());
+    }
+
+    fn pop(&mut self, num_scopes: u32) {
+        let n = self.scopes.len() - num_scopes as usize;
+        self.trail.truncate(self.scopes[n]);
+        self.scopes.truncate(n);
+    }
+
+    fn fixed(&mut self, cb: &PropagatorCallback<'ctx>, id: u32, value: &Ast<'ctx>) {
+        self.stats.borrow_mut().fixed += 1;
+        if value.as_bool() == Some(true) {
+            if let Some(&other) = self.trail.first() {
+                cb.conflict(&[other, id], &[]);
+            }
+            self.trail.push(id);
+        }
+    }
+
+    fn final_check(&mut self, _cb: &PropagatorCallback<'ctx>) {
+        self.stats.borrow_mut().finals += 1;
+    }
+}
+
+#[test]
+fn test_user_propagator() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let vars: Vec<Ast> = (0..4)
+        .map(|i| ctx.named_bool_const(&format!("v{}", i)))
+        .collect();
+    let stats = std::rc::Rc::new(std::cell::RefCell::new(PropagatorStats::default()));
+
+    let mut solver = Solver::new_simple(&ctx);
+    solver.set_user_propagator(AtMostOne {
+        trail: vec![],
+        scopes: vec![],
+        stats: stats.clone(),
+    });
+    let ids: Vec<u32> = vars.iter().map(|v| solver.propagate_register(v)).collect();
+    assert_eq!(ids.len(), 4);
+
+    solver.assert(&vars[0].or(&vars[1..].iter().collect::<Vec<_>>()));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    let set = vars
+        .iter()
+        .filter(|v| model.eval(v, true).unwrap().as_bool() == Some(true))
+        .count();
+    assert_eq!(set, 1);
+    assert!(stats.borrow().fixed > 0);
+    assert!(stats.borrow().finals > 0);
+
+    solver.push();
+    solver.assert(&vars[1].and(&[&vars[2]]));
+    assert_eq!(solver.check(), SatResult::Unsat);
+    solver.pop(1);
+    assert_eq!(solver.check(), SatResult::Sat);
+}
+
+// Propagates `vars[i + 1]` whenever `vars[i]` becomes true.
+struct Chain<'ctx> {
+    vars: Vec<Ast<'ctx>>,
+}
+
+impl<'ctx> UserPropagator<'ctx> for Chain<'ctx> {
+    fn push(&mut self) {}
+
+    fn pop(&mut self, _num_scopes: u32) {}
+
+    fn fixed(&mut self, cb: &PropagatorCallback<'ctx>, id: u32, value: &Ast<'ctx>) {
+        let next = id as usize + 1;
+        if value.as_bool() == Some(true) && next < self.vars.len() {
+            cb.propagate(&[id], &[], &self.vars[next]);
+        }
+    }
+}
+
+#[test]
+fn test_user_propagator_consequences() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let vars: Vec<Ast> = (0..4)
+        .map(|i| ctx.named_bool_const(&format!("c{}", i)))
+        .collect();
+
+    let mut solver = Solver::new_simple(&ctx);
+    solver.set_user_propagator(Chain { vars: vars.clone() });
+    for (i, v) in vars.iter().enumerate() {
+        assert_eq!(solver.propagate_register(v), i as u32);
+    }
+
+    solver.assert(&vars[1]);
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    for v in &vars[1..] {
+        assert_eq!(model.eval(v, true).unwrap().as_bool(), Some(true));
+    }
+
+    solver.assert(&vars[3].not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+struct Panicking;
+
+impl<'ctx> UserPropagator<'ctx> for Panicking {
+    fn push(&mut self) {}
+
+    fn pop(&mut self, _num_scopes: u32) {}
+
+    fn fixed(&mut self, _cb: &PropagatorCallback<'ctx>, _id: u32, _value: &Ast<'ctx>) {
+        panic!("propagator failed");
+    }
+}
+
+#[test]
+#[should_panic(expected = "propagator failed")]
+fn test_user_propagator_panic() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let p = ctx.named_bool_const("p");
+    let mut solver = Solver::new_simple(&ctx);
+    solver.set_user_propagator(Panicking);
+    solver.propagate_register(&p);
+    solver.assert(&p);
+    solver.check();
+}
+
+#[test]
+#[should_panic(expected = "user-propagators are only supported on the SMT solver")]
+fn test_user_propagator_unsupported_solver() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let mut solver = Solver::from_tactic(&Tactic::new(&ctx, "smt"));
+    solver.set_user_propagator(Panicking);
+}
+
+#[cfg(feature = "z3_4_13")]
+#[test]
+fn test_on_clause() {
+    let mut cfg = Config::new();
+    cfg.set_proof_generation(true);
+    let ctx = Context::new(&cfg);
+    let p = ctx.named_bool_const("p");
+    let q = ctx.named_bool_const("q");
+    let mut clauses = Vec::new();
+    let mut solver = Solver::new(&ctx);
+    solver.register_on_clause(|_, _, literals| clauses.push(literals.len()));
+    solver.assert(&p.or(&[&q]));
+    solver.assert(&p.not().or(&[&q]));
+    solver.assert(&q.not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+    drop(solver);
+    assert!(!clauses.is_empty());
+}
+
+#[cfg(feature = "z3_4_13")]
+#[test]
+#[should_panic(expected = "on-clause callback failed")]
+fn test_on_clause_panic() {
+    let mut cfg = Config::new();
+    cfg.set_proof_generation(true);
+    let ctx = Context::new(&cfg);
+    let p = ctx.named_bool_const("p");
+    let q = ctx.named_bool_const("q");
+    let mut solver = Solver::new(&ctx);
+    solver.register_on_clause(|_, _, _| panic!("on-clause callback failed"));
+    solver.assert(&p.or(&[&q]));
+    solver.assert(&p.not().or(&[&q]));
+    solver.assert(&q.not());
+    solver.check();
+}
+
+#[test]
+fn test_minimize_core() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+    let lits: Vec<Ast> = (0..6)
+        .map(|i| ctx.named_bool_const(&format!("l{}", i)))
+        .collect();
+    let solver = Solver::new(&ctx);
+    let constraints = [
+        x.gt(&ctx.from_i64(0)),
+        y.gt(&x),
+        y.lt(&ctx.from_i64(100)),
+        x.gt(&ctx.from_i64(50)),
+        y.lt(&ctx.from_i64(40)),
+        x._eq(&ctx.from_i64(60)),
+    ];
+    for (l, c) in lits.iter().zip(constraints.iter()) {
+        solver.assert(&l.implies(c));
+    }
+    let assertions_before = solver.assertions().count();
+
+    let all: Vec<&Ast> = lits.iter().collect();
+    let core = solver.minimize_core(&all);
+    // Either {x > 50, y > x, y < 40} or {x = 60, y > x, y < 40}.
+    assert_eq!(core.len(), 3);
+    assert!(core.contains(&lits[1]) && core.contains(&lits[4]));
+    assert!(core.contains(&lits[3]) || core.contains(&lits[5]));
+    let core_refs: Vec<&Ast> = core.iter().collect();
+    match solver.check_assumptions(&core_refs) {
+        CheckResult::Unsatisfiable => {}
+        _ => panic!("minimized core is not unsat"),
+    }
+    for i in 0..core.len() {
+        let mut fewer = core_refs.clone();
+        fewer.remove(i);
+        assert_eq!(solver.minimize_core(&fewer).len(), fewer.len());
+    }
+    assert_eq!(solver.assertions().count(), assertions_before);
+    assert_eq!(solver.get_num_scopes(), 0);
+}
+
+#[test]
+fn test_maxsat() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x: Vec<Ast> = (0..4)
+        .map(|i| ctx.named_bool_const(&format!("x{}", i)))
+        .collect();
+
+    // At most one of the variables can be true.
+    let mut maxsat = MaxSat::new(&ctx);
+    for i in 0..x.len() {
+        for j in i + 1..x.len() {
+            maxsat.assert(&x[i].and(&[&x[j]]).not());
+        }
+    }
+    let weights = [4, 3, 5, 2];
+    for (v, &w) in x.iter().zip(weights.iter()) {
+        maxsat.add_soft(v, w);
+    }
+    let either = maxsat.add_soft(&x[0].or(&[&x[1]]), 7);
+
+    // x0 with the clause costs 3 + 5 + 2 = 10; x2 alone costs 4 + 3 + 2 + 7.
+    let solution = maxsat.solve().unwrap();
+    assert_eq!(solution.cost, 10);
+    assert_eq!(solution.satisfied, vec![0, either]);
+    assert_eq!(
+        solution.model.eval(&x[0], true).unwrap().as_bool(),
+        Some(true)
+    );
+
+    maxsat.assert(&x[1].and(&[&x[3]]));
+    assert!(maxsat.solve().is_none());
+    assert_eq!(maxsat.optimize().check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_portfolio() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+    let solver = Solver::new(&ctx);
+    solver.assert(&x.add(&[&y])._eq(&ctx.from_i64(10)));
+    solver.assert(&x.gt(&y));
+
+    // The "fail" tactic always gives up, so only the second strategy can win.
+    let portfolio = Portfolio::new().tactic("fail").strategy(Solver::new);
+    assert_eq!(portfolio.len(), 2);
+    let result = portfolio.check(&cfg, &solver);
+    assert_eq!(result.result, SatResult::Sat);
+    assert_eq!(result.winner, Some(1));
+    let model = result.model.unwrap();
+    let xv = model.eval(&x, true).unwrap().as_i64().unwrap();
+    let yv = model.eval(&y, true).unwrap().as_i64().unwrap();
+    assert!(xv + yv == 10 && xv > yv);
+
+    solver.assert(&x.lt(&y));
+    let result = portfolio.check(&cfg, &solver);
+    assert_eq!(result.result, SatResult::Unsat);
+    assert_eq!(result.winner, Some(1));
+    assert!(result.model.is_none());
+
+    let result = Portfolio::new().tactic("fail").check(&cfg, &solver);
+    assert_eq!(result.result, SatResult::Unknown);
+    assert_eq!(result.winner, None);
+}
+
+#[test]
+fn test_portfolio_interrupts_others() {
+    // Assert a pigeonhole problem that takes far too long to refute, so
+    // the test only finishes if the strategy is interrupted.
+    fn pigeonhole(ctx: &Context) -> Solver<'_> {
+        let solver = Solver::new(ctx);
+        let (pigeons, holes) = (14, 13);
+        let p: Vec<Vec<Ast>> = (0..pigeons)
+            .map(|i| {
+                (0..holes)
+                    .map(|j| ctx.named_bool_const(&format!("p{}_{}", i, j)))
+                    .collect()
+            })
+            .collect();
+        for row in &p {
+            let rest: Vec<&Ast> = row[1..].iter().collect();
+            solver.assert(&row[0].or(&rest));
+        }
+        for j in 0..holes {
+            let column: Vec<&Ast> = p.iter().map(|row| &row[j]).collect();
+            for (a, pa) in column.iter().enumerate() {
+                for pb in &column[a + 1..] {
+                    solver.assert(&pa.and(&[pb]).not());
+                }
+            }
+        }
+        solver
+    }
+
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_bool_const("x");
+    let solver = Solver::new(&ctx);
+    solver.assert(&x);
+
+    let portfolio = Portfolio::new().strategy(pigeonhole).strategy(Solver::new);
+    let result = portfolio.check(&cfg, &solver);
+    assert_eq!(result.result, SatResult::Sat);
+    assert_eq!(result.winner, Some(1));
+    let model = result.model.unwrap();
+    assert_eq!(model.eval(&x, true).unwrap().as_bool(), Some(true));
+}
+
+#[test]
+#[should_panic(expected = "portfolio has no strategies")]
+fn test_portfolio_without_strategies() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    Portfolio::new().check(&cfg, &Solver::new(&ctx));
+}
+
+#[test]
+fn test_ast_print_mode() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let mut t = x.add(&[&ctx.from_i64(-3)]);
+    for _ in 0..4 {
+        t = t.mul(&[&t]);
+    }
+
+    let full = t.to_string();
+    assert!(!full.contains("let"));
+
+    ctx.set_ast_print_mode(AstPrintMode::SmtLib2Compliant);
+    let compliant = t.to_string();
+    assert!(compliant.starts_with("(let "));
+    assert!(compliant.len() < full.len());
+    let src = format!("(assert (= {} 0))", compliant);
+    let parsed = ctx.parse_smtlib2(&src, &[], &[&x.decl()]).unwrap();
+    let solver = Solver::new(&ctx);
+    solver.assert(&parsed[0].iff(&t._eq(&ctx.from_i64(0))).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    ctx.set_ast_print_mode(AstPrintMode::LowLevel);
+    assert!(t.to_string().contains(":="));
+
+    ctx.set_ast_print_mode(AstPrintMode::SmtLibFull);
+    assert_eq!(t.to_string(), full);
+}
+
+#[test]
+fn test_ast_to_smt2() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let s = ctx.uninterpreted_sort(&ctx.str_sym("U"));
+    let u = ctx.named_const("u", &s);
+    let f = ctx.func_decl(&ctx.str_sym("f"), &[&s], &ctx.int_sort());
+    let x = ctx.named_int_const("x");
+    let sum = x.add(&[&f.apply(&[&u])]);
+    let product = sum.mul(&[&sum]);
+    let formula = product.gt(&ctx.from_i64(0));
+
+    // The print mode does not affect the output.
+    ctx.set_ast_print_mode(AstPrintMode::LowLevel);
+    let smt2 = formula.to_smt2();
+    assert!(smt2.starts_with("(declare-sort U 0)\n"));
+    assert!(smt2.contains("(declare-fun f (U) Int)\n"));
+    assert!(!smt2.contains("assert"));
+    assert!(!smt2.contains("check-sat"));
+
+    // Declarations and term can be read back.
+    let (decls, term): (Vec<&str>, Vec<&str>) =
+        smt2.lines().partition(|l| l.starts_with("(declare-"));
+    let src = format!("{}\n(assert {})", decls.join("\n"), term.join("\n"));
+    let parsed = ctx.parse_smtlib2(&src, &[], &[]).unwrap();
+    let solver = Solver::new(&ctx);
+    solver.assert(&parsed[0].iff(&formula).not());
+    assert_eq!(solver.check(), SatResult::Unsat);
+
+    let term = product.to_smt2();
+    assert_eq!(term.lines().next(), Some("(declare-sort U 0)"));
+    assert!(term.contains("(let "));
+    assert_eq!(x.to_smt2(), "(declare-fun x () Int)\nx");
+    assert_eq!(ctx.from_bool(true).to_smt2(), "true");
+    assert_eq!(ctx.from_bool(false).to_smt2(), "false");
+}
+
+#[test]
+fn test_model_display_and_solver_debug() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let f = ctx.func_decl(&ctx.str_sym("f"), &[&ctx.int_sort()], &ctx.int_sort());
+    let solver = Solver::new(&ctx);
+    solver.assert(&x._eq(&ctx.from_i64(3)));
+    solver.push();
+    solver.assert(&f.apply(&[&x])._eq(&ctx.from_i64(4)));
+    assert_eq!(
+        format!("{:?}", solver),
+        "Solver { scopes: 1, assertions: 2, user_propagator: false }"
+    );
+
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    let shown = model.to_string();
+    assert!(shown.lines().any(|l| l == "x -> 3"));
+    assert!(shown.contains("f -> "));
+
+    assert_eq!(Model::new(&ctx).to_string(), "");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_model_serialization() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let b = ctx.named_bool_const("b");
+    let i = ctx.named_int_const("i");
+    let r = ctx.named_real_const("r");
+    let bv = ctx.named_bitvector_const("bv", 8);
+    let s = Seq::new_string_const(&ctx, "s");
+    let f = ctx.func_decl(&ctx.str_sym("f"), &[&ctx.int_sort()], &ctx.int_sort());
+
+    let solver = Solver::new(&ctx);
+    solver.assert(&b.not());
+    solver.assert(&i._eq(&ctx.from_i64(-12)));
+    solver.assert(&r._eq(&ctx.from_real(1, 3)));
+    solver.assert(&bv._eq(&ctx.bitvector_sort(8).from_i64(-1)));
+    solver.assert(&s._eq(&Seq::from_string(&ctx, "a\"b")));
+    solver.assert(&f.apply(&[&i])._eq(&i));
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+
+    let values = model.values();
+    assert_eq!(values.len(), 5);
+    assert_eq!(values["b"], ModelValue::Bool(false));
+    assert_eq!(values["i"], ModelValue::Int("-12".to_owned()));
+    assert_eq!(values["r"], ModelValue::Real("1/3".to_owned()));
+    assert_eq!(
+        values["bv"],
+        ModelValue::BitVec {
+            width: 8,
+            value: "255".to_owned()
+        }
+    );
+    assert_eq!(values["s"], ModelValue::String("a\"b".to_owned()));
+
+    let json = serde_json::to_string(&model).unwrap();
+    assert_eq!(
+        json,
+        concat!(
+            r#"{"b":{"sort":"bool","value":false},"#,
+            r#""bv":{"sort":"bitvec","value":{"width":8,"value":"255"}},"#,
+            r#""i":{"sort":"int","value":"-12"},"#,
+            r#""r":{"sort":"real","value":"1/3"},"#,
+            r#""s":{"sort":"string","value":"a\"b"}}"#
+        )
+    );
+    let parsed: std::collections::BTreeMap<String, ModelValue> =
+        serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, values);
+}
+
+#[test]
+fn test_z3_macro() {
+    fn positive<'ctx>(ctx: &'ctx Context, x: &Ast<'ctx>) -> Ast<'ctx> {
+        z3!(ctx, x > 0)
+    }
+
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let y = ctx.named_int_const("y");
+    let r = ctx.named_real_const("r");
+    let b = ctx.named_bitvector_const("b", 8);
+    let p = ctx.named_bool_const("p");
+    let q = ctx.named_bool_const("q");
+    let f = ctx.func_decl(
+        &ctx.str_sym("f"),
+        &[&ctx.int_sort(), &ctx.real_sort()],
+        &ctx.int_sort(),
+    );
+    let int = |n| ctx.from_i64(n);
+    let real = |n| ctx.real_sort().from_i64(n);
+    let bv8 = |n| ctx.bitvector_sort(8).from_i64(n);
+
+    // Precedence and associativity.
+    assert!(z3!(ctx, x + 1 > x) == x.add(&[&int(1)]).gt(&x));
+    assert!(z3!(ctx, x + 2 * y - 1) == x.add(&[&int(2).mul(&[&y])]).sub(&[&int(1)]));
+    assert!(z3!(ctx, x - y - 1) == x.sub(&[&y]).sub(&[&int(1)]));
+    assert!(z3!(ctx, -x == 2 * 3) == x.minus()._eq(&int(6)));
+    assert!(z3!(ctx, x / 2 != x % 3) == x.div(&int(2))._eq(&x.modulo(&int(3))).not());
+    assert!(z3!(ctx, p && !q || q) == p.and(&[&q.not()]).or(&[&q]));
+    assert!(z3!(ctx, p => q || p => q) == p.implies(&q.or(&[&p]).implies(&q)));
+    assert!(z3!(ctx, (p => q) && true) == p.implies(&q).and(&[&ctx.from_bool(true)]));
+
+    // Literals take the sort of the terms they are combined with.
+    assert!(z3!(ctx, r + 1 >= -2) == r.add(&[&real(1)]).ge(&real(-2)));
+    assert!(z3!(ctx, b * 2 < 8) == b.bvmul(&bv8(2)).bvult(&bv8(8)));
+    assert!(z3!(ctx, f(x + 1, 2) <= y) == f.apply(&[&x.add(&[&int(1)]), &real(2)]).le(&y));
+    assert!(z3!(ctx, { b.bvashr(&bv8(1)) } == 3) == b.bvashr(&bv8(1))._eq(&bv8(3)));
+    assert!(z3!(ctx, r == 1 / 2) == r._eq(&real(1).div(&real(2))));
+    assert!(z3!(ctx, r < -(1 / 2) + 1) == r.lt(&real(1).div(&real(2)).minus().add(&[&real(1)])));
+    assert!(z3!(ctx, 7 / 2 == 3) == int(7).div(&int(2))._eq(&int(3)));
+    assert!(positive(&ctx, &x) == x.gt(&int(0)));
+
+    // Quantifiers bind fresh constants that shadow Rust variables.
+    let bound = ctx.named_int_const("x");
+    assert!(
+        z3!(ctx, forall x: Int . x + 1 > x)
+            == Ast::forall_const(&ctx, &[&bound], &[], &bound.add(&[&int(1)]).gt(&bound))
+    );
+    let solver = Solver::new(&ctx);
+    solver.assert(&z3!(ctx, p && exists c: BitVec(8), d: BitVec(8) . c * d == 1 && c != 1));
+    solver.assert(&z3!(ctx, forall v: Real, w: {ctx.real_sort()} . v < w => 2 * v < v + w));
+    assert_eq!(solver.check(), SatResult::Sat);
+    solver.assert(&z3!(ctx, forall z: Int . z * z >= 0 => !p));
+    assert_eq!(solver.check(), SatResult::Unsat);
+}
+
+#[test]
+fn test_z3_sort_primitives() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+
+    assert!(bool::z3_sort(&ctx) == ctx.bool_sort());
+    assert!(u8::z3_sort(&ctx) == ctx.int_sort());
+    assert!(Box::<i64>::z3_sort(&ctx) == ctx.int_sort());
+    assert!(String::z3_sort(&ctx) == Sort::string(&ctx));
+    assert!(true.to_z3(&ctx) == ctx.from_bool(true));
+    assert!((-3i32).to_z3(&ctx) == ctx.from_i64(-3));
+    assert!(u64::MAX.to_z3(&ctx) == ctx.from_u64(u64::MAX));
+    assert!(Box::new(7usize).to_z3(&ctx) == ctx.from_u64(7));
+    assert!("abc".to_z3(&ctx) == String::from("abc").to_z3(&ctx));
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn test_derive_z3_sort() {
+    #[derive(Z3Sort)]
+    struct Point {
+        x: i64,
+        y: i64,
+    }
+
+    #[derive(Z3Sort)]
+    enum Shape {
+        Empty,
+        Circle { center: Point, radius: u32 },
+        Polygon(Point, Box<Shape>),
+    }
+
+    #[derive(Z3Sort)]
+    enum List {
+        Nil,
+        Cons(bool, Box<List>),
+    }
+
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+
+    let point = Point::z3_datatype(&ctx);
+    assert_eq!(point.variants.len(), 1);
+    assert_eq!(point.variants[0].constructor.name(), "Point");
+    assert_eq!(point.variants[0].accessors[1].name(), "y");
+    let shape = Shape::z3_datatype(&ctx);
+    assert_eq!(shape.variants[2].accessors[1].name(), "Polygon_1");
+    assert!(shape.variants[2].accessors[1].range() == Shape::z3_sort(&ctx));
+    assert!(Shape::z3_sort(&ctx) == shape.sort);
+
+    // Values convert to constructor applications.
+    let p = Point { x: 1, y: -2 }.to_z3(&ctx);
+    let args = [&ctx.from_i64(1), &ctx.from_i64(-2)];
+    assert!(p == point.variants[0].constructor.apply(&args));
+    let list = List::Cons(true, Box::new(List::Cons(false, Box::new(List::Nil))));
+    let list_sort = List::z3_datatype(&ctx);
+    let x = ctx.named_const("x", &list_sort.sort);
+    let cons = &list_sort.variants[1];
+    let solver = Solver::new(&ctx);
+    solver.assert(&cons.accessors[1].apply(&[&x])._eq(&list.to_z3(&ctx)));
+    solver.assert(&cons.accessors[0].apply(&[&x]).not());
+    assert_eq!(solver.check(), SatResult::Sat);
+    let model = solver.get_model().unwrap();
+    let tail = cons.accessors[1].apply(&[&model.eval(&x, true).unwrap()]);
+    let is_cons = model.eval(&cons.tester.apply(&[&tail]), true).unwrap();
+    assert_eq!(is_cons.as_bool(), Some(true));
+
+    let shapes = [
+        Shape::Empty,
+        Shape::Circle {
+            center: Point { x: 0, y: 0 },
+            radius: 2,
+        },
+        Shape::Polygon(Point { x: 3, y: 4 }, Box::new(Shape::Empty)),
+    ];
+    for (i, s) in shapes.iter().enumerate() {
+        let value = s.to_z3(&ctx);
+        let tester = shape.variants[i].tester.apply(&[&value]);
+        assert_eq!(model.eval(&tester, true).unwrap().as_bool(), Some(true));
+    }
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn test_derive_z3_sort_declared_once() {
+    #[derive(Z3Sort)]
+    enum Tree {
+        Leaf(i64),
+        Node(Box<Forest>),
+    }
+
+    #[derive(Z3Sort)]
+    enum Forest {
+        Nil,
+        Cons(Box<Tree>, Box<Forest>),
+    }
+
+    #[derive(Z3Sort)]
+    struct Wrapper {
+        forest: Forest,
+        size: u32,
+    }
+
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+
+    let tree = Tree::Node(Box::new(Forest::Cons(
+        Box::new(Tree::Leaf(1)),
+        Box::new(Forest::Nil),
+    )));
+    let a = tree.to_z3(&ctx);
+    let b = tree.to_z3(&ctx);
+    assert!(a.get_sort() == b.get_sort());
+    assert!(a == b);
+    assert!(a.get_sort() == Tree::z3_sort(&ctx));
+
+    // The datatypes refer to each other.
+    let tree_sort = Tree::z3_datatype(&ctx);
+    let forest_sort = Forest::z3_datatype(&ctx);
+    assert!(tree_sort.variants[1].accessors[0].range() == forest_sort.sort);
+    assert!(forest_sort.variants[1].accessors[0].range() == tree_sort.sort);
+    assert!(forest_sort.variants[1].accessors[1].range() == forest_sort.sort);
+    let wrapper = Wrapper::z3_datatype(&ctx);
+    assert!(wrapper.variants[0].accessors[0].range() == Forest::z3_sort(&ctx));
+
+    let x = ctx.named_const("x", &tree_sort.sort);
+    let solver = Solver::new(&ctx);
+    solver.assert(&x._eq(&a));
+    solver.assert(&tree_sort.variants[0].tester.apply(&[&x]).not());
+    assert_eq!(solver.check(), SatResult::Sat);
+
+    // Each context declares its own datatypes.
+    let other = Context::new(&cfg);
+    assert_eq!(Tree::z3_datatype(&other).variants.len(), 2);
+    assert!(Tree::z3_sort(&other) == Tree::z3_sort(&other));
+}
+
+#[test]
+fn test_version() {
+    let v = version();
+    assert!(v.major >= 4);
+    assert!(full_version().contains(&v.to_string()));
+
+    let next = Version {
+        minor: v.minor + 1,
+        build_number: 0,
+        ..v
+    };
+    assert!(v < next);
+    assert_eq!(next.major, v.major);
+}
+
+#[test]
+fn test_memory() {
+    let cfg = Config::new();
+    // Memory is never released while a configuration or context is alive,
+    // e.g. those of other tests running in parallel.
+    assert!(!reset_memory());
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let solver = Solver::new(&ctx);
+    solver.assert(&x.gt(&ctx.from_i64(0)));
+    assert_eq!(solver.check(), SatResult::Sat);
+    assert!(estimated_alloc_size() > 0);
+    assert!(!reset_memory());
+    assert!(!finalize_memory());
+    assert_eq!(solver.check(), SatResult::Sat);
+}
+
+#[test]
+#[should_panic(expected = "objective index 1 out of range for 1 objectives")]
+fn test_optimize_objective_index_out_of_range() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let opt = Optimize::new(&ctx);
+    opt.assert(&x.lt(&ctx.from_i64(5)));
+    opt.maximize(&x);
+    assert_eq!(opt.check(), SatResult::Sat);
+    assert_eq!(opt.get_upper(0).as_i64(), Some(4));
+    opt.get_lower(1);
+}
+
+#[test]
+fn test_optimize_objective_popped() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let x = ctx.named_int_const("x");
+    let opt = Optimize::new(&ctx);
+    opt.assert(&x.lt(&ctx.from_i64(5)));
+    opt.push();
+    let max_x = opt.maximize(&x);
+    opt.pop();
+    assert_eq!(opt.check(), SatResult::Sat);
+
+    let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| max_x.upper()));
+    let err = err.err().unwrap();
+    assert_eq!(
+        err.downcast_ref::<String>().unwrap(),
+        "objective index 0 out of range for 0 objectives"
+    );
+}
+
+#[test]
+#[should_panic(expected = "objective index 0 out of range for 0 objectives")]
+fn test_optimize_soft_popped() {
+    let cfg = Config::new();
+    let ctx = Context::new(&cfg);
+    let p = ctx.named_bool_const("p");
+    let opt = Optimize::new(&ctx);
+    opt.push();
+    let soft = opt.add_soft(&p, 1, None);
+    opt.pop();
+    assert_eq!(opt.check(), SatResult::Sat);
+    soft.penalty();
+}
@@ -32,10 +32,7 @@ impl Spec {
 type SpecMap = HashMap<String, Vec<Spec>>;
 
 fn get_version(sm: &SpecMap, pkg: &str, ver: usize) -> Option<Version> {
-    match sm.get(pkg) {
-        None => None,
-        Some(specs) => Some(specs[ver].vers.clone()),
-    }
+    sm.get(pkg).map(|specs| specs[ver].vers.clone())
 }
 
 fn version_index(sm: &SpecMap, pkg: &str, ver: &str) -> Option<usize> {
@@ -198,7 +195,7 @@ fn test_solve_simple_semver_example() {
     opt.maximize(&ctx.from_i64(0).add(&asts.values().collect::<Vec<&Ast>>()));
 
     // Ensure we have a constant for every pkg _or_ dep listed
-    for k in (&smap).keys() {
+    for k in smap.keys() {
         asts.entry(k.clone()).or_insert_with(|| {
             info!("new AST for {}", k);
             ctx.fresh_int_const("pkg")
@@ -206,7 +203,7 @@ fn test_solve_simple_semver_example() {
     }
     for specs in smap.values() {
         for spec in specs {
-            for r in (&spec).reqs.keys() {
+            for r in spec.reqs.keys() {
                 asts.entry(r.clone()).or_insert_with(|| {
                     info!("new AST for {}", r);
                     ctx.fresh_int_const("dep-pkg")
@@ -230,10 +227,10 @@ fn test_solve_simple_semver_example() {
                             "Asserting: {} == #{} {} => {} >= #{} {}",
                             k,
                             n,
-                            get_version(&smap, k, n as usize).unwrap(),
+                            get_version(&smap, k, n).unwrap(),
                             r,
                             low,
-                            get_version(&smap, r, low as usize).unwrap()
+                            get_version(&smap, r, low).unwrap()
                         );
                         opt.assert(
                             &k_ast
@@ -249,10 +246,10 @@ fn test_solve_simple_semver_example() {
                             "Asserting: {} == #{} {} => {} <= #{} {}",
                             k,
                             n,
-                            get_version(&smap, k, n as usize).unwrap(),
+                            get_version(&smap, k, n).unwrap(),
                             r,
                             high,
-                            get_version(&smap, r, high as usize).unwrap()
+                            get_version(&smap, r, high).unwrap()
                         );
                         opt.assert(
                             &k_ast
@@ -265,12 +262,12 @@ fn test_solve_simple_semver_example() {
         }
     }
 
-    assert!(opt.check());
-    let model = opt.get_model();
+    assert_eq!(opt.check(), SatResult::Sat);
+    let model = opt.get_model().unwrap();
 
     for k in root.keys() {
         let ast = &asts[k];
-        let idx = model.eval(ast).unwrap().as_i64().unwrap();
+        let idx = model.eval(ast, true).unwrap().as_i64().unwrap();
         info!(
             "solved: {}: #{} = {}",
             k,
@@ -282,8 +279,8 @@ fn test_solve_simple_semver_example() {
     let pg_a = &asts["postgres"];
     let r2_a = &asts["r2d2-postgres"];
 
-    let pg_v = model.eval(pg_a).unwrap().as_i64().unwrap() as usize;
-    let r2_v = model.eval(r2_a).unwrap().as_i64().unwrap() as usize;
+    let pg_v = model.eval(pg_a, true).unwrap().as_i64().unwrap() as usize;
+    let r2_v = model.eval(r2_a, true).unwrap().as_i64().unwrap() as usize;
 
     assert_eq!(
         get_version(&smap, "postgres", pg_v).unwrap(),